@@ -0,0 +1,2 @@
+-- Processing fee charged on top of the transaction amount
+ALTER TABLE transactions ADD COLUMN fee_amount BIGINT NOT NULL DEFAULT 0;
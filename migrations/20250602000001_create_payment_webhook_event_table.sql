@@ -0,0 +1,5 @@
+-- Provider event ids already applied, so redelivered callbacks are ignored
+CREATE TABLE IF NOT EXISTS payment_webhook_events (
+    event_id VARCHAR(255) PRIMARY KEY,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
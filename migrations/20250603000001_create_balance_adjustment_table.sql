@@ -0,0 +1,20 @@
+-- Audit trail for admin balance corrections
+CREATE TABLE IF NOT EXISTS balance_adjustments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL,
+    admin_id UUID NOT NULL,
+    delta BIGINT NOT NULL,
+    reason TEXT NOT NULL,
+    resulting_balance BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (admin_id) REFERENCES users(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_balance_adjustments_user_id ON balance_adjustments(user_id);
+
+-- An admin adjustment flagged allow_negative marks the balance as overdrawn on
+-- purpose; every other balance must still stay non-negative
+ALTER TABLE balances ADD COLUMN overdraft_allowed BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE balances DROP CONSTRAINT IF EXISTS check_balance_non_negative;
+ALTER TABLE balances ADD CONSTRAINT check_balance_non_negative CHECK (amount >= 0 OR overdraft_allowed);
@@ -0,0 +1,2 @@
+-- Optional payment window for payment-link transactions
+ALTER TABLE transactions ADD COLUMN expires_at TIMESTAMPTZ;
@@ -0,0 +1,2 @@
+-- Reporting category derived from the flow that created the transaction
+ALTER TABLE transactions ADD COLUMN category VARCHAR(20) NOT NULL DEFAULT 'ticket';
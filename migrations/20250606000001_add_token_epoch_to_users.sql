@@ -0,0 +1,2 @@
+-- Incremented on password change so older access tokens stop working
+ALTER TABLE users ADD COLUMN token_epoch INTEGER NOT NULL DEFAULT 0;
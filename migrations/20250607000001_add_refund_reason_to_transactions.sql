@@ -0,0 +1,3 @@
+-- Why a refunded transaction was refunded; NULL until it is
+ALTER TABLE transactions ADD COLUMN refund_reason_category VARCHAR(30);
+ALTER TABLE transactions ADD COLUMN refund_reason_note TEXT;
@@ -0,0 +1,3 @@
+-- Set when a refresh token is exchanged for its successor, so replaying it can
+-- be told apart from presenting one revoked by logout or the session cap
+ALTER TABLE refresh_tokens ADD COLUMN rotated_at TIMESTAMPTZ;
@@ -0,0 +1,3 @@
+-- Emails are matched case-insensitively on login and signup, so they must be
+-- unique that way too; this also lets those lookups use an index
+CREATE UNIQUE INDEX idx_users_email_lower ON users (LOWER(email));
@@ -0,0 +1,8 @@
+-- Single-row table; access tokens issued before tokens_not_before are
+-- rejected, bumped by the admin revoke-all endpoint
+CREATE TABLE auth_settings (
+    id BOOLEAN PRIMARY KEY DEFAULT TRUE CHECK (id),
+    tokens_not_before TIMESTAMPTZ
+);
+
+INSERT INTO auth_settings (id) VALUES (TRUE);
@@ -0,0 +1,73 @@
+use serde::Deserializer;
+use serde::de::{self, Visitor};
+use std::fmt;
+use serde_json::Value;
+
+/// Money fields that are rewritten as strings when a client asks for string amounts.
+/// `total` is left out because `Page` uses it for a row count.
+pub const AMOUNT_FIELDS: [&str; 8] = [
+    "amount",
+    "fee_amount",
+    "balance",
+    "delta",
+    "resulting_balance",
+    "subtotal",
+    "discount",
+    "fees",
+];
+
+struct AmountVisitor;
+
+impl Visitor<'_> for AmountVisitor {
+    type Value = i64;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "an integer amount or a numeric string")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<i64, E> {
+        Ok(value)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<i64, E> {
+        i64::try_from(value).map_err(|_| E::custom(format!("amount {} is out of range", value)))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<i64, E> {
+        value
+            .trim()
+            .parse::<i64>()
+            .map_err(|_| E::custom(format!("invalid amount '{}'", value)))
+    }
+}
+
+/// Accepts an amount either as a JSON number or as a quoted integer string,
+/// so clients that receive string amounts can send them back unchanged
+pub fn deserialize<'de, D>(deserializer: D) -> Result<i64, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_any(AmountVisitor)
+}
+
+/// Turns every integer under an `AMOUNT_FIELDS` key into its decimal string,
+/// keeping values beyond 2^53 intact for JavaScript clients
+pub fn stringify_amounts(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                match field {
+                    Value::Number(n) if AMOUNT_FIELDS.contains(&key.as_str()) && n.is_i64() => {
+                        *field = Value::String(n.to_string());
+                    }
+                    _ => stringify_amounts(field),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(stringify_amounts),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
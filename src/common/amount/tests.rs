@@ -0,0 +1,43 @@
+#[cfg(test)]
+mod tests {
+    use super::super::stringify_amounts;
+    use serde::Deserialize;
+    use serde_json::json;
+
+    #[derive(Deserialize)]
+    struct Payload {
+        #[serde(deserialize_with = "super::super::deserialize")]
+        amount: i64,
+    }
+
+    #[test]
+    fn test_string_mode_quotes_amounts() {
+        let large = 9_007_199_254_740_993i64; // 2^53 + 1
+        let mut body = json!({
+            "success": true,
+            "status_code": 200,
+            "data": { "amount": large, "fee_amount": 0, "items": [{ "balance": 10 }] }
+        });
+
+        stringify_amounts(&mut body);
+
+        assert_eq!(body["data"]["amount"], json!("9007199254740993"));
+        assert_eq!(body["data"]["fee_amount"], json!("0"));
+        assert_eq!(body["data"]["items"][0]["balance"], json!("10"));
+        assert_eq!(body["status_code"], json!(200));
+    }
+
+    #[test]
+    fn test_string_amount_deserializes_back_to_i64() {
+        let large = 9_007_199_254_740_993i64;
+        let mut body = json!({ "amount": large });
+        stringify_amounts(&mut body);
+
+        let parsed: Payload = serde_json::from_value(body).unwrap();
+        assert_eq!(parsed.amount, large);
+
+        let parsed: Payload = serde_json::from_str(r#"{"amount":1500}"#).unwrap();
+        assert_eq!(parsed.amount, 1500);
+        assert!(serde_json::from_str::<Payload>(r#"{"amount":"12abc"}"#).is_err());
+    }
+}
@@ -1,2 +1,7 @@
+pub mod amount;
 pub mod logging;
-pub mod response;
\ No newline at end of file
+pub mod pagination;
+pub mod password;
+pub mod response;
+pub mod sort;
+pub mod validation;
\ No newline at end of file
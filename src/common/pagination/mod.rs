@@ -0,0 +1,92 @@
+use serde::Serialize;
+
+use crate::error::ValidationError;
+
+pub const DEFAULT_PER_PAGE: usize = 20;
+pub const MAX_PER_PAGE: usize = 100;
+
+/// Validated `?page=&per_page=` values; pages are 1-based.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pagination {
+    page: usize,
+    per_page: usize,
+}
+
+impl Pagination {
+    pub fn parse(page: Option<usize>, per_page: Option<usize>) -> Result<Self, Vec<ValidationError>> {
+        let page = page.unwrap_or(1);
+        let per_page = per_page.unwrap_or(DEFAULT_PER_PAGE);
+
+        let mut errors = Vec::new();
+        if page == 0 {
+            errors.push(ValidationError {
+                field: "page".to_string(),
+                message: "must be at least 1".to_string(),
+            });
+        }
+        if per_page == 0 || per_page > MAX_PER_PAGE {
+            errors.push(ValidationError {
+                field: "per_page".to_string(),
+                message: format!("must be between 1 and {}", MAX_PER_PAGE),
+            });
+        }
+
+        if errors.is_empty() {
+            Ok(Self { page, per_page })
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// The first `per_page` items, for views that only show the head of a list
+    pub fn first(per_page: usize) -> Self {
+        Self { page: 1, per_page }
+    }
+
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    pub fn per_page(&self) -> usize {
+        self.per_page
+    }
+
+    /// Items to skip before the requested page starts
+    pub fn offset(&self) -> usize {
+        (self.page - 1) * self.per_page
+    }
+
+    /// Wraps a page already cut out by the data source, e.g. with SQL
+    /// `LIMIT`/`OFFSET`; `total` counts every matching item
+    pub fn page_of<T>(&self, items: Vec<T>, total: usize) -> Page<T> {
+        Page {
+            items,
+            page: self.page,
+            per_page: self.per_page,
+            total,
+        }
+    }
+
+    /// Cuts the requested page out of an already filtered and sorted list
+    pub fn apply<T>(&self, items: Vec<T>) -> Page<T> {
+        let total = items.len();
+        let items = items
+            .into_iter()
+            .skip(self.offset())
+            .take(self.per_page)
+            .collect();
+
+        self.page_of(items, total)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: usize,
+    pub per_page: usize,
+    pub total: usize,
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,33 @@
+#[cfg(test)]
+mod tests {
+    use super::super::{DEFAULT_PER_PAGE, Pagination};
+
+    #[test]
+    fn test_parse_defaults_to_first_page() {
+        let pagination = Pagination::parse(None, None).unwrap();
+        assert_eq!(pagination.page(), 1);
+        assert_eq!(pagination.per_page(), DEFAULT_PER_PAGE);
+    }
+
+    #[test]
+    fn test_parse_rejects_zero_page_and_oversized_per_page() {
+        let errors = Pagination::parse(Some(0), Some(1000)).unwrap_err();
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["page", "per_page"]);
+    }
+
+    #[test]
+    fn test_apply_slices_requested_page() {
+        let pagination = Pagination::parse(Some(2), Some(2)).unwrap();
+        let page = pagination.apply(vec![1, 2, 3, 4, 5]);
+
+        assert_eq!(page.items, vec![3, 4]);
+        assert_eq!(page.total, 5);
+    }
+
+    #[test]
+    fn test_offset_skips_earlier_pages() {
+        let pagination = Pagination::parse(Some(3), Some(10)).unwrap();
+        assert_eq!(pagination.offset(), 20);
+    }
+}
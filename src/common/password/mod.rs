@@ -0,0 +1,136 @@
+use crate::error::ValidationError;
+
+/// Minimum estimated entropy, in bits, a new password must reach by default
+pub const DEFAULT_MIN_ENTROPY_BITS: u32 = 50;
+
+/// Passwords attackers try first, lowercase and without the digits or
+/// symbols people usually tack on the end
+const COMMON_PASSWORDS: [&str; 40] = [
+    "password", "123456", "qwerty", "abc123", "letmein", "welcome", "monkey", "dragon",
+    "football", "baseball", "iloveyou", "admin", "login", "master", "sunshine", "princess",
+    "shadow", "superman", "trustno", "starwars", "passw0rd", "qwertyuiop", "asdfgh", "zxcvbn",
+    "hello", "freedom", "whatever", "summer", "winter", "spring", "autumn", "secret",
+    "michael", "charlie", "jordan", "hunter", "ranger", "computer", "internet", "eventsphere",
+];
+
+const KEYBOARD_ROWS: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// Estimated strength of a password and what would make it stronger
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordStrength {
+    pub entropy_bits: f64,
+    pub feedback: Vec<String>,
+}
+
+/// Rough zxcvbn-style estimate: known-common passwords score almost nothing,
+/// and characters that repeat or continue a sequence add far less than a
+/// fresh character from the password's alphabet would
+pub fn estimate_strength(password: &str) -> PasswordStrength {
+    let mut feedback = Vec::new();
+
+    let lowercase = password.to_lowercase();
+    let base = lowercase.trim_end_matches(|c: char| c.is_ascii_digit() || c.is_ascii_punctuation());
+    let common = [lowercase.as_str(), base].into_iter().find_map(|candidate| {
+        COMMON_PASSWORDS
+            .iter()
+            .position(|common| *common == candidate)
+            .map(|rank| (rank, lowercase.chars().count() - candidate.chars().count()))
+    });
+    if let Some((rank, suffix_len)) = common {
+        feedback.push("This is a very common password".to_string());
+        return PasswordStrength {
+            entropy_bits: ((rank + 1) as f64).log2() + suffix_len as f64 * 10f64.log2(),
+            feedback,
+        };
+    }
+
+    let chars: Vec<char> = password.chars().collect();
+    let per_char = (alphabet_size(&chars) as f64).log2();
+    let mut entropy_bits = 0.0;
+    let mut repeated = false;
+    let mut sequential = false;
+
+    for (i, &c) in chars.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|j| chars[j]);
+        entropy_bits += match previous {
+            Some(prev) if prev == c => {
+                repeated = true;
+                1.0
+            }
+            Some(prev) if continues_sequence(prev, c) => {
+                sequential = true;
+                2.0
+            }
+            _ => per_char,
+        };
+    }
+
+    if repeated {
+        feedback.push("Avoid repeated characters like \"aaa\"".to_string());
+    }
+    if sequential {
+        feedback.push("Avoid sequences like \"abc\", \"123\" or \"qwerty\"".to_string());
+    }
+
+    PasswordStrength { entropy_bits, feedback }
+}
+
+/// How many symbols an attacker must guess from, given the character classes used
+fn alphabet_size(chars: &[char]) -> u32 {
+    let mut size = 0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        size += 26;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        size += 10;
+    }
+    if chars.iter().any(|c| !c.is_ascii_alphanumeric()) {
+        size += 33;
+    }
+    size.max(1)
+}
+
+fn continues_sequence(prev: char, c: char) -> bool {
+    let (prev, c) = (prev.to_ascii_lowercase(), c.to_ascii_lowercase());
+    if prev.is_ascii_alphanumeric() && (c as i32 - prev as i32).abs() == 1 {
+        return true;
+    }
+    KEYBOARD_ROWS.iter().any(|row| {
+        row.find(prev)
+            .is_some_and(|i| row[i + 1..].starts_with(c) || row[..i].ends_with(c))
+    })
+}
+
+/// Entropy floor applied to new passwords at registration and password change
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PasswordPolicy {
+    min_entropy_bits: u32,
+}
+
+impl PasswordPolicy {
+    /// `0` turns the check off
+    pub fn new(min_entropy_bits: u32) -> Self {
+        Self { min_entropy_bits }
+    }
+
+    /// Rejects passwords estimated below the floor, with hints on how to fix them
+    pub fn check(&self, password: &str, field_name: &str) -> Result<(), ValidationError> {
+        let strength = estimate_strength(password);
+        if strength.entropy_bits >= self.min_entropy_bits as f64 {
+            return Ok(());
+        }
+
+        let mut feedback = strength.feedback;
+        feedback.push("Add another word or symbol".to_string());
+        Err(ValidationError {
+            field: field_name.to_string(),
+            message: format!("is too weak: {}", feedback.join(". ")),
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,37 @@
+#[cfg(test)]
+mod tests {
+    use super::super::{PasswordPolicy, estimate_strength};
+
+    #[test]
+    fn test_common_password_is_rejected_with_feedback() {
+        let policy = PasswordPolicy::new(50);
+
+        let err = policy.check("Password123!", "password").unwrap_err();
+        assert_eq!(err.field, "password");
+        assert!(err.message.contains("This is a very common password"), "{}", err.message);
+        assert!(err.message.ends_with("Add another word or symbol"), "{}", err.message);
+
+        assert!(policy.check("123456", "password").is_err());
+    }
+
+    #[test]
+    fn test_repeats_and_sequences_count_for_little() {
+        let strength = estimate_strength("aaaabbbb1234");
+        assert!(strength.entropy_bits < 30.0, "{}", strength.entropy_bits);
+        assert_eq!(strength.feedback.len(), 2);
+
+        assert!(estimate_strength("qwertyasdf").entropy_bits < estimate_strength("qpwoalskdj").entropy_bits);
+    }
+
+    #[test]
+    fn test_strong_passphrase_is_accepted() {
+        let policy = PasswordPolicy::new(50);
+        assert!(policy.check("correct horse battery staple", "password").is_ok());
+        assert!(policy.check("Tr0ub4dor&3", "password").is_ok());
+    }
+
+    #[test]
+    fn test_zero_minimum_accepts_anything() {
+        assert!(PasswordPolicy::new(0).check("password", "password").is_ok());
+    }
+}
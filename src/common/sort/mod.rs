@@ -0,0 +1,52 @@
+use crate::error::ValidationError;
+
+/// Validated `?sort=` value; a leading `-` sorts descending.
+///
+/// The key is always one of the endpoint's allow-listed `&'static str`s,
+/// so it can be placed in an `ORDER BY` clause as-is.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortOrder {
+    key: &'static str,
+    descending: bool,
+}
+
+impl SortOrder {
+    pub fn ascending(key: &'static str) -> Self {
+        Self { key, descending: false }
+    }
+
+    pub fn descending(key: &'static str) -> Self {
+        Self { key, descending: true }
+    }
+
+    pub fn parse(raw: &str, allowed: &[&'static str]) -> Result<Self, ValidationError> {
+        let raw = raw.trim();
+        let (name, descending) = match raw.strip_prefix('-') {
+            Some(name) => (name, true),
+            None => (raw, false),
+        };
+
+        match allowed.iter().find(|key| **key == name) {
+            Some(key) => Ok(Self { key, descending }),
+            None => Err(ValidationError {
+                field: "sort".to_string(),
+                message: format!("must be one of: {}", allowed.join(", ")),
+            }),
+        }
+    }
+
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn is_descending(&self) -> bool {
+        self.descending
+    }
+
+    pub fn to_sql(&self) -> String {
+        format!("{} {}", self.key, if self.descending { "DESC" } else { "ASC" })
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,24 @@
+#[cfg(test)]
+mod tests {
+    use super::super::SortOrder;
+
+    const ALLOWED: [&str; 2] = ["created_at", "amount"];
+
+    #[test]
+    fn test_parse_ascending_and_descending() {
+        let ascending = SortOrder::parse("amount", &ALLOWED).unwrap();
+        assert_eq!(ascending, SortOrder::ascending("amount"));
+        assert_eq!(ascending.to_sql(), "amount ASC");
+
+        let descending = SortOrder::parse("-created_at", &ALLOWED).unwrap();
+        assert!(descending.is_descending());
+        assert_eq!(descending.to_sql(), "created_at DESC");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_key() {
+        let err = SortOrder::parse("-password_hash", &ALLOWED).unwrap_err();
+        assert_eq!(err.field, "sort");
+        assert_eq!(err.message, "must be one of: created_at, amount");
+    }
+}
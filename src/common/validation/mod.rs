@@ -0,0 +1,39 @@
+use crate::error::ValidationError;
+
+/// Description limits shared by every create/update flow
+pub const DESCRIPTION_MIN_LENGTH: usize = 1;
+pub const DESCRIPTION_MAX_LENGTH: usize = 500;
+
+/// Display name limits for user profiles
+pub const NAME_MIN_LENGTH: usize = 1;
+pub const NAME_MAX_LENGTH: usize = 100;
+
+/// Check that a free-text field has between `min` and `max` characters once trimmed
+pub fn validate_text_field(
+    value: &str,
+    min: usize,
+    max: usize,
+    field_name: &str,
+) -> Result<(), ValidationError> {
+    let length = value.trim().chars().count();
+
+    let message = if length < min {
+        if min <= 1 {
+            "must not be empty".to_string()
+        } else {
+            format!("must be at least {} characters", min)
+        }
+    } else if length > max {
+        format!("must be at most {} characters", max)
+    } else {
+        return Ok(());
+    };
+
+    Err(ValidationError {
+        field: field_name.to_string(),
+        message,
+    })
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,29 @@
+#[cfg(test)]
+mod tests {
+    use super::super::validate_text_field;
+
+    #[test]
+    fn test_too_short_value_is_rejected() {
+        let err = validate_text_field("   ", 1, 10, "description").unwrap_err();
+        assert_eq!(err.field, "description");
+        assert_eq!(err.message, "must not be empty");
+
+        let err = validate_text_field("ab", 3, 10, "title").unwrap_err();
+        assert_eq!(err.field, "title");
+        assert_eq!(err.message, "must be at least 3 characters");
+    }
+
+    #[test]
+    fn test_too_long_value_is_rejected() {
+        let err = validate_text_field(&"x".repeat(11), 1, 10, "description").unwrap_err();
+        assert_eq!(err.field, "description");
+        assert_eq!(err.message, "must be at most 10 characters");
+    }
+
+    #[test]
+    fn test_valid_value_is_accepted() {
+        assert!(validate_text_field("Concert ticket", 1, 20, "description").is_ok());
+        // Limits are inclusive and count characters, not bytes
+        assert!(validate_text_field(&"é".repeat(10), 1, 10, "description").is_ok());
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests {
+    use super::super::{Config, Environment};
+    use crate::model::transaction::RefundPolicy;
+    use std::collections::HashMap;
+
+    fn lookup_from(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |key| vars.get(key).cloned()
+    }
+
+    #[test]
+    fn test_valid_production_config_parses() {
+        let config = Config::from_lookup(lookup_from(&[
+            ("ENVIRONMENT", "production"),
+            ("DATABASE_URL", "postgres://app@db:5432/eventsphere"),
+            ("JWT_SECRET", "prod-secret"),
+            ("JWT_REFRESH_SECRET", "prod-refresh-secret"),
+            ("PEPPER", "prod-pepper"),
+            ("PAYMENT_WEBHOOK_SECRET", "prod-webhook-secret"),
+            ("DB_POOL_SIZE", "20"),
+            ("MAX_ACTIVE_SESSIONS", "3"),
+            ("MAX_CONCURRENT_CHECKOUTS", "2"),
+            ("REFRESH_TOKEN_RENEW_WITHIN_HOURS", "24"),
+            ("ALLOWED_ORIGINS", "https://a.example, https://b.example"),
+        ]))
+        .expect("Config should be valid");
+
+        assert_eq!(config.environment, Environment::Production);
+        assert_eq!(config.db_pool_size, 20);
+        assert_eq!(config.max_active_sessions, Some(3));
+        assert_eq!(config.max_concurrent_checkouts, Some(2));
+        assert_eq!(config.refresh_token_renew_within_hours, Some(24));
+        assert_eq!(config.jwt_leeway_secs, 30);
+        assert_eq!(
+            config.cors.allowed_origins,
+            vec!["https://a.example".to_string(), "https://b.example".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_development_config_uses_defaults() {
+        let config = Config::from_lookup(lookup_from(&[])).expect("Defaults should be valid");
+
+        assert!(config.environment.is_dev());
+        assert_eq!(config.jwt_secret, "dev_jwt_secret_key");
+        assert_eq!(config.db_pool_size, 5);
+        assert_eq!(config.max_active_sessions, None);
+        assert_eq!(config.max_concurrent_checkouts, None);
+        assert_eq!(config.email_check_max_requests, 10);
+        assert_eq!(config.refresh_token_lifetime_days, 7);
+        assert_eq!(config.refresh_token_renew_within_hours, None);
+        assert!(config.registration_enabled);
+        assert_eq!(config.password_min_entropy_bits, 50);
+        assert!(config.enumeration_safe_errors);
+        assert_eq!(config.refund_policy, RefundPolicy::Original);
+        assert_eq!(config.payment_methods, vec!["credit_card", "bank_transfer", "e_wallet", "balance"]);
+    }
+
+    #[test]
+    fn test_dev_secret_in_production_is_rejected() {
+        let err = Config::from_lookup(lookup_from(&[
+            ("ENVIRONMENT", "production"),
+            ("DATABASE_URL", "postgres://app@db:5432/eventsphere"),
+            ("JWT_SECRET", "dev_jwt_secret_key"),
+            ("JWT_REFRESH_SECRET", "prod-refresh-secret"),
+            ("PEPPER", "prod-pepper"),
+            ("PAYMENT_WEBHOOK_SECRET", "prod-webhook-secret"),
+        ]))
+        .expect_err("Dev secret must not be accepted in production");
+
+        assert_eq!(err.problems.len(), 1);
+        assert!(err.problems[0].contains("JWT_SECRET"));
+    }
+
+    #[test]
+    fn test_fee_and_refund_settings_are_parsed() {
+        let config = Config::from_lookup(lookup_from(&[
+            ("TRANSACTION_FEES", "credit_card=2000;balance=500"),
+            ("BALANCE_FEE_EXEMPT", "true"),
+            ("REFUND_POLICY", "balance"),
+            ("PAYMENT_METHODS", "credit_card, paypal"),
+        ]))
+        .expect("Config should be valid");
+
+        assert_eq!(config.transaction_fees.fee_for("credit_card", 10_000), 2000);
+        assert_eq!(config.transaction_fees.fee_for("balance", 10_000), 0);
+        assert_eq!(config.refund_policy, RefundPolicy::Balance);
+        assert_eq!(config.payment_methods, vec!["credit_card", "paypal"]);
+    }
+
+    #[test]
+    fn test_all_problems_are_reported_together() {
+        let err = Config::from_lookup(lookup_from(&[
+            ("ENVIRONMENT", "production"),
+            ("DB_POOL_SIZE", "0"),
+            ("JWT_LEEWAY_SECS", "soon"),
+            ("REFUND_POLICY", "cash"),
+        ]))
+        .expect_err("Config should be invalid");
+
+        let message = err.to_string();
+        for key in ["DATABASE_URL", "DB_POOL_SIZE", "JWT_LEEWAY_SECS", "REFUND_POLICY", "JWT_SECRET", "JWT_REFRESH_SECRET", "PEPPER"] {
+            assert!(message.contains(key), "Missing problem for {}: {}", key, message);
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod stats_controller;
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,58 @@
+use rocket::{Route, State, get, http::Status, routes, serde::json::Json};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::controller::transaction::transaction_controller::{ApiResponse, ResponseData};
+use crate::repository::user::user_repo::UserRepository;
+use crate::service::transaction::transaction_service::TransactionService;
+
+pub fn admin_stats_routes() -> Vec<Route> {
+    routes![get_admin_stats_handler]
+}
+
+/// Platform-wide counters for the admin dashboard. Events and ads are owned
+/// by other services, so their counts aren't reported here.
+#[derive(Debug, Serialize)]
+pub struct AdminStatsResponse {
+    pub total_users: i64,
+    pub total_revenue: i64,
+    pub pending_transactions: i64,
+}
+
+impl ResponseData for AdminStatsResponse {}
+
+#[get("/admin/stats")]
+pub async fn get_admin_stats_handler(
+    token: crate::middleware::auth::JwtToken,
+    user_repository: &State<Arc<dyn UserRepository>>,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<AdminStatsResponse>>, Status> {
+    if !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    let total_users = match user_repository.count().await {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count users: {:?}", e);
+            return Ok(ApiResponse::error(500, &format!("Failed to load stats: {}", e)));
+        }
+    };
+
+    let stats = match service.get_transaction_stats().await {
+        Ok(stats) => stats,
+        Err(e) => {
+            eprintln!("Failed to get transaction stats: {:?}", e);
+            return Ok(ApiResponse::error(500, &format!("Failed to load stats: {}", e)));
+        }
+    };
+
+    Ok(ApiResponse::success(
+        "Stats found",
+        AdminStatsResponse {
+            total_users,
+            total_revenue: stats.revenue,
+            pending_transactions: stats.pending_count,
+        },
+    ))
+}
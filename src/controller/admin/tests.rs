@@ -0,0 +1,113 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::controller::admin::stats_controller::admin_stats_routes;
+use crate::model::user::{User, UserRole};
+use crate::repository::transaction::balance_repo::{DbBalanceRepository, InMemoryBalancePersistence};
+use crate::repository::transaction::transaction_repo::{
+    DbTransactionRepository, InMemoryTransactionPersistence,
+};
+use crate::repository::user::user_repo::{DbUserRepository, InMemoryUserPersistence, UserRepository};
+use crate::service::auth::auth_service::AuthService;
+use crate::service::transaction::balance_service::DefaultBalanceService;
+use crate::service::transaction::payment_service::MockPaymentService;
+use crate::service::transaction::tests::common::customer_refund_reason;
+use crate::service::transaction::transaction_service::{DefaultTransactionService, TransactionService};
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+
+struct AdminStatsFixture {
+    client: Client,
+    auth_service: Arc<AuthService>,
+    service: Arc<dyn TransactionService + Send + Sync>,
+    user_repository: Arc<dyn UserRepository>,
+}
+
+impl AdminStatsFixture {
+    async fn new() -> Self {
+        let auth_service = Arc::new(AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        ));
+        let service: Arc<dyn TransactionService + Send + Sync> = Arc::new(DefaultTransactionService::new(
+            Arc::new(DbTransactionRepository::new(InMemoryTransactionPersistence::new())),
+            Arc::new(DefaultBalanceService::new(Arc::new(DbBalanceRepository::new(
+                InMemoryBalancePersistence::new(),
+            )))),
+            Arc::new(MockPaymentService::new()),
+        ));
+        let user_repository: Arc<dyn UserRepository> =
+            Arc::new(DbUserRepository::new(InMemoryUserPersistence::new()));
+
+        let rocket = rocket::build()
+            .manage(auth_service.clone())
+            .manage(service.clone())
+            .manage(user_repository.clone())
+            .mount("/api", admin_stats_routes());
+        let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+        Self { client, auth_service, service, user_repository }
+    }
+
+    async fn user(&self, email: &str, role: UserRole) -> (User, String) {
+        let user = User::new("Stats User".to_string(), email.to_string(), "hash".to_string(), role);
+        self.user_repository.create(&user).await.unwrap();
+        let token = self.auth_service.generate_token(&user).await.unwrap().access_token;
+        (user, format!("Bearer {}", token))
+    }
+
+    async fn ticket(&self, user_id: Uuid, amount: i64) -> Uuid {
+        self.service
+            .create_transaction(user_id, Some(Uuid::new_v4()), amount, "Ticket".to_string(), "credit_card".to_string())
+            .await
+            .unwrap()
+            .id
+    }
+}
+
+#[tokio::test]
+async fn test_admin_stats_counts_seeded_data() {
+    let fixture = AdminStatsFixture::new().await;
+    let (_, admin_bearer) = fixture.user("admin@example.com", UserRole::Admin).await;
+    let (attendee, _) = fixture.user("attendee@example.com", UserRole::Attendee).await;
+    fixture.user("organizer@example.com", UserRole::Organizer).await;
+
+    // Two pending, one paid, one paid and then refunded
+    fixture.ticket(attendee.id, 1000).await;
+    fixture.ticket(attendee.id, 2000).await;
+    let paid = fixture.ticket(attendee.id, 5000).await;
+    let paid = fixture.service.process_payment(paid, None).await.unwrap();
+    let refunded = fixture.ticket(attendee.id, 7000).await;
+    fixture.service.process_payment(refunded, None).await.unwrap();
+    fixture.service.refund_transaction(refunded, customer_refund_reason(), None).await.unwrap();
+
+    let response = fixture
+        .client
+        .get("/api/admin/stats")
+        .header(Header::new("Authorization", admin_bearer))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let body: serde_json::Value = response.into_json().await.unwrap();
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+
+    let data = &body["data"];
+    assert_eq!(data["total_users"].as_i64().unwrap(), 3);
+    assert_eq!(data["pending_transactions"].as_i64().unwrap(), 2);
+    assert_eq!(data["total_revenue"].as_i64().unwrap(), paid.total_charged());
+}
+
+#[tokio::test]
+async fn test_admin_stats_requires_admin() {
+    let fixture = AdminStatsFixture::new().await;
+    let (_, bearer) = fixture.user("organizer@example.com", UserRole::Organizer).await;
+
+    let response = fixture
+        .client
+        .get("/api/admin/stats")
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Forbidden);
+}
@@ -1,9 +1,15 @@
-use crate::model::user::{User, UserRole};
+use crate::controller::json_body::JsonBody;
+use crate::common::validation::{NAME_MAX_LENGTH, NAME_MIN_LENGTH, validate_text_field};
+use crate::model::user::{Permission, User, UserRole};
+use chrono::{DateTime, Utc};
 use crate::repository::user::user_repo::UserRepository;
-use crate::service::auth::auth_service::{AuthService, TokenPair};
+use crate::service::auth::auth_service::{AuthService, InvalidResetToken, MalformedRefreshToken};
 use crate::service::transaction::balance_service::BalanceService;
+use rocket::http::{Cookie, CookieJar, SameSite};
+use rocket::request::{FromRequest, Outcome, Request};
 use rocket::{State, post, put, get, serde::json::Json, http::Status, routes};
 use serde::{Deserialize, Serialize};
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -14,7 +20,16 @@ pub fn auth_routes() -> Vec<rocket::Route> {
         get_user_handler,
         update_profile_handler,
         refresh_token_handler,
-        get_current_user_handler
+        logout_handler,
+        logout_all_handler,
+        get_current_user_handler,
+        revoke_all_tokens_handler,
+        forgot_password_handler,
+        reset_password_handler,
+        validate_token_handler,
+        get_permissions_handler,
+        email_available_handler,
+        change_password_handler
     ]
 }
 
@@ -70,7 +85,8 @@ pub struct LoginRequest {
 #[derive(Debug, Serialize)]
 pub struct AuthResponse {
     pub token: String,
-    pub refresh_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
     pub user_id: Uuid,
     pub name: String,
     pub email: String,
@@ -79,9 +95,95 @@ pub struct AuthResponse {
 
 #[derive(Debug, Deserialize)]
 pub struct RefreshTokenRequest {
+    #[serde(default)]
     pub refresh_token: String,
 }
 
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenValidationResponse {
+    pub valid: bool,
+    pub expires_at: String,
+    pub user_id: String,
+    pub role: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PermissionsResponse {
+    pub role: String,
+    pub permissions: Vec<Permission>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmailAvailabilityResponse {
+    pub available: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: String,
+    pub new_password: String,
+}
+
+pub const FORGOT_PASSWORD_MESSAGE: &str =
+    "If the email is registered, password reset instructions have been sent";
+
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+
+/// How the client wants to receive its refresh token. Browser clients send
+/// `X-Client-Type: web` and get it as an HttpOnly cookie; everything else gets
+/// it in the JSON body.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientMode {
+    Web,
+    Api,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ClientMode {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.headers().get_one("X-Client-Type") {
+            Some(kind) if kind.eq_ignore_ascii_case("web") => Outcome::Success(ClientMode::Web),
+            _ => Outcome::Success(ClientMode::Api),
+        }
+    }
+}
+
+/// Returns the refresh token for the response body, or sets it as a cookie and
+/// returns `None` for web clients.
+fn deliver_refresh_token(
+    mode: ClientMode,
+    cookies: &CookieJar<'_>,
+    refresh_token: String,
+) -> Option<String> {
+    match mode {
+        ClientMode::Api => Some(refresh_token),
+        ClientMode::Web => {
+            let cookie = Cookie::build((REFRESH_TOKEN_COOKIE, refresh_token))
+                .http_only(true)
+                .secure(true)
+                .same_site(SameSite::Strict)
+                .path("/");
+            cookies.add(cookie);
+            None
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
     pub id: Uuid,
@@ -93,6 +195,12 @@ pub struct UserResponse {
     pub last_login: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: String,
+    pub new_password: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct UpdateProfileRequest {
     pub name: Option<String>,
@@ -101,12 +209,20 @@ pub struct UpdateProfileRequest {
 
 #[post("/auth/register", data = "<req>")]
 pub async fn register_handler(
-    req: Json<RegisterRequest>,
+    req: JsonBody<RegisterRequest>,
+    client_mode: ClientMode,
+    cookies: &CookieJar<'_>,
     user_repository: &State<Arc<dyn UserRepository>>,
     auth_service: &State<Arc<AuthService>>,
     balance_service: &State<Arc<dyn BalanceService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<AuthResponse>>, Status> {let repo = user_repository.inner();
     let service = auth_service.inner();
+    if !service.registration_enabled() {
+        return Ok(ApiResponse::error(403, "Registrations are currently closed"));
+    }
+    if let Err(e) = service.check_password_strength(&req.password, "password") {
+        return Ok(ApiResponse::error(400, &format!("Password {}", e.message)));
+    }
     if let Ok(Some(_)) = repo.find_by_email(&req.email).await {
         return Ok(ApiResponse::error(400, "Email already registered"));
     }
@@ -137,7 +253,7 @@ pub async fn register_handler(
     
     Ok(ApiResponse::success("Registration successful", AuthResponse {
         token: token_pair.access_token,
-        refresh_token: token_pair.refresh_token,
+        refresh_token: deliver_refresh_token(client_mode, cookies, token_pair.refresh_token),
         user_id: user.id,
         name: user.name,
         email: user.email,
@@ -147,7 +263,9 @@ pub async fn register_handler(
 
 #[post("/auth/login", data = "<req>")]
 pub async fn login_handler(
-    req: Json<LoginRequest>,
+    req: JsonBody<LoginRequest>,
+    client_mode: ClientMode,
+    cookies: &CookieJar<'_>,
     user_repository: &State<Arc<dyn UserRepository>>,
     auth_service: &State<Arc<AuthService>>,
 ) -> Result<Json<ApiResponse<AuthResponse>>, Status> {
@@ -172,7 +290,7 @@ pub async fn login_handler(
     
     Ok(ApiResponse::success("Login successful", AuthResponse {
         token: token_pair.access_token,
-        refresh_token: token_pair.refresh_token,
+        refresh_token: deliver_refresh_token(client_mode, cookies, token_pair.refresh_token),
         user_id: updated_user.id,
         name: updated_user.name,
         email: updated_user.email,
@@ -219,7 +337,7 @@ pub async fn get_user_handler(
 pub async fn update_profile_handler(
     token: crate::middleware::auth::JwtToken,
     user_id: &str,
-    req: Json<UpdateProfileRequest>,
+    req: JsonBody<UpdateProfileRequest>,
     user_repository: &State<Arc<dyn UserRepository>>,
 ) -> Result<Json<ApiResponse<UserResponse>>, Status> {
     let uuid = match Uuid::parse_str(user_id) {
@@ -241,13 +359,24 @@ pub async fn update_profile_handler(
         _ => return Ok(ApiResponse::error(404, "User not found")),
     };
     if let Some(ref new_email) = req.email {
-        if new_email != &user.email {
-            if let Ok(Some(_)) = repo.find_by_email(new_email).await {
-                return Ok(ApiResponse::error(400, "Email already in use"));
-            }
+        // Lookups ignore case, so the match may be this same user re-casing their address
+        if let Ok(Some(existing)) = repo.find_by_email(new_email).await
+            && existing.id != user.id
+        {
+            return Ok(ApiResponse::error(400, "Email already in use"));
         }
     }
-    user.update_profile(req.name.clone(), req.email.clone());
+    let name = match &req.name {
+        Some(name) => match validate_text_field(name, NAME_MIN_LENGTH, NAME_MAX_LENGTH, "name") {
+            Ok(()) => Some(name.trim().to_string()),
+            Err(_) if name.trim().is_empty() => {
+                return Ok(ApiResponse::error(400, "Name cannot be empty"));
+            }
+            Err(e) => return Ok(ApiResponse::error(400, &format!("Name {}", e.message))),
+        },
+        None => None,
+    };
+    user.update_profile(name, req.email.clone());
     if let Err(_) = repo.update(&user).await {
         return Ok(ApiResponse::error(500, "Failed to update user"));
     }
@@ -262,18 +391,150 @@ pub async fn update_profile_handler(
     }))
 }
 
+/// Changing the password bumps the user's token epoch, so every access token
+/// issued before now stops working, and revokes all refresh tokens. The caller
+/// gets a fresh pair to stay signed in.
+#[put("/auth/password", data = "<req>")]
+pub async fn change_password_handler(
+    token: crate::middleware::auth::JwtToken,
+    req: JsonBody<ChangePasswordRequest>,
+    client_mode: ClientMode,
+    cookies: &CookieJar<'_>,
+    user_repository: &State<Arc<dyn UserRepository>>,
+    auth_service: &State<Arc<AuthService>>,
+) -> Result<Json<ApiResponse<TokenResponse>>, Status> {
+    let user_id = match Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    let repo = user_repository.inner();
+    let mut user = match repo.find_by_id(user_id).await {
+        Ok(Some(u)) => u,
+        _ => return Ok(ApiResponse::error(404, "User not found")),
+    };
+
+    match auth_service.verify_password(&user.password, &req.current_password) {
+        Ok(true) => {}
+        _ => return Ok(ApiResponse::error(400, "Current password is incorrect")),
+    }
+    if req.new_password.trim().is_empty() {
+        return Ok(ApiResponse::error(400, "New password cannot be empty"));
+    }
+    if let Err(e) = auth_service.check_password_strength(&req.new_password, "new_password") {
+        return Ok(ApiResponse::error(400, &format!("New password {}", e.message)));
+    }
+
+    let hashed_password = match auth_service.hash_password(&req.new_password) {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("Failed to hash password: {:?}", e);
+            return Ok(ApiResponse::error(500, "Failed to hash password"));
+        }
+    };
+    user.update_password(hashed_password);
+    if repo.update(&user).await.is_err() {
+        return Ok(ApiResponse::error(500, "Failed to update password"));
+    }
+
+    if let Err(e) = auth_service.logout_all(user.id).await {
+        eprintln!("Failed to revoke refresh tokens after password change: {:?}", e);
+    }
+
+    match auth_service.generate_token(&user).await {
+        Ok(token_pair) => Ok(ApiResponse::success("Password changed", TokenResponse {
+            access_token: token_pair.access_token,
+            refresh_token: deliver_refresh_token(client_mode, cookies, token_pair.refresh_token),
+            expires_in: token_pair.expires_in,
+        })),
+        Err(_) => Ok(ApiResponse::error(500, "Failed to generate token")),
+    }
+}
+
 #[post("/auth/refresh", data = "<req>")]
 pub async fn refresh_token_handler(
-    req: Json<RefreshTokenRequest>,
+    req: JsonBody<RefreshTokenRequest>,
+    client_mode: ClientMode,
+    cookies: &CookieJar<'_>,
     auth_service: &State<Arc<AuthService>>,
-) -> Result<Json<ApiResponse<TokenPair>>, Status> {
+) -> Result<Json<ApiResponse<TokenResponse>>, Status> {
     let service = auth_service.inner();
-    match service.refresh_access_token(&req.refresh_token).await {
-        Ok(token_pair) => Ok(ApiResponse::success("Token refreshed", token_pair)),
+    // Web clients never see their refresh token, so fall back to the cookie
+    let presented_token = if req.refresh_token.is_empty() {
+        match cookies.get(REFRESH_TOKEN_COOKIE) {
+            Some(cookie) => cookie.value().to_string(),
+            None => return Ok(ApiResponse::error(400, "Invalid refresh token")),
+        }
+    } else {
+        req.refresh_token.clone()
+    };
+
+    match service.refresh_access_token(&presented_token).await {
+        Ok(token_pair) => Ok(ApiResponse::success("Token refreshed", TokenResponse {
+            access_token: token_pair.access_token,
+            refresh_token: deliver_refresh_token(client_mode, cookies, token_pair.refresh_token),
+            expires_in: token_pair.expires_in,
+        })),
         Err(_) => Ok(ApiResponse::error(400, "Invalid refresh token")),
     }
 }
 
+/// Revokes the presented refresh token; logging out twice is not an error
+#[post("/auth/logout", data = "<req>")]
+pub async fn logout_handler(
+    req: JsonBody<RefreshTokenRequest>,
+    cookies: &CookieJar<'_>,
+    auth_service: &State<Arc<AuthService>>,
+) -> Result<Json<ApiResponse<()>>, Status> {
+    // Web clients never see their refresh token, so fall back to the cookie
+    let presented_token = if req.refresh_token.is_empty() {
+        match cookies.get(REFRESH_TOKEN_COOKIE) {
+            Some(cookie) => cookie.value().to_string(),
+            None => return Ok(ApiResponse::error(400, "Invalid refresh token")),
+        }
+    } else {
+        req.refresh_token.clone()
+    };
+
+    match auth_service.logout(&presented_token).await {
+        Ok(()) => {
+            cookies.remove(Cookie::build(REFRESH_TOKEN_COOKIE).path("/"));
+            Ok(ApiResponse::success("Logged out", ()))
+        }
+        Err(e) if e.downcast_ref::<MalformedRefreshToken>().is_some() => {
+            Ok(ApiResponse::error(400, "Invalid refresh token"))
+        }
+        Err(e) => {
+            eprintln!("Failed to log out: {:?}", e);
+            Ok(ApiResponse::error(500, "Failed to log out"))
+        }
+    }
+}
+
+/// Revokes every refresh token the caller holds, signing out all their devices
+#[post("/auth/logout-all")]
+pub async fn logout_all_handler(
+    token: crate::middleware::auth::JwtToken,
+    cookies: &CookieJar<'_>,
+    auth_service: &State<Arc<AuthService>>,
+) -> Result<Json<ApiResponse<u64>>, Status> {
+    let user_id = match Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    match auth_service.logout_all(user_id).await {
+        Ok(revoked) => {
+            cookies.remove(Cookie::build(REFRESH_TOKEN_COOKIE).path("/"));
+            Ok(ApiResponse::success("Logged out of all sessions", revoked))
+        }
+        Err(e) => {
+            eprintln!("Failed to log out of all sessions: {:?}", e);
+            Ok(ApiResponse::error(500, "Failed to log out"))
+        }
+    }
+}
+
 #[get("/auth/me")]
 pub async fn get_current_user_handler(
     token: crate::middleware::auth::JwtToken,
@@ -300,3 +561,134 @@ pub async fn get_current_user_handler(
         last_login: user.last_login.map(|dt| dt.to_rfc3339()),
     }))
 }
+
+/// Lets clients check a stored access token on load. Invalid, expired or
+/// revoked tokens are rejected with 401 by the `JwtToken` guard.
+#[get("/auth/validate-token")]
+pub async fn validate_token_handler(
+    token: crate::middleware::auth::JwtToken,
+) -> Result<Json<ApiResponse<TokenValidationResponse>>, Status> {
+    let expires_at = match DateTime::<Utc>::from_timestamp(token.expires_at, 0) {
+        Some(dt) => dt,
+        None => return Err(Status::Unauthorized),
+    };
+
+    Ok(ApiResponse::success("Token is valid", TokenValidationResponse {
+        valid: true,
+        expires_at: expires_at.to_rfc3339(),
+        user_id: token.user_id,
+        role: token.role,
+    }))
+}
+
+/// Capabilities of the caller's role, so the frontend can mirror the server's
+/// authorization rules instead of hardcoding them. Unknown roles get none.
+#[get("/auth/permissions")]
+pub async fn get_permissions_handler(
+    token: crate::middleware::auth::JwtToken,
+) -> Result<Json<ApiResponse<PermissionsResponse>>, Status> {
+    let permissions = match token.role.parse::<UserRole>() {
+        Ok(role) => role.permissions().to_vec(),
+        Err(_) => Vec::new(),
+    };
+
+    Ok(ApiResponse::success("Permissions retrieved", PermissionsResponse {
+        role: token.role,
+        permissions,
+    }))
+}
+
+#[post("/admin/revoke-all-tokens?<bump_epoch>")]
+pub async fn revoke_all_tokens_handler(
+    token: crate::middleware::auth::JwtToken,
+    bump_epoch: Option<bool>,
+    auth_service: &State<Arc<AuthService>>,
+) -> Result<Json<ApiResponse<()>>, Status> {
+    if !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    match auth_service.revoke_all_tokens(bump_epoch.unwrap_or(false)).await {
+        Ok(_) => Ok(ApiResponse::success("All tokens revoked", ())),
+        Err(e) => {
+            eprintln!("Failed to revoke tokens: {:?}", e);
+            Ok(ApiResponse::error(500, "Failed to revoke tokens"))
+        }
+    }
+}
+
+#[post("/auth/forgot-password", data = "<req>")]
+pub async fn forgot_password_handler(
+    req: JsonBody<ForgotPasswordRequest>,
+    client_ip: Option<IpAddr>,
+    auth_service: &State<Arc<AuthService>>,
+) -> Result<Json<ApiResponse<()>>, Status> {
+    if !auth_service.allow_password_reset_request(&req.email, client_ip) {
+        return Ok(ApiResponse::error(429, "Too many password reset requests, try again later"));
+    }
+
+    // Nothing delivers reset emails yet, so the token is only stored
+    if let Err(e) = auth_service.create_reset_token(&req.email).await {
+        eprintln!("Failed to create password reset token: {:?}", e);
+    }
+
+    // The response must not depend on whether the email belongs to an account
+    Ok(ApiResponse::success(FORGOT_PASSWORD_MESSAGE, ()))
+}
+
+/// Sets a new password with a token from forgot-password. The token works
+/// once, and all of the user's existing sessions are signed out.
+#[post("/auth/reset-password", data = "<req>")]
+pub async fn reset_password_handler(
+    req: JsonBody<ResetPasswordRequest>,
+    auth_service: &State<Arc<AuthService>>,
+) -> Result<Json<ApiResponse<()>>, Status> {
+    if req.new_password.trim().is_empty() {
+        return Ok(ApiResponse::error(400, "New password cannot be empty"));
+    }
+    if let Err(e) = auth_service.check_password_strength(&req.new_password, "new_password") {
+        return Ok(ApiResponse::error(400, &format!("New password {}", e.message)));
+    }
+
+    match auth_service.reset_password(&req.token, &req.new_password).await {
+        Ok(()) => Ok(ApiResponse::success("Password reset", ())),
+        Err(e) if e.downcast_ref::<InvalidResetToken>().is_some() => {
+            Ok(ApiResponse::error(400, "Invalid or expired reset token"))
+        }
+        Err(e) => {
+            eprintln!("Failed to reset password: {:?}", e);
+            Ok(ApiResponse::error(500, "Failed to reset password"))
+        }
+    }
+}
+
+/// Signup-form check. Only says whether the address is free; nothing about
+/// the existing account is revealed.
+#[get("/auth/email-available?<email>")]
+pub async fn email_available_handler(
+    email: Option<String>,
+    remote: Option<SocketAddr>,
+    user_repository: &State<Arc<dyn UserRepository>>,
+    auth_service: &State<Arc<AuthService>>,
+) -> Result<Json<ApiResponse<EmailAvailabilityResponse>>, Status> {
+    // Keyed on the peer address: a forwarded-IP header is client-chosen and
+    // would let a prober reset the limit on every request
+    if !auth_service.allow_email_check(remote.map(|addr| addr.ip())) {
+        return Ok(ApiResponse::error(429, "Too many email checks, try again later"));
+    }
+
+    let email = email.unwrap_or_default().trim().to_lowercase();
+    if email.is_empty() {
+        return Ok(ApiResponse::error(400, "Email is required"));
+    }
+
+    match user_repository.find_by_email(&email).await {
+        Ok(existing) => Ok(ApiResponse::success("Email availability checked", EmailAvailabilityResponse {
+            available: existing.is_none(),
+        })),
+        Err(e) => {
+            eprintln!("Failed to check email availability: {:?}", e);
+            Ok(ApiResponse::error(500, "Failed to check email availability"))
+        }
+    }
+}
@@ -1,6 +1,10 @@
 use super::auth_controller::auth_routes;
+use crate::common::password::PasswordPolicy;
+use crate::model::auth::{PasswordResetToken, RefreshToken};
 use crate::model::transaction::Balance;
 use crate::model::user::User;
+use crate::repository::auth::password_reset_repo::PasswordResetTokenRepository;
+use crate::repository::auth::token_repo::TokenRepository;
 use crate::repository::user::user_repo::UserRepository;
 use crate::service::auth::auth_service::AuthService;
 use crate::service::transaction::balance_service::BalanceService;
@@ -109,6 +113,7 @@ mock! {
         async fn update(&self, user: &User) -> Result<(), Box<dyn Error>>;
         async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error>>;
         async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>>;
+        async fn count(&self) -> Result<i64, Box<dyn Error>>;
     }
 }
 
@@ -137,8 +142,8 @@ impl UserRepository for InMemoryUserRepo {
         let users_by_email = self.users_by_email.lock().unwrap();
         let users = self.users.lock().unwrap();
 
-        match users_by_email.get(email) {
-            Some(user_id) => Ok(users.get(user_id).cloned()),
+        match users_by_email.iter().find(|(stored, _)| stored.eq_ignore_ascii_case(email)) {
+            Some((_, user_id)) => Ok(users.get(user_id).cloned()),
             None => Ok(None),
         }
     }
@@ -185,6 +190,134 @@ impl UserRepository for InMemoryUserRepo {
         let users = self.users.lock().unwrap();
         Ok(users.values().cloned().collect())
     }
+
+    async fn count(&self) -> Result<i64, Box<dyn Error>> {
+        Ok(self.users.lock().unwrap().len() as i64)
+    }
+}
+
+struct InMemoryTokenRepo {
+    tokens: Mutex<Vec<RefreshToken>>,
+    not_before: Mutex<Option<chrono::DateTime<chrono::Utc>>>,
+}
+
+impl InMemoryTokenRepo {
+    fn new() -> Self {
+        Self {
+            tokens: Mutex::new(Vec::new()),
+            not_before: Mutex::new(None),
+        }
+    }
+}
+
+#[async_trait]
+impl TokenRepository for InMemoryTokenRepo {
+    async fn create(&self, token: &RefreshToken) -> Result<(), Box<dyn Error>> {
+        self.tokens.lock().unwrap().push(token.clone());
+        Ok(())
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<Option<RefreshToken>, Box<dyn Error>> {
+        let tokens = self.tokens.lock().unwrap();
+        Ok(tokens.iter().find(|t| t.token == token).cloned())
+    }
+
+    async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<RefreshToken>, Box<dyn Error>> {
+        let tokens = self.tokens.lock().unwrap();
+        Ok(tokens.iter().filter(|t| t.user_id == user_id).cloned().collect())
+    }
+
+    async fn revoke(&self, token_id: Uuid) -> Result<(), Box<dyn Error>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        if let Some(token) = tokens.iter_mut().find(|t| t.id == token_id) {
+            token.is_revoked = true;
+        }
+        Ok(())
+    }
+
+    async fn rotate(&self, token_id: Uuid, successor: &RefreshToken) -> Result<bool, Box<dyn Error>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        match tokens.iter_mut().find(|t| t.id == token_id && !t.is_revoked) {
+            Some(token) => {
+                token.is_revoked = true;
+                token.rotated_at = Some(chrono::Utc::now());
+            }
+            None => return Ok(false),
+        }
+        tokens.push(successor.clone());
+        Ok(true)
+    }
+
+    async fn update_expiry(&self, token_id: Uuid, expires_at: chrono::DateTime<chrono::Utc>) -> Result<(), Box<dyn Error>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        if let Some(token) = tokens.iter_mut().find(|t| t.id == token_id) {
+            token.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<u64, Box<dyn Error>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        let mut revoked = 0;
+        for token in tokens.iter_mut().filter(|t| t.user_id == user_id && !t.is_revoked) {
+            token.is_revoked = true;
+            revoked += 1;
+        }
+        Ok(revoked)
+    }
+
+    async fn revoke_all(&self) -> Result<(), Box<dyn Error>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        for token in tokens.iter_mut() {
+            token.is_revoked = true;
+        }
+        Ok(())
+    }
+
+    async fn tokens_not_before(&self) -> Result<Option<chrono::DateTime<chrono::Utc>>, Box<dyn Error>> {
+        Ok(*self.not_before.lock().unwrap())
+    }
+
+    async fn set_tokens_not_before(&self, at: chrono::DateTime<chrono::Utc>) -> Result<(), Box<dyn Error>> {
+        *self.not_before.lock().unwrap() = Some(at);
+        Ok(())
+    }
+}
+
+struct InMemoryResetTokenRepo {
+    tokens: Mutex<Vec<PasswordResetToken>>,
+}
+
+impl InMemoryResetTokenRepo {
+    fn new() -> Self {
+        Self {
+            tokens: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl PasswordResetTokenRepository for InMemoryResetTokenRepo {
+    async fn create(&self, token: &PasswordResetToken) -> Result<(), Box<dyn Error>> {
+        self.tokens.lock().unwrap().push(token.clone());
+        Ok(())
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<Option<PasswordResetToken>, Box<dyn Error>> {
+        let tokens = self.tokens.lock().unwrap();
+        Ok(tokens.iter().find(|t| t.token == token).cloned())
+    }
+
+    async fn mark_used(&self, token_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        let mut tokens = self.tokens.lock().unwrap();
+        match tokens.iter_mut().find(|t| t.id == token_id && t.used_at.is_none()) {
+            Some(token) => {
+                token.used_at = Some(chrono::Utc::now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
 }
 
 fn setup_test_dependencies() -> (
@@ -246,6 +379,97 @@ async fn test_register_success() {
     assert!(!data.get("token").unwrap().as_str().unwrap().is_empty());
 }
 
+async fn register_with_signups(enabled: bool) -> rocket::serde::json::Value {
+    let (user_repo, _, balance_service) = setup_test_dependencies();
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_registration_enabled(enabled),
+    );
+
+    let rocket = rocket::build()
+        .manage(user_repo)
+        .manage(auth_service)
+        .manage(balance_service)
+        .mount("/", auth_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let response = client
+        .post("/auth/register")
+        .header(rocket::http::ContentType::JSON)
+        .body(r#"{"name":"Invitee","email":"invitee@example.com","password":"password"}"#)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_register_rejected_when_registrations_closed() {
+    let body = register_with_signups(false).await;
+
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 403);
+    assert_eq!(body["message"].as_str().unwrap(), "Registrations are currently closed");
+}
+
+#[tokio::test]
+async fn test_register_allowed_when_registrations_open() {
+    let body = register_with_signups(true).await;
+
+    assert!(body["success"].as_bool().unwrap());
+    assert_eq!(body["data"]["email"].as_str().unwrap(), "invitee@example.com");
+}
+
+async fn register_with_password_policy(password: &str) -> rocket::serde::json::Value {
+    let (user_repo, _, balance_service) = setup_test_dependencies();
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_password_policy(PasswordPolicy::new(50)),
+    );
+
+    let rocket = rocket::build()
+        .manage(user_repo)
+        .manage(auth_service)
+        .manage(balance_service)
+        .mount("/", auth_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let response = client
+        .post("/auth/register")
+        .header(rocket::http::ContentType::JSON)
+        .body(format!(r#"{{"name":"Policy","email":"policy@example.com","password":"{}"}}"#, password))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_register_rejects_weak_password_with_feedback() {
+    let body = register_with_password_policy("password123").await;
+
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.starts_with("Password is too weak"), "{}", message);
+    assert!(message.contains("Add another word or symbol"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_register_accepts_strong_passphrase() {
+    let body = register_with_password_policy("correct horse battery staple").await;
+
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+}
+
 #[tokio::test]
 async fn test_register_duplicate_email() {
     let (user_repo, auth_service, balance_service) = setup_test_dependencies();
@@ -522,6 +746,89 @@ async fn test_update_profile() {
     assert_eq!(data["email"].as_str().unwrap(), "updated@example.com");
 }
 
+async fn update_profile_name(name: &str) -> rocket::serde::json::Value {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let register_body: rocket::serde::json::Value = client
+        .post("/auth/register")
+        .header(rocket::http::ContentType::JSON)
+        .body(r#"{"name":"Original Name","email":"rename@example.com","password":"password"}"#)
+        .dispatch()
+        .await
+        .into_json()
+        .await
+        .unwrap();
+    let user_id = register_body["data"]["user_id"].as_str().unwrap();
+    let token = register_body["data"]["token"].as_str().unwrap();
+
+    let response = client
+        .put(format!("/auth/profile/{}", user_id))
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", token)))
+        .body(serde_json::json!({ "name": name }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_update_profile_rejects_blank_name() {
+    let body = update_profile_name("   ").await;
+
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+    assert_eq!(body["message"].as_str().unwrap(), "Name cannot be empty");
+}
+
+#[tokio::test]
+async fn test_update_profile_trims_valid_name() {
+    let body = update_profile_name("  New Name  ").await;
+
+    assert!(body["success"].as_bool().unwrap());
+    assert_eq!(body["data"]["name"].as_str().unwrap(), "New Name");
+    assert_eq!(body["data"]["email"].as_str().unwrap(), "rename@example.com");
+}
+
+#[tokio::test]
+async fn test_update_profile_allows_recasing_own_email() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (token, _) = register_for_tokens(&client, "recase@example.com", "Attendee").await;
+    let user_id = auth_service.verify_token(&token).await.unwrap();
+
+    let response = client
+        .put(format!("/auth/profile/{}", user_id))
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", token)))
+        .body(r#"{"email":"ReCase@example.com"}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+    assert_eq!(body["data"]["email"].as_str().unwrap(), "ReCase@example.com");
+}
+
 #[tokio::test]
 async fn test_login_with_incorrect_password() {
     let (user_repo, auth_service, balance_service) = setup_test_dependencies();
@@ -952,3 +1259,834 @@ async fn test_retrieve_user_balance() {
     let balance = balance_option.unwrap();
     assert_eq!(balance.amount, 0);
 }
+
+#[tokio::test]
+async fn test_login_web_mode_sets_refresh_cookie() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let register_json = r#"{
+        "name":"Web Login Test",
+        "email":"web_login@example.com",
+        "password":"password123",
+        "role":null
+    }"#;
+
+    client
+        .post("/auth/register")
+        .header(rocket::http::ContentType::JSON)
+        .body(register_json)
+        .dispatch()
+        .await;
+
+    let login_json = r#"{
+        "email":"web_login@example.com",
+        "password":"password123"
+    }"#;
+
+    let response = client
+        .post("/auth/login")
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("X-Client-Type", "web"))
+        .body(login_json)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+
+    let cookie = response
+        .cookies()
+        .get("refresh_token")
+        .expect("Refresh token cookie should be set")
+        .clone();
+    assert!(!cookie.value().is_empty());
+    assert_eq!(cookie.http_only(), Some(true));
+    assert_eq!(cookie.secure(), Some(true));
+    assert_eq!(cookie.same_site(), Some(rocket::http::SameSite::Strict));
+
+    let response_body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(response_body["success"].as_bool().unwrap());
+    assert!(!response_body["data"]["token"].as_str().unwrap().is_empty());
+    assert!(
+        response_body["data"].get("refresh_token").is_none(),
+        "Web clients must not receive the refresh token in the body"
+    );
+}
+
+#[tokio::test]
+async fn test_refresh_token_web_mode_reads_cookie() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let register_json = r#"{
+        "name":"Web Refresh Test",
+        "email":"web_refresh@example.com",
+        "password":"password123",
+        "role":null
+    }"#;
+
+    let response = client
+        .post("/auth/register")
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("X-Client-Type", "web"))
+        .body(register_json)
+        .dispatch()
+        .await;
+
+    assert!(response.cookies().get("refresh_token").is_some());
+    let register_body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(register_body["data"].get("refresh_token").is_none());
+
+    // The tracked client replays the cookie, so the body can omit the token
+    let response = client
+        .post("/auth/refresh")
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("X-Client-Type", "web"))
+        .body("{}")
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.cookies().get("refresh_token").is_some());
+
+    let refresh_body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(refresh_body["success"].as_bool().unwrap());
+    assert!(!refresh_body["data"]["access_token"].as_str().unwrap().is_empty());
+    assert!(refresh_body["data"].get("refresh_token").is_none());
+}
+
+async fn register_for_tokens(client: &Client, email: &str, role: &str) -> (String, String) {
+    let register_json = format!(
+        r#"{{"name":"Revoke Test","email":"{}","password":"password123","role":"{}"}}"#,
+        email, role
+    );
+
+    let response = client
+        .post("/auth/register")
+        .header(rocket::http::ContentType::JSON)
+        .body(register_json)
+        .dispatch()
+        .await;
+
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    (
+        body["data"]["token"].as_str().unwrap().to_string(),
+        body["data"]["refresh_token"].as_str().unwrap().to_string(),
+    )
+}
+
+#[tokio::test]
+async fn test_revoke_all_tokens_invalidates_refresh_tokens() {
+    let (user_repo, _, balance_service) = setup_test_dependencies();
+    let token_repo: Arc<dyn TokenRepository> = Arc::new(InMemoryTokenRepo::new());
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_token_repository(token_repo),
+    );
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (admin_token, _) = register_for_tokens(&client, "revoke_admin@example.com", "Admin").await;
+    let (_, user_refresh) = register_for_tokens(&client, "revoke_user@example.com", "Attendee").await;
+
+    let response = client
+        .post("/admin/revoke-all-tokens")
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", admin_token)))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(body["success"].as_bool().unwrap());
+
+    let response = client
+        .post("/auth/refresh")
+        .header(rocket::http::ContentType::JSON)
+        .body(format!(r#"{{"refresh_token":"{}"}}"#, user_refresh))
+        .dispatch()
+        .await;
+
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["message"].as_str().unwrap(), "Invalid refresh token");
+}
+
+#[tokio::test]
+async fn test_logout_revokes_refresh_token_and_is_idempotent() {
+    let (user_repo, _, balance_service) = setup_test_dependencies();
+    let token_repo: Arc<dyn TokenRepository> = Arc::new(InMemoryTokenRepo::new());
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_token_repository(token_repo),
+    );
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (_, refresh) = register_for_tokens(&client, "logout@example.com", "Attendee").await;
+    let post = |path: &'static str, body: String| {
+        let client = &client;
+        async move {
+            let response = client
+                .post(path)
+                .header(rocket::http::ContentType::JSON)
+                .body(body)
+                .dispatch()
+                .await;
+            response.into_json::<rocket::serde::json::Value>().await.unwrap()
+        }
+    };
+    let refresh_body = format!(r#"{{"refresh_token":"{}"}}"#, refresh);
+
+    for _ in 0..2 {
+        let body = post("/auth/logout", refresh_body.clone()).await;
+        assert!(body["success"].as_bool().unwrap(), "{}", body);
+    }
+
+    let body = post("/auth/refresh", refresh_body).await;
+    assert!(!body["success"].as_bool().unwrap());
+
+    let body = post("/auth/logout", r#"{"refresh_token":"not-a-token"}"#.to_string()).await;
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+}
+
+#[tokio::test]
+async fn test_logout_all_revokes_every_session_and_reports_count() {
+    let (user_repo, _, balance_service) = setup_test_dependencies();
+    let token_repo: Arc<dyn TokenRepository> = Arc::new(InMemoryTokenRepo::new());
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_token_repository(token_repo),
+    );
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (access, first_refresh) =
+        register_for_tokens(&client, "logout_all@example.com", "Attendee").await;
+    let response = client
+        .post("/auth/login")
+        .header(rocket::http::ContentType::JSON)
+        .body(r#"{"email":"logout_all@example.com","password":"password123"}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    let second_refresh = body["data"]["refresh_token"].as_str().unwrap().to_string();
+
+    for expected in [2, 0] {
+        let response = client
+            .post("/auth/logout-all")
+            .header(rocket::http::Header::new("Authorization", format!("Bearer {}", access)))
+            .dispatch()
+            .await;
+        let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+        assert!(body["success"].as_bool().unwrap(), "{}", body);
+        assert_eq!(body["data"].as_u64().unwrap(), expected);
+    }
+
+    for refresh in [first_refresh, second_refresh] {
+        let response = client
+            .post("/auth/refresh")
+            .header(rocket::http::ContentType::JSON)
+            .body(format!(r#"{{"refresh_token":"{}"}}"#, refresh))
+            .dispatch()
+            .await;
+        let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+        assert!(!body["success"].as_bool().unwrap());
+    }
+
+    let response = client.post("/auth/logout-all").dispatch().await;
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+#[tokio::test]
+async fn test_revoke_all_tokens_requires_admin() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (user_token, _) = register_for_tokens(&client, "revoke_attendee@example.com", "Attendee").await;
+
+    let response = client
+        .post("/admin/revoke-all-tokens")
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", user_token)))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+async fn request_password_reset(client: &Client, email: &str) -> rocket::serde::json::Value {
+    let response = client
+        .post("/auth/forgot-password")
+        .header(rocket::http::ContentType::JSON)
+        .remote("203.0.113.7:4000".parse().unwrap())
+        .body(format!(r#"{{"email":"{}"}}"#, email))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_forgot_password_throttles_fourth_request() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    for _ in 0..3 {
+        let body = request_password_reset(&client, "victim@example.com").await;
+        assert!(body["success"].as_bool().unwrap());
+    }
+
+    let body = request_password_reset(&client, "victim@example.com").await;
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 429);
+}
+
+#[tokio::test]
+async fn test_forgot_password_throttles_per_ip() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    for i in 0..3 {
+        let body = request_password_reset(&client, &format!("spray{}@example.com", i)).await;
+        assert!(body["success"].as_bool().unwrap());
+    }
+
+    let body = request_password_reset(&client, "spray3@example.com").await;
+    assert_eq!(body["status_code"].as_u64().unwrap(), 429);
+}
+
+#[tokio::test]
+async fn test_forgot_password_does_not_reveal_account_existence() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    register_for_tokens(&client, "known@example.com", "Attendee").await;
+
+    let known = request_password_reset(&client, "known@example.com").await;
+    let unknown = request_password_reset(&client, "unknown@example.com").await;
+
+    assert_eq!(known, unknown);
+    assert!(known["success"].as_bool().unwrap());
+}
+
+async fn reset_password_client() -> (Client, Arc<AuthService>) {
+    let user_repo: Arc<dyn UserRepository> = Arc::new(InMemoryUserRepo::new());
+    let token_repo: Arc<dyn TokenRepository> = Arc::new(InMemoryTokenRepo::new());
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_token_repository(token_repo)
+        .with_reset_token_repository(Arc::new(InMemoryResetTokenRepo::new()))
+        .with_user_repository(user_repo.clone()),
+    );
+    let (_, _, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo)
+        .manage(auth_service.clone())
+        .manage(balance_service)
+        .mount("/", auth_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    (client, auth_service)
+}
+
+async fn reset_password(client: &Client, token: &str, new_password: &str) -> rocket::serde::json::Value {
+    let response = client
+        .post("/auth/reset-password")
+        .header(rocket::http::ContentType::JSON)
+        .body(format!(r#"{{"token":"{}","new_password":"{}"}}"#, token, new_password))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_reset_password_sets_new_password_and_signs_out() {
+    let (client, auth_service) = reset_password_client().await;
+    let (_, old_refresh) = register_for_tokens(&client, "forgetful@example.com", "Attendee").await;
+
+    let body = request_password_reset(&client, "forgetful@example.com").await;
+    assert!(body["success"].as_bool().unwrap());
+    let token = auth_service
+        .create_reset_token("forgetful@example.com")
+        .await
+        .unwrap()
+        .expect("registered email should get a reset token");
+
+    let body = reset_password(&client, &token, "brand-new-password-789").await;
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+
+    let login = |password: &'static str| {
+        let client = &client;
+        async move {
+            let response = client
+                .post("/auth/login")
+                .header(rocket::http::ContentType::JSON)
+                .body(format!(r#"{{"email":"forgetful@example.com","password":"{}"}}"#, password))
+                .dispatch()
+                .await;
+            response.into_json::<rocket::serde::json::Value>().await.unwrap()
+        }
+    };
+    assert!(!login("password123").await["success"].as_bool().unwrap());
+    assert!(login("brand-new-password-789").await["success"].as_bool().unwrap());
+
+    let response = client
+        .post("/auth/refresh")
+        .header(rocket::http::ContentType::JSON)
+        .body(format!(r#"{{"refresh_token":"{}"}}"#, old_refresh))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(!body["success"].as_bool().unwrap());
+}
+
+#[tokio::test]
+async fn test_reset_token_works_only_once() {
+    let (client, auth_service) = reset_password_client().await;
+    register_for_tokens(&client, "once@example.com", "Attendee").await;
+    let token = auth_service.create_reset_token("once@example.com").await.unwrap().unwrap();
+
+    let body = reset_password(&client, &token, "first-new-password-1").await;
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+
+    let body = reset_password(&client, &token, "second-new-password-2").await;
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+    assert_eq!(body["message"].as_str().unwrap(), "Invalid or expired reset token");
+}
+
+#[tokio::test]
+async fn test_reset_password_rejects_unknown_token() {
+    let (client, auth_service) = reset_password_client().await;
+
+    assert!(auth_service.create_reset_token("nobody@example.com").await.unwrap().is_none());
+    let body = reset_password(&client, "not-a-reset-token", "some-new-password-3").await;
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+}
+
+#[tokio::test]
+async fn test_validate_token_returns_claims() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (token, _) = register_for_tokens(&client, "validate@example.com", "Organizer").await;
+    let user_id = auth_service.verify_token(&token).await.unwrap();
+
+    let response = client
+        .get("/auth/validate-token")
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", token)))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(body["data"]["valid"].as_bool().unwrap());
+    assert_eq!(body["data"]["user_id"].as_str().unwrap(), user_id.to_string());
+    assert_eq!(body["data"]["role"].as_str().unwrap(), "Organizer");
+    let expires_at = chrono::DateTime::parse_from_rfc3339(body["data"]["expires_at"].as_str().unwrap())
+        .expect("expires_at should be RFC 3339");
+    assert!(expires_at > chrono::Utc::now());
+}
+
+#[tokio::test]
+async fn test_validate_token_rejects_expired_token() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let issued_at = chrono::Utc::now() - chrono::Duration::hours(2);
+    let claims = crate::middleware::auth::Claims {
+        sub: Uuid::new_v4().to_string(),
+        role: "Attendee".to_string(),
+        iat: issued_at.timestamp(),
+        exp: (issued_at + chrono::Duration::hours(1)).timestamp() as usize,
+        epoch: 0,
+    };
+    let expired = jsonwebtoken::encode(
+        &jsonwebtoken::Header::default(),
+        &claims,
+        &jsonwebtoken::EncodingKey::from_secret("test_secret".as_bytes()),
+    )
+    .unwrap();
+
+    let response = client
+        .get("/auth/validate-token")
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", expired)))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+async fn permissions_for(role: &str, email: &str) -> Vec<String> {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (token, _) = register_for_tokens(&client, email, role).await;
+
+    let response = client
+        .get("/auth/permissions")
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", token)))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["data"]["role"].as_str().unwrap(), role);
+    body["data"]["permissions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p.as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_permissions_for_admin_include_everything() {
+    let permissions = permissions_for("Admin", "perm_admin@example.com").await;
+
+    for expected in ["can_create_event", "can_validate_tickets", "can_manage_ads", "can_adjust_balances"] {
+        assert!(permissions.iter().any(|p| p == expected), "admin is missing {}", expected);
+    }
+}
+
+#[tokio::test]
+async fn test_permissions_for_attendee_are_restricted() {
+    let permissions = permissions_for("Attendee", "perm_attendee@example.com").await;
+
+    assert!(permissions.iter().any(|p| p == "can_purchase_tickets"));
+    for denied in ["can_create_event", "can_validate_tickets", "can_manage_ads", "can_adjust_balances"] {
+        assert!(!permissions.iter().any(|p| p == denied), "attendee should not have {}", denied);
+    }
+}
+
+async fn check_email(client: &Client, email: &str) -> rocket::serde::json::Value {
+    let response = client
+        .get(format!("/auth/email-available?email={}", email))
+        .remote("198.51.100.4:5000".parse().unwrap())
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_email_available_ignores_case() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    register_for_tokens(&client, "Taken.Address@example.com", "Attendee").await;
+
+    let unused = check_email(&client, "fresh@example.com").await;
+    assert!(unused["data"]["available"].as_bool().unwrap());
+
+    let taken = check_email(&client, "taken.address@EXAMPLE.com").await;
+    assert!(!taken["data"]["available"].as_bool().unwrap());
+    assert_eq!(taken["data"].as_object().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_email_available_is_rate_limited_per_ip() {
+    let (user_repo, _, balance_service) = setup_test_dependencies();
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_email_check_limit(2, std::time::Duration::from_secs(60)),
+    );
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    for i in 0..2 {
+        let body = check_email(&client, &format!("probe{}@example.com", i)).await;
+        assert!(body["success"].as_bool().unwrap());
+    }
+
+    let body = check_email(&client, "probe2@example.com").await;
+    assert_eq!(body["status_code"].as_u64().unwrap(), 429);
+
+    // A forged forwarding header doesn't buy a fresh allowance
+    let response = client
+        .get("/auth/email-available?email=probe3@example.com")
+        .remote("198.51.100.4:5000".parse().unwrap())
+        .header(rocket::http::Header::new("X-Real-IP", "203.0.113.99"))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["status_code"].as_u64().unwrap(), 429);
+}
+
+#[tokio::test]
+async fn test_password_change_rejects_older_access_tokens() {
+    let user_repo: Arc<dyn UserRepository> = Arc::new(InMemoryUserRepo::new());
+    let token_repo: Arc<dyn TokenRepository> = Arc::new(InMemoryTokenRepo::new());
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_token_repository(token_repo)
+        .with_user_repository(user_repo.clone()),
+    );
+    let (_, _, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (old_token, old_refresh) = register_for_tokens(&client, "epoch@example.com", "Attendee").await;
+
+    let response = client
+        .put("/auth/password")
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", old_token)))
+        .body(r#"{"current_password":"password123","new_password":"new-password-456"}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+    let new_token = body["data"]["access_token"].as_str().unwrap().to_string();
+
+    let me = |token: String| {
+        let client = &client;
+        async move {
+            client
+                .get("/auth/me")
+                .header(rocket::http::Header::new("Authorization", format!("Bearer {}", token)))
+                .dispatch()
+                .await
+                .status()
+        }
+    };
+    assert_eq!(me(old_token).await, Status::Unauthorized);
+    assert_eq!(me(new_token).await, Status::Ok);
+
+    let response = client
+        .post("/auth/refresh")
+        .header(rocket::http::ContentType::JSON)
+        .body(format!(r#"{{"refresh_token":"{}"}}"#, old_refresh))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(!body["success"].as_bool().unwrap());
+}
+
+#[tokio::test]
+async fn test_password_change_rejects_weak_new_password() {
+    let (user_repo, _, balance_service) = setup_test_dependencies();
+    let auth_service = Arc::new(
+        AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        )
+        .with_password_policy(PasswordPolicy::new(50)),
+    );
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let response = client
+        .post("/auth/register")
+        .header(rocket::http::ContentType::JSON)
+        .body(r#"{"name":"Weak","email":"weak@example.com","password":"violet-tractor-umbrella"}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    let token = body["data"]["token"].as_str().unwrap().to_string();
+
+    let response = client
+        .put("/auth/password")
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", token)))
+        .body(r#"{"current_password":"violet-tractor-umbrella","new_password":"qwerty1"}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.starts_with("New password is too weak"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_password_change_requires_current_password() {
+    let (user_repo, auth_service, balance_service) = setup_test_dependencies();
+
+    let rocket = rocket::build()
+        .manage(user_repo.clone())
+        .manage(auth_service.clone())
+        .manage(balance_service.clone())
+        .mount("/", auth_routes());
+
+    let client = Client::tracked(rocket)
+        .await
+        .expect("valid rocket instance");
+
+    let (token, _) = register_for_tokens(&client, "wrongpass@example.com", "Attendee").await;
+
+    let response = client
+        .put("/auth/password")
+        .header(rocket::http::ContentType::JSON)
+        .header(rocket::http::Header::new("Authorization", format!("Bearer {}", token)))
+        .body(r#"{"current_password":"not-it","new_password":"new-password-456"}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+    assert_eq!(body["message"].as_str().unwrap(), "Current password is incorrect");
+}
@@ -0,0 +1,20 @@
+use rocket::data::{Limits, ToByteUnit};
+use rocket::figment::Figment;
+use rocket::serde::json::{Json, Value, json};
+use rocket::{Request, catch};
+
+/// Rocket configuration with the JSON body limit overridden. Form and file
+/// limits keep Rocket's defaults so uploads are not affected.
+pub fn figment_with_json_limit(limit_bytes: u64) -> Figment {
+    rocket::Config::figment().merge(("limits", Limits::default().limit("json", limit_bytes.bytes())))
+}
+
+#[catch(413)]
+pub fn payload_too_large(_: &Request) -> Json<Value> {
+    Json(json!({
+        "success": false,
+        "status_code": 413,
+        "message": "Request body too large",
+        "data": null
+    }))
+}
@@ -0,0 +1,98 @@
+use rocket::data::{Data, FromData, Limits, Outcome};
+use rocket::http::Status;
+use rocket::serde::json::{Json, Value, json};
+use rocket::{Request, catch};
+use serde::de::DeserializeOwned;
+use std::ops::Deref;
+
+/// JSON request body whose parse failures are answered with a 422 envelope
+/// naming the offending field, instead of Rocket's opaque `Json<T>` rejection
+#[derive(Debug)]
+pub struct JsonBody<T>(pub T);
+
+impl<T> Deref for JsonBody<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Why a body was rejected; cached on the request for `unprocessable_entity`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonBodyError {
+    /// Dotted path of the offending field, when serde could tell
+    pub field: Option<String>,
+    pub reason: String,
+}
+
+impl JsonBodyError {
+    fn from_path_error(error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let path = error.path().to_string();
+        let inner = error.into_inner();
+        let reason = inner.to_string();
+
+        // serde reports a missing field against its parent, so lift the name
+        // out of "missing field `amount`"
+        let missing = reason
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.split('`').next())
+            .map(|name| if path == "." { name.to_string() } else { format!("{}.{}", path, name) });
+
+        let field = missing.or_else(|| (path != ".").then_some(path));
+        Self { field, reason }
+    }
+
+    fn message(&self) -> String {
+        match &self.field {
+            Some(field) => format!("Invalid request body: {}: {}", field, self.reason),
+            None => format!("Invalid request body: {}", self.reason),
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, T: DeserializeOwned> FromData<'r> for JsonBody<T> {
+    type Error = JsonBodyError;
+
+    async fn from_data(req: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let limit = req.limits().get("json").unwrap_or(Limits::JSON);
+        let body = match data.open(limit).into_string().await {
+            Ok(body) if body.is_complete() => body.into_inner(),
+            Ok(_) => {
+                let error = JsonBodyError { field: None, reason: "body too large".to_string() };
+                return Outcome::Error((Status::PayloadTooLarge, error));
+            }
+            Err(e) => {
+                let error = JsonBodyError { field: None, reason: e.to_string() };
+                return Outcome::Error((Status::BadRequest, error));
+            }
+        };
+
+        let deserializer = &mut serde_json::Deserializer::from_str(&body);
+        match serde_path_to_error::deserialize(deserializer) {
+            Ok(value) => Outcome::Success(JsonBody(value)),
+            Err(e) => {
+                let error = JsonBodyError::from_path_error(e);
+                req.local_cache(|| Some(error.clone()));
+                Outcome::Error((Status::UnprocessableEntity, error))
+            }
+        }
+    }
+}
+
+#[catch(422)]
+pub fn unprocessable_entity(req: &Request) -> Json<Value> {
+    let message = req
+        .local_cache(|| None::<JsonBodyError>)
+        .as_ref()
+        .map(JsonBodyError::message)
+        .unwrap_or_else(|| "Unprocessable request".to_string());
+
+    Json(json!({
+        "success": false,
+        "status_code": 422,
+        "message": message,
+        "data": null
+    }))
+}
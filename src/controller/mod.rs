@@ -1,3 +1,8 @@
 pub mod transaction;
 pub mod auth;
-pub mod health;
\ No newline at end of file
+pub mod user;
+pub mod admin;
+pub mod body_limit;
+pub mod json_body;
+pub mod health;
+pub mod test_data;
@@ -0,0 +1,4 @@
+pub mod test_data_controller;
+
+#[cfg(test)]
+pub mod tests;
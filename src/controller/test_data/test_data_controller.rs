@@ -0,0 +1,130 @@
+use crate::model::user::{User, UserRole};
+use crate::repository::user::user_repo::UserRepository;
+use crate::service::auth::auth_service::AuthService;
+use crate::service::transaction::balance_service::BalanceService;
+use rocket::{Build, Rocket, State, post, routes, serde::json::Json};
+use serde::Serialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Password shared by every seeded account
+pub const SEED_PASSWORD: &str = "password123";
+pub const SEED_BALANCE: i64 = 500_000;
+
+const SEED_USERS: [(&str, &str, UserRole); 3] = [
+    ("Seed Admin", "admin@seed.eventsphere.test", UserRole::Admin),
+    ("Seed Organizer", "organizer@seed.eventsphere.test", UserRole::Organizer),
+    ("Seed Attendee", "attendee@seed.eventsphere.test", UserRole::Attendee),
+];
+
+/// Seeding and wiping data is only ever exposed outside production; in
+/// production the routes are not mounted at all, so they answer 404.
+pub fn mount_test_data_routes(rocket: Rocket<Build>, is_production: bool) -> Rocket<Build> {
+    if is_production {
+        return rocket;
+    }
+    rocket.mount("/api/test", routes![seed_handler, reset_handler])
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiResponse<T>
+where
+    T: Serialize,
+{
+    pub success: bool,
+    pub status_code: u16,
+    pub message: String,
+    pub data: Option<T>,
+}
+
+impl<T> ApiResponse<T>
+where
+    T: Serialize,
+{
+    pub fn success(message: &str, data: T) -> Json<Self> {
+        Json(Self {
+            success: true,
+            status_code: 200,
+            message: message.to_string(),
+            data: Some(data),
+        })
+    }
+
+    pub fn error(status_code: u16, message: &str) -> Json<Self> {
+        Json(Self {
+            success: false,
+            status_code,
+            message: message.to_string(),
+            data: None,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeededUser {
+    pub id: Uuid,
+    pub email: String,
+    pub role: UserRole,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeedResponse {
+    pub password: String,
+    pub users: Vec<SeededUser>,
+}
+
+#[post("/seed")]
+pub async fn seed_handler(
+    user_repository: &State<Arc<dyn UserRepository>>,
+    auth_service: &State<Arc<AuthService>>,
+    balance_service: &State<Arc<dyn BalanceService + Send + Sync>>,
+) -> Json<ApiResponse<SeedResponse>> {
+    let repo = user_repository.inner();
+    if let Ok(Some(_)) = repo.find_by_email(SEED_USERS[0].1).await {
+        return ApiResponse::error(409, "Seed data already present, reset first");
+    }
+
+    let hashed_password = match auth_service.hash_password(SEED_PASSWORD) {
+        Ok(p) => p,
+        Err(_) => return ApiResponse::error(500, "Failed to hash password"),
+    };
+
+    let mut users = Vec::new();
+    for (name, email, role) in SEED_USERS {
+        let user = User::new(name.to_string(), email.to_string(), hashed_password.clone(), role);
+        if let Err(e) = repo.create(&user).await {
+            eprintln!("Failed to seed user: {:?}", e);
+            return ApiResponse::error(500, &format!("Failed to seed user {}", email));
+        }
+        if let Err(e) = balance_service.add_funds(user.id, SEED_BALANCE).await {
+            eprintln!("Failed to seed balance: {:?}", e);
+            return ApiResponse::error(500, &format!("Failed to seed balance for {}", email));
+        }
+
+        users.push(SeededUser {
+            id: user.id,
+            email: user.email,
+            role: user.role,
+        });
+    }
+
+    ApiResponse::success("Test data seeded", SeedResponse {
+        password: SEED_PASSWORD.to_string(),
+        users,
+    })
+}
+
+#[post("/reset")]
+pub async fn reset_handler(db_pool: &State<Arc<PgPool>>) -> Json<ApiResponse<()>> {
+    match sqlx::query("TRUNCATE TABLE transactions, balances, refresh_tokens, users CASCADE")
+        .execute(db_pool.inner().as_ref())
+        .await
+    {
+        Ok(_) => ApiResponse::success("Test data reset", ()),
+        Err(e) => {
+            eprintln!("Failed to reset test data: {:?}", e);
+            ApiResponse::error(500, "Failed to reset test data")
+        }
+    }
+}
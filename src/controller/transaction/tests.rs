@@ -8,14 +8,50 @@ use warp::{Filter, Rejection, Reply};
 
 use crate::controller::transaction::transaction_controller::{
     AddFundsRequest, ApiResponse, BalanceResponse, CreateTransactionRequest, ProcessPaymentRequest,
-    WithdrawFundsRequest,
+    WithdrawFundsRequest, admin_balance_routes, admin_transaction_routes, balance_routes,
+    transaction_routes, user_routes, webhook_routes,
 };
-use crate::model::transaction::{Balance, Transaction, TransactionStatus};
+use crate::repository::transaction::balance_adjustment_repo::InMemoryBalanceAdjustmentRepository;
+use crate::repository::transaction::payment_event_repo::InMemoryPaymentEventRepository;
+use crate::common::pagination::{Page, Pagination};
+use crate::common::sort::SortOrder;
+use crate::middleware::amount_format::AmountFormatFairing;
+use crate::middleware::compression::CompressionFairing;
+use crate::middleware::ownership::OwnershipPolicy;
+use crate::repository::transaction::transaction_repo::{
+    DbTransactionRepository, InMemoryTransactionPersistence, TransactionFilter, TransactionRepository,
+    TransactionStats, sort_transactions,
+};
+use crate::service::transaction::balance_service::{BalanceService, DefaultBalanceService};
+use crate::service::transaction::tests::common::{MockBalanceRepository, customer_refund_reason};
+use crate::service::transaction::balance_adjustment_service::{
+    BalanceAdjustmentService, DefaultBalanceAdjustmentService,
+};
+use crate::service::transaction::fee_schedule::{FeeRule, FeeSchedule};
+use crate::service::transaction::payment_service::MockPaymentService;
+use crate::service::transaction::payment_webhook_service::{
+    DefaultPaymentWebhookService, PaymentWebhookService,
+};
+use crate::service::transaction::transaction_service::{DefaultTransactionService, PaymentNotRetryable};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use crate::model::transaction::{
+    Balance, DEFAULT_PAYMENT_METHODS, RefundPolicy, RefundReason, TOPUP_DESCRIPTION, Transaction, TransactionCategory, TransactionStatus,
+    TransactionStatusSnapshot,
+};
+use crate::controller::body_limit::{figment_with_json_limit, payload_too_large};
+use crate::controller::json_body::unprocessable_entity;
+use crate::common::validation::DESCRIPTION_MAX_LENGTH;
+use crate::model::user::{User, UserRole};
+use crate::service::auth::auth_service::AuthService;
 use crate::service::transaction::TransactionService;
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::asynchronous::Client;
 
 struct MockTransactionService {
     transactions: Mutex<HashMap<Uuid, Transaction>>,
     balances: Mutex<HashMap<Uuid, Balance>>,
+    payment_methods: Vec<String>,
 }
 
 impl MockTransactionService {
@@ -23,6 +59,7 @@ impl MockTransactionService {
         Self {
             transactions: Mutex::new(HashMap::new()),
             balances: Mutex::new(HashMap::new()),
+            payment_methods: DEFAULT_PAYMENT_METHODS.iter().map(|m| m.to_string()).collect(),
         }
     }
 }
@@ -46,6 +83,30 @@ impl TransactionService for MockTransactionService {
         Ok(transaction)
     }
 
+    async fn create_payment_link(
+        &self,
+        user_id: Uuid,
+        ticket_id: Option<Uuid>,
+        amount: i64,
+        description: String,
+        payment_method: String,
+        expires_at: chrono::DateTime<Utc>,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+        let mut transaction = Transaction::new(user_id, ticket_id, amount, description, payment_method);
+        transaction.expires_at = Some(expires_at);
+        let mut transactions = self.transactions.lock().unwrap();
+        transactions.insert(transaction.id, transaction.clone());
+        Ok(transaction)
+    }
+
+    fn quote_fee(&self, _amount: i64, _payment_method: &str) -> i64 {
+        0
+    }
+
+    fn payment_methods(&self) -> &[String] {
+        &self.payment_methods
+    }
+
     async fn process_payment(
         &self,
         transaction_id: Uuid,
@@ -66,6 +127,21 @@ impl TransactionService for MockTransactionService {
         }
     }
 
+    async fn retry_payment(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+        let mut transactions = self.transactions.lock().unwrap();
+        match transactions.get_mut(&transaction_id) {
+            Some(transaction) if transaction.status == TransactionStatus::Failed => {
+                transaction.process(true, Some(format!("PG-REF-{}", Uuid::new_v4())));
+                Ok(transaction.clone())
+            }
+            Some(_) => Err(Box::new(PaymentNotRetryable)),
+            None => Err("Transaction not found".into()),
+        }
+    }
+
     async fn validate_payment(
         &self,
         transaction_id: Uuid,
@@ -81,6 +157,8 @@ impl TransactionService for MockTransactionService {
     async fn refund_transaction(
         &self,
         transaction_id: Uuid,
+        reason: RefundReason,
+        _refund_to: Option<RefundPolicy>,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
         let mut transactions = self.transactions.lock().unwrap();
         if let Some(transaction) = transactions.get_mut(&transaction_id) {
@@ -88,6 +166,7 @@ impl TransactionService for MockTransactionService {
                 return Err("Only successful transactions can be refunded".into());
             }
             transaction.status = TransactionStatus::Refunded;
+            transaction.refund_reason = Some(reason);
             transaction.updated_at = Utc::now();
             Ok(transaction.clone())
         } else {
@@ -103,17 +182,77 @@ impl TransactionService for MockTransactionService {
         Ok(transactions.get(&transaction_id).cloned())
     }
 
+    async fn get_transaction_status(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync + 'static>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions.get(&transaction_id).map(TransactionStatusSnapshot::from))
+    }
+
+    async fn get_transaction_by_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions
+            .values()
+            .find(|t| t.external_reference.as_deref() == Some(reference))
+            .cloned())
+    }
+
+    async fn get_transactions_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(ids.iter().filter_map(|id| transactions.get(id).cloned()).collect())
+    }
+
     async fn get_user_transactions(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
         let transactions = self.transactions.lock().unwrap();
-        Ok(transactions
+        let mut user_transactions: Vec<Transaction> = transactions
             .values()
             .filter(|t| t.user_id == user_id)
             .cloned()
-            .collect())
+            .collect();
+        sort_transactions(&mut user_transactions, sort);
+        Ok(user_transactions)
+    }
+
+    async fn get_user_transactions_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        pagination: &Pagination,
+    ) -> Result<Page<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
+        let transactions = self.transactions.lock().unwrap();
+        let mut matching: Vec<Transaction> = transactions
+            .values()
+            .filter(|t| t.user_id == user_id && filter.matches(t))
+            .cloned()
+            .collect();
+        sort_transactions(&mut matching, sort);
+        Ok(pagination.apply(matching))
+    }
+
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync + 'static>> {
+        let now = Utc::now();
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions
+            .values()
+            .filter(|t| t.user_id == user_id && t.status == TransactionStatus::Pending && !t.is_expired(now))
+            .count())
     }
+
     async fn add_funds_to_balance(
         &self,
         user_id: Uuid,
@@ -129,6 +268,11 @@ impl TransactionService for MockTransactionService {
             .entry(user_id)
             .or_insert_with(|| Balance::new(user_id));
         let new_amount = balance.add_funds(amount).map_err(|e| e.to_string())?;
+
+        let mut topup = Transaction::new(user_id, None, amount, TOPUP_DESCRIPTION.to_string(), payment_method);
+        topup.category = TransactionCategory::Topup;
+        topup.status = TransactionStatus::Success;
+        self.transactions.lock().unwrap().insert(topup.id, topup);
         Ok(new_amount)
     }
     async fn withdraw_funds(
@@ -179,6 +323,13 @@ impl TransactionService for MockTransactionService {
         }
     }
 
+    async fn get_transaction_stats(
+        &self,
+    ) -> Result<TransactionStats, Box<dyn Error + Send + Sync + 'static>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions.values().collect())
+    }
+
     async fn delete_transaction(
         &self,
         transaction_id: Uuid,
@@ -302,7 +453,7 @@ async fn refund_transaction_handler_for_test(
     transaction_id: Uuid,
     service: Arc<MockTransactionService>,
 ) -> Result<impl Reply, Rejection> {
-    match service.refund_transaction(transaction_id).await {
+    match service.refund_transaction(transaction_id, customer_refund_reason(), None).await {
         Ok(transaction) => {
             let response = ApiResponse {
                 success: true,
@@ -356,7 +507,7 @@ async fn get_user_transactions_handler_for_test(
     user_id: Uuid,
     service: Arc<MockTransactionService>,
 ) -> Result<impl Reply, Rejection> {
-    match service.get_user_transactions(user_id).await {
+    match service.get_user_transactions(user_id, &SortOrder::descending("created_at")).await {
         Ok(transactions) => {
             let response = ApiResponse {
                 success: true,
@@ -569,3 +720,1290 @@ fn create_test_routes() -> impl Filter<Extract = impl Reply, Error = Rejection>
         .or(withdraw_funds)
         .or(delete_transaction)
 }
+
+fn setup_rocket_client_state() -> (Arc<AuthService>, Arc<dyn TransactionService + Send + Sync>) {
+    let auth_service = Arc::new(AuthService::new(
+        "test_secret".to_string(),
+        "test_refresh_secret".to_string(),
+        "test_pepper".to_string(),
+    ));
+    let service: Arc<dyn TransactionService + Send + Sync> = Arc::new(MockTransactionService::new());
+    (auth_service, service)
+}
+
+async fn rocket_client() -> (Client, Uuid, String) {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Validation User".to_string(),
+        "validation@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service)
+        .mount("/api/transactions", transaction_routes())
+        .mount("/api/balance", balance_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    (client, user.id, format!("Bearer {}", token))
+}
+
+async fn post_json(client: &Client, path: &str, bearer: &str, body: String) -> rocket::serde::json::Value {
+    let response = client
+        .post(path.to_string())
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", bearer.to_string()))
+        .body(body)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    response.into_json().await.unwrap()
+}
+
+fn assert_field_rejected(body: &rocket::serde::json::Value, field: &str) {
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+    assert!(
+        body["message"].as_str().unwrap().contains(field),
+        "Expected an error for {}: {}",
+        field,
+        body["message"]
+    );
+}
+
+#[tokio::test]
+async fn test_create_transaction_rejects_non_positive_amount() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":0,"description":"Ticket","payment_method":"credit_card"}}"#,
+        user_id
+    );
+
+    let body = post_json(&client, "/api/transactions", &bearer, body).await;
+    assert_field_rejected(&body, "amount");
+}
+
+#[tokio::test]
+async fn test_create_transaction_rejects_empty_description() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":100,"description":"  ","payment_method":"credit_card"}}"#,
+        user_id
+    );
+
+    let body = post_json(&client, "/api/transactions", &bearer, body).await;
+    assert_field_rejected(&body, "description");
+}
+
+#[tokio::test]
+async fn test_create_transaction_rejects_overlong_description() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":100,"description":"{}","payment_method":"credit_card"}}"#,
+        user_id,
+        "x".repeat(DESCRIPTION_MAX_LENGTH + 1)
+    );
+
+    let body = post_json(&client, "/api/transactions", &bearer, body).await;
+    assert_field_rejected(&body, "description");
+}
+
+#[tokio::test]
+async fn test_create_transaction_rejects_unknown_payment_method() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":100,"description":"Ticket","payment_method":"bitcoin"}}"#,
+        user_id
+    );
+
+    let body = post_json(&client, "/api/transactions", &bearer, body).await;
+    assert_field_rejected(&body, "payment_method");
+}
+
+#[tokio::test]
+async fn test_create_transaction_accepts_valid_request() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":100,"description":"Ticket","payment_method":"credit_card"}}"#,
+        user_id
+    );
+
+    let body = post_json(&client, "/api/transactions", &bearer, body).await;
+    assert!(body["success"].as_bool().unwrap());
+}
+
+#[tokio::test]
+async fn test_add_funds_rejects_negative_amount() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","amount":-50,"payment_method":"bank_transfer"}}"#,
+        user_id
+    );
+
+    let body = post_json(&client, "/api/balance/add", &bearer, body).await;
+    assert_field_rejected(&body, "amount");
+}
+
+#[tokio::test]
+async fn test_withdraw_funds_rejects_empty_description() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","amount":50,"description":""}}"#,
+        user_id
+    );
+
+    let body = post_json(&client, "/api/balance/withdraw", &bearer, body).await;
+    assert_field_rejected(&body, "description");
+}
+
+#[tokio::test]
+async fn test_add_funds_round_trips_string_amounts() {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Amount User".to_string(),
+        "amounts@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service)
+        .attach(AmountFormatFairing)
+        .mount("/api/balance", balance_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    let body = format!(
+        r#"{{"user_id":"{}","amount":"9007199254740993","payment_method":"bank_transfer"}}"#,
+        user.id
+    );
+
+    let body = post_json(&client, "/api/balance/add?amounts=string", &format!("Bearer {}", token), body).await;
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+    assert_eq!(body["data"]["balance"], "9007199254740993");
+    assert_eq!(body["status_code"].as_u64().unwrap(), 200);
+}
+
+async fn post_malformed_transaction(body: &str) -> rocket::serde::json::Value {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Malformed User".to_string(),
+        "malformed@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service)
+        .register("/", rocket::catchers![unprocessable_entity])
+        .mount("/api/transactions", transaction_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let response = client
+        .post("/api/transactions")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", token)))
+        .body(body.replace("USER_ID", &user.id.to_string()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+    response.into_json().await.unwrap()
+}
+
+#[tokio::test]
+async fn test_missing_amount_returns_descriptive_422() {
+    let body = post_malformed_transaction(
+        r#"{"user_id":"USER_ID","ticket_id":null,"description":"Ticket","payment_method":"credit_card"}"#,
+    )
+    .await;
+
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 422);
+    let message = body["message"].as_str().unwrap();
+    assert!(message.starts_with("Invalid request body: amount: missing field `amount`"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_non_numeric_amount_returns_descriptive_422() {
+    let body = post_malformed_transaction(
+        r#"{"user_id":"USER_ID","ticket_id":null,"amount":"lots","description":"Ticket","payment_method":"credit_card"}"#,
+    )
+    .await;
+    let message = body["message"].as_str().unwrap();
+    assert!(message.starts_with("Invalid request body: amount: invalid amount 'lots'"), "{}", message);
+
+    let body = post_malformed_transaction(
+        r#"{"user_id":"USER_ID","ticket_id":null,"amount":true,"description":"Ticket","payment_method":"credit_card"}"#,
+    )
+    .await;
+    let message = body["message"].as_str().unwrap();
+    assert!(message.contains("expected an integer amount or a numeric string"), "{}", message);
+}
+
+#[tokio::test]
+async fn test_oversized_json_body_returns_413_envelope() {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Large Body User".to_string(),
+        "large_body@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let rocket = rocket::custom(figment_with_json_limit(1024))
+        .manage(auth_service)
+        .manage(service)
+        .register("/", rocket::catchers![payload_too_large])
+        .mount("/api/transactions", transaction_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let oversized = format!("[{}]", vec!["\"filler-item\""; 500].join(","));
+    let response = client
+        .post("/api/transactions")
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", token)))
+        .body(oversized)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::PayloadTooLarge);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 413);
+    assert_eq!(body["message"].as_str().unwrap(), "Request body too large");
+}
+
+fn sign_webhook(secret: &str, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+async fn webhook_client() -> (Client, Arc<dyn TransactionRepository + Send + Sync>) {
+    let transaction_repository: Arc<dyn TransactionRepository + Send + Sync> =
+        Arc::new(DbTransactionRepository::new(InMemoryTransactionPersistence::new()));
+    let webhook_service: Arc<dyn PaymentWebhookService + Send + Sync> =
+        Arc::new(DefaultPaymentWebhookService::new(
+            "webhook_secret".to_string(),
+            Arc::new(InMemoryPaymentEventRepository::new(transaction_repository.clone())),
+            transaction_repository.clone(),
+        ));
+
+    let rocket = rocket::build()
+        .manage(webhook_service)
+        .mount("/", webhook_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    (client, transaction_repository)
+}
+
+#[tokio::test]
+async fn test_payment_webhook_capture_then_duplicate() {
+    let (client, repo) = webhook_client().await;
+    let transaction = repo
+        .save(&Transaction::new(
+            Uuid::new_v4(),
+            None,
+            5000,
+            "Gateway purchase".to_string(),
+            "credit_card".to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let body = format!(
+        r#"{{"event_id":"evt_route_1","type":"payment.captured","transaction_id":"{}","reference":"PG-1"}}"#,
+        transaction.id
+    );
+    let signature = sign_webhook("webhook_secret", &body);
+
+    for expected_message in ["Event applied", "Event already processed"] {
+        let response = client
+            .post("/webhooks/payments")
+            .header(ContentType::JSON)
+            .header(Header::new("X-Signature", signature.clone()))
+            .body(body.clone())
+            .dispatch()
+            .await;
+
+        assert_eq!(response.status(), Status::Ok);
+        let json: rocket::serde::json::Value = response.into_json().await.unwrap();
+        assert_eq!(json["message"].as_str().unwrap(), expected_message);
+    }
+
+    let stored = repo.find_by_id(transaction.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, TransactionStatus::Success);
+}
+
+#[tokio::test]
+async fn test_payment_webhook_rejects_bad_signature() {
+    let (client, _) = webhook_client().await;
+    let body = format!(
+        r#"{{"event_id":"evt_forged","type":"payment.captured","transaction_id":"{}"}}"#,
+        Uuid::new_v4()
+    );
+
+    let response = client
+        .post("/webhooks/payments")
+        .header(ContentType::JSON)
+        .header(Header::new("X-Signature", sign_webhook("wrong_secret", &body)))
+        .body(body)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Unauthorized);
+}
+
+async fn adjust_balance_client(role: UserRole) -> (Client, String) {
+    let (auth_service, _) = setup_rocket_client_state();
+    let user = User::new(
+        "Support User".to_string(),
+        "support@example.com".to_string(),
+        "hash".to_string(),
+        role,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let balances = Arc::new(MockBalanceRepository::new());
+    let balance_service: Arc<dyn BalanceService + Send + Sync> =
+        Arc::new(DefaultBalanceService::new(balances.clone()));
+    let adjustment_service: Arc<dyn BalanceAdjustmentService + Send + Sync> =
+        Arc::new(DefaultBalanceAdjustmentService::new(
+            balance_service,
+            Arc::new(InMemoryBalanceAdjustmentRepository::new(balances)),
+        ));
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(adjustment_service)
+        .mount("/api", admin_balance_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    (client, format!("Bearer {}", token))
+}
+
+#[tokio::test]
+async fn test_adjust_balance_requires_admin() {
+    let (client, bearer) = adjust_balance_client(UserRole::Attendee).await;
+
+    let response = client
+        .post(format!("/api/admin/users/{}/balance/adjust", Uuid::new_v4()))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", bearer))
+        .body(r#"{"delta":500,"reason":"Goodwill credit"}"#)
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[tokio::test]
+async fn test_adjust_balance_rejects_overdraw_without_flag() {
+    let (client, bearer) = adjust_balance_client(UserRole::Admin).await;
+    let path = format!("/api/admin/users/{}/balance/adjust", Uuid::new_v4());
+
+    let body = post_json(&client, &path, &bearer, r#"{"delta":-500,"reason":"Error reversal"}"#.to_string()).await;
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+
+    let body = post_json(&client, &path, &bearer, r#"{"delta":500,"reason":"Goodwill credit"}"#.to_string()).await;
+    assert!(body["success"].as_bool().unwrap());
+    assert_eq!(body["data"]["resulting_balance"].as_i64().unwrap(), 500);
+}
+
+#[tokio::test]
+async fn test_user_transactions_rejects_unknown_sort_key() {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Sorting User".to_string(),
+        "sorting@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service)
+        .mount("/api/users", user_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let response = client
+        .get(format!("/api/users/{}/transactions?sort=-password", user.id))
+        .header(Header::new("Authorization", format!("Bearer {}", token)))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_field_rejected(&body, "sort");
+}
+
+#[tokio::test]
+async fn test_checkout_preview_matches_charged_total() {
+    let auth_service = Arc::new(AuthService::new(
+        "test_secret".to_string(),
+        "test_refresh_secret".to_string(),
+        "test_pepper".to_string(),
+    ));
+    let transaction_repository: Arc<dyn TransactionRepository + Send + Sync> =
+        Arc::new(DbTransactionRepository::new(InMemoryTransactionPersistence::new()));
+    let service: Arc<dyn TransactionService + Send + Sync> = Arc::new(
+        DefaultTransactionService::new(
+            transaction_repository.clone(),
+            Arc::new(DefaultBalanceService::new(Arc::new(MockBalanceRepository::new()))),
+            Arc::new(MockPaymentService::new()),
+        )
+        .with_fee_schedule(
+            FeeSchedule::new().with_rule("credit_card", FeeRule { flat: 2000, percent_bps: 290 }),
+        ),
+    );
+    let user = User::new(
+        "Preview User".to_string(),
+        "preview@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let bearer = format!("Bearer {}", auth_service.generate_token(&user).await.unwrap().access_token);
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service)
+        .mount("/api/transactions", transaction_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let cart = format!(
+        r#"{{"user_id":"{}","ticket_id":"{}","amount":100000,"description":"Festival pass","payment_method":"credit_card"}}"#,
+        user.id,
+        Uuid::new_v4()
+    );
+
+    let preview = post_json(&client, "/api/transactions/preview", &bearer, cart.clone()).await;
+    assert_eq!(preview["data"]["subtotal"].as_i64().unwrap(), 100_000);
+    assert_eq!(preview["data"]["discount"].as_i64().unwrap(), 0);
+    assert_eq!(preview["data"]["fees"].as_i64().unwrap(), 4900);
+    let nothing_created = transaction_repository
+        .find_by_user(user.id, &SortOrder::descending("created_at"))
+        .await
+        .unwrap();
+    assert!(nothing_created.is_empty());
+
+    let created = post_json(&client, "/api/transactions", &bearer, cart).await;
+    let charged = created["data"]["amount"].as_i64().unwrap() + created["data"]["fee_amount"].as_i64().unwrap();
+    assert_eq!(preview["data"]["total"].as_i64().unwrap(), charged);
+}
+
+#[tokio::test]
+async fn test_user_transactions_filter_by_category() {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Category User".to_string(),
+        "category@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service.clone())
+        .mount("/api/users", user_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    service
+        .create_transaction(user.id, Some(Uuid::new_v4()), 1500, "Concert".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.add_funds_to_balance(user.id, 5000, "bank_transfer".to_string()).await.unwrap();
+
+    let response = client
+        .get(format!("/api/users/{}/transactions?category=topup", user.id))
+        .header(Header::new("Authorization", format!("Bearer {}", token)))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    let rows = body["data"]["items"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(body["data"]["total"].as_u64().unwrap(), 1);
+    assert_eq!(rows[0]["category"].as_str().unwrap(), "Topup");
+    assert_eq!(rows[0]["amount"].as_i64().unwrap(), 5000);
+
+    let response = client
+        .get(format!("/api/users/{}/transactions?category=lottery", user.id))
+        .header(Header::new("Authorization", format!("Bearer {}", token)))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_field_rejected(&body, "category");
+}
+
+#[tokio::test]
+async fn test_user_transactions_filter_by_status_and_dates() {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "History User".to_string(),
+        "history@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service.clone())
+        .mount("/api/users", user_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let pending = service
+        .create_transaction(user.id, Some(Uuid::new_v4()), 1500, "Concert".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.add_funds_to_balance(user.id, 5000, "bank_transfer".to_string()).await.unwrap();
+
+    let get = |query: String| {
+        let client = &client;
+        let token = &token;
+        async move {
+            let response = client
+                .get(format!("/api/users/{}/transactions?{}", user.id, query))
+                .header(Header::new("Authorization", format!("Bearer {}", token)))
+                .dispatch()
+                .await;
+            response.into_json::<rocket::serde::json::Value>().await.unwrap()
+        }
+    };
+
+    let body = get("status=Pending".to_string()).await;
+    let rows = body["data"]["items"].as_array().unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0]["id"].as_str().unwrap(), pending.id.to_string());
+
+    let tomorrow = (Utc::now() + chrono::Duration::days(1)).format("%Y-%m-%dT%H:%M:%SZ");
+    let body = get(format!("from_date={}", tomorrow)).await;
+    assert!(body["data"]["items"].as_array().unwrap().is_empty());
+    let body = get(format!("to_date={}", tomorrow)).await;
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 2);
+
+    let body = get("status=Lost".to_string()).await;
+    assert_field_rejected(&body, "status");
+    let body = get("from_date=yesterday".to_string()).await;
+    assert_field_rejected(&body, "from_date");
+    let body = get(format!("from_date={}&to_date=2020-01-01T00:00:00Z", tomorrow)).await;
+    assert_field_rejected(&body, "from_date");
+}
+
+#[tokio::test]
+async fn test_user_transactions_are_paged_with_status_filter() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+    for i in 0..5 {
+        let transaction = service
+            .create_transaction(user_id, None, 100 + i, format!("Ticket {}", i), "credit_card".to_string())
+            .await
+            .unwrap();
+        if i < 3 {
+            service.process_payment(transaction.id, None).await.unwrap();
+        }
+    }
+
+    let get = |query: &'static str| {
+        let client = &client;
+        let bearer = bearer.clone();
+        async move {
+            let response = client
+                .get(format!("/api/users/{}/transactions?{}", user_id, query))
+                .header(Header::new("Authorization", bearer))
+                .dispatch()
+                .await;
+            response.into_json::<rocket::serde::json::Value>().await.unwrap()
+        }
+    };
+
+    let body = get("per_page=2&page=2&sort=amount").await;
+    let amounts: Vec<i64> = body["data"]["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["amount"].as_i64().unwrap())
+        .collect();
+    assert_eq!(amounts, vec![102, 103]);
+    assert_eq!(body["data"]["total"].as_u64().unwrap(), 5);
+    assert_eq!(body["data"]["page"].as_u64().unwrap(), 2);
+
+    let body = get("status=Success&per_page=2").await;
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"]["total"].as_u64().unwrap(), 3);
+
+    let body = get("status=Pending&limit=1").await;
+    assert_eq!(body["data"]["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["data"]["per_page"].as_u64().unwrap(), 1);
+    assert_eq!(body["data"]["total"].as_u64().unwrap(), 2);
+
+    let body = get("per_page=101").await;
+    assert_field_rejected(&body, "per_page");
+}
+
+#[tokio::test]
+async fn test_batch_get_returns_only_owned_transactions() {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Cart User".to_string(),
+        "cart@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service.clone())
+        .mount("/api/transactions", transaction_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let mine = service
+        .create_transaction(user.id, None, 100, "Mine".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    let theirs = service
+        .create_transaction(Uuid::new_v4(), None, 200, "Theirs".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    let body = format!(
+        r#"{{"ids":["{}","{}","{}"]}}"#,
+        mine.id,
+        theirs.id,
+        Uuid::new_v4()
+    );
+
+    let body = post_json(&client, "/api/transactions/batch-get", &format!("Bearer {}", token), body).await;
+    assert!(body["success"].as_bool().unwrap());
+    let ids: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec![mine.id.to_string()]);
+}
+
+#[tokio::test]
+async fn test_pending_purchase_can_be_resumed_from_another_session() {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Resume User".to_string(),
+        "resume@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let first_session = auth_service.generate_token(&user).await.unwrap().access_token;
+    let second_session = auth_service.generate_token(&user).await.unwrap().access_token;
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service.clone())
+        .mount("/api/transactions", transaction_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":750,"description":"Ticket","payment_method":"credit_card"}}"#,
+        user.id
+    );
+    let body = post_json(&client, "/api/transactions", &format!("Bearer {}", first_session), body).await;
+    let transaction_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let response = client
+        .get(format!("/api/transactions/{}/resume", transaction_id))
+        .header(Header::new("Authorization", format!("Bearer {}", second_session)))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["data"]["step"], "awaiting_payment");
+    assert_eq!(
+        body["data"]["next_action"].as_str().unwrap(),
+        format!("PUT /api/transactions/{}/process", transaction_id)
+    );
+
+    let response = client
+        .put(format!("/api/transactions/{}/process", transaction_id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", format!("Bearer {}", second_session)))
+        .body(r#"{"external_reference":null}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["data"]["status"], "Success");
+
+    let response = client
+        .get(format!("/api/transactions/{}/resume", transaction_id))
+        .header(Header::new("Authorization", format!("Bearer {}", first_session)))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["data"]["step"], "completed");
+    assert!(body["data"]["next_action"].is_null());
+}
+
+async fn dashboard_client() -> (Client, Arc<dyn TransactionService + Send + Sync>, Uuid, String) {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Dashboard User".to_string(),
+        "dashboard@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service.clone())
+        .mount("/api/transactions", transaction_routes())
+        .mount("/api/users", user_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    (client, service, user.id, format!("Bearer {}", token))
+}
+
+#[tokio::test]
+async fn test_user_dashboard_bundles_balance_and_recent_transactions() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+    service.add_funds_to_balance(user_id, 5000, "bank_transfer".to_string()).await.unwrap();
+
+    let mut created = Vec::new();
+    for amount in 1..=12 {
+        let transaction = service
+            .create_transaction(user_id, None, amount * 100, format!("Ticket {}", amount), "credit_card".to_string())
+            .await
+            .unwrap();
+        created.push(transaction.id);
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+    service.process_payment(created[0], None).await.unwrap();
+
+    let response = client
+        .get(format!("/api/users/{}/dashboard", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    let data = &body["data"];
+
+    assert_eq!(data["balance"]["amount"].as_i64().unwrap(), 5000);
+    assert_eq!(data["pending_count"].as_u64().unwrap(), 11);
+
+    let recent: Vec<String> = data["recent_transactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["id"].as_str().unwrap().to_string())
+        .collect();
+    let expected: Vec<String> = created.iter().rev().take(10).map(|id| id.to_string()).collect();
+    assert_eq!(recent, expected);
+}
+
+#[tokio::test]
+async fn test_user_dashboard_pending_count_skips_expired_links() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+    service
+        .create_transaction(user_id, None, 100, "Open checkout".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service
+        .create_payment_link(
+            user_id,
+            None,
+            200,
+            "Lapsed link".to_string(),
+            "credit_card".to_string(),
+            Utc::now() - chrono::Duration::minutes(1),
+        )
+        .await
+        .unwrap();
+
+    let response = client
+        .get(format!("/api/users/{}/dashboard", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+
+    assert_eq!(body["data"]["pending_count"].as_u64().unwrap(), 1);
+    assert_eq!(body["data"]["recent_transactions"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_user_dashboard_forbids_other_users() {
+    let (client, _, _, bearer) = dashboard_client().await;
+
+    let response = client
+        .get(format!("/api/users/{}/dashboard", Uuid::new_v4()))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[tokio::test]
+async fn test_user_refunds_lists_only_refunded_transactions() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+
+    let refunded = service
+        .create_transaction(user_id, None, 300, "Refunded ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.process_payment(refunded.id, None).await.unwrap();
+    service.refund_transaction(refunded.id, customer_refund_reason(), None).await.unwrap();
+
+    let purchased = service
+        .create_transaction(user_id, None, 500, "Kept ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.process_payment(purchased.id, None).await.unwrap();
+
+    let response = client
+        .get(format!("/api/users/{}/refunds", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    let data = &body["data"];
+
+    let ids: Vec<&str> = data["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(ids, vec![refunded.id.to_string()]);
+    assert_eq!(data["total"].as_u64().unwrap(), 1);
+    assert_eq!(data["page"].as_u64().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_refund_reason_appears_in_refund_history() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+
+    let transaction = service
+        .create_transaction(user_id, None, 300, "Cancelled show".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.process_payment(transaction.id, None).await.unwrap();
+
+    let response = client
+        .put(format!("/api/transactions/{}/refund", transaction.id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", bearer.clone()))
+        .body(r#"{"reason":"event_cancelled","note":"Venue closed"}"#)
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(body["success"].as_bool().unwrap(), "{}", body);
+    assert_eq!(body["data"]["refund_reason"]["category"], "event_cancelled");
+
+    let response = client
+        .get(format!("/api/users/{}/refunds", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    let reason = &body["data"]["items"][0]["refund_reason"];
+    assert_eq!(reason["category"], "event_cancelled");
+    assert_eq!(reason["note"], "Venue closed");
+}
+
+#[tokio::test]
+async fn test_refund_requires_reason() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+
+    let transaction = service
+        .create_transaction(user_id, None, 300, "Ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.process_payment(transaction.id, None).await.unwrap();
+
+    let response = client
+        .put(format!("/api/transactions/{}/refund", transaction.id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", bearer))
+        .body("{}")
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::UnprocessableEntity);
+
+    let stored = service.get_transaction(transaction.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, TransactionStatus::Success);
+}
+
+#[tokio::test]
+async fn test_refund_destination_override_is_admin_only() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+
+    let transaction = service
+        .create_transaction(user_id, None, 300, "Ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.process_payment(transaction.id, None).await.unwrap();
+
+    let response = client
+        .put(format!("/api/transactions/{}/refund", transaction.id))
+        .header(ContentType::JSON)
+        .header(Header::new("Authorization", bearer))
+        .body(r#"{"reason":"customer_request","refund_to":"balance"}"#)
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let stored = service.get_transaction(transaction.id).await.unwrap().unwrap();
+    assert_eq!(stored.status, TransactionStatus::Success);
+}
+
+#[tokio::test]
+async fn test_transactions_by_category_returns_only_that_category() {
+    let (client, service, user_id, bearer) = dashboard_client().await;
+    service
+        .create_transaction(user_id, Some(Uuid::new_v4()), 1500, "Concert".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    for amount in [1000, 2000, 3000] {
+        service.add_funds_to_balance(user_id, amount, "bank_transfer".to_string()).await.unwrap();
+    }
+
+    let response = client
+        .get(format!("/api/users/{}/transactions/by-category/topup?per_page=2", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    let data = &body["data"];
+
+    let items = data["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|t| t["category"] == "Topup"));
+    assert_eq!(data["total"].as_u64().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn test_transactions_by_category_rejects_unknown_category() {
+    let (client, _, user_id, bearer) = dashboard_client().await;
+
+    let response = client
+        .get(format!("/api/users/{}/transactions/by-category/lottery", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_field_rejected(&body, "category");
+}
+
+#[tokio::test]
+async fn test_transactions_by_category_forbids_other_users() {
+    let (client, _, _, bearer) = dashboard_client().await;
+
+    let response = client
+        .get(format!("/api/users/{}/transactions/by-category/topup", Uuid::new_v4()))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[tokio::test]
+async fn test_user_refunds_rejects_zero_page() {
+    let (client, _, user_id, bearer) = dashboard_client().await;
+
+    let response = client
+        .get(format!("/api/users/{}/refunds?page=0", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Ok);
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_field_rejected(&body, "page");
+}
+
+#[tokio::test]
+async fn test_empty_transaction_list_returns_empty_array() {
+    let (client, _, user_id, bearer) = dashboard_client().await;
+
+    let body = post_json(&client, "/api/transactions/batch-get", &bearer, r#"{"ids":[]}"#.to_string()).await;
+    assert!(body["success"].as_bool().unwrap());
+    assert_eq!(body["data"], rocket::serde::json::json!([]));
+
+    // Failed collection requests keep the [] shape too
+    let too_many: Vec<String> = (0..=100).map(|_| format!("\"{}\"", Uuid::new_v4())).collect();
+    let body = post_json(
+        &client,
+        "/api/transactions/batch-get",
+        &bearer,
+        format!(r#"{{"ids":[{}]}}"#, too_many.join(",")),
+    )
+    .await;
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["data"], rocket::serde::json::json!([]));
+
+    // Paged listings keep an empty page
+    let response = client
+        .get(format!("/api/users/{}/transactions", user_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(body["success"].as_bool().unwrap());
+    assert_eq!(body["data"]["items"], rocket::serde::json::json!([]));
+    assert_eq!(body["data"]["total"].as_u64().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn test_missing_transaction_returns_null_data() {
+    let (client, _, bearer) = rocket_client().await;
+
+    let response = client
+        .get(format!("/api/transactions/{}", Uuid::new_v4()))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 404);
+    assert!(body["data"].is_null());
+}
+
+async fn admin_transaction_client(role: UserRole) -> (Client, Arc<dyn TransactionService + Send + Sync>, String) {
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Reconciliation User".to_string(),
+        "reconciliation@example.com".to_string(),
+        "hash".to_string(),
+        role,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service.clone())
+        .mount("/api", admin_transaction_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    (client, service, format!("Bearer {}", token))
+}
+
+#[tokio::test]
+async fn test_admin_finds_transaction_by_reference() {
+    let (client, service, bearer) = admin_transaction_client(UserRole::Admin).await;
+    let transaction = service
+        .create_transaction(Uuid::new_v4(), None, 1000, "Ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    service.process_payment(transaction.id, Some("PG-RECON-7".to_string())).await.unwrap();
+
+    let response = client
+        .get("/api/admin/transactions/by-reference/PG-RECON-7")
+        .header(Header::new("Authorization", bearer.clone()))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(body["success"].as_bool().unwrap());
+    assert_eq!(body["data"]["id"].as_str().unwrap(), transaction.id.to_string());
+
+    let response = client
+        .get("/api/admin/transactions/by-reference/PG-MISSING")
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 404);
+}
+
+#[tokio::test]
+async fn test_transaction_by_reference_requires_admin() {
+    let (client, _, bearer) = admin_transaction_client(UserRole::Attendee).await;
+
+    let response = client
+        .get("/api/admin/transactions/by-reference/PG-RECON-7")
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+async fn ownership_client(policy: Option<OwnershipPolicy>) -> (Client, Uuid, String) {
+    let (auth_service, service) = setup_rocket_client_state();
+    let intruder = User::new(
+        "Intruder".to_string(),
+        "intruder@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&intruder).await.unwrap().access_token;
+    let victims_transaction = service
+        .create_transaction(Uuid::new_v4(), None, 1000, "Ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+
+    let mut rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service)
+        .mount("/api/transactions", transaction_routes());
+    if let Some(policy) = policy {
+        rocket = rocket.manage(policy);
+    }
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    (client, victims_transaction.id, format!("Bearer {}", token))
+}
+
+#[tokio::test]
+async fn test_other_users_transaction_looks_missing_under_enumeration_safe_policy() {
+    let (client, transaction_id, bearer) = ownership_client(None).await;
+
+    let response = client
+        .get(format!("/api/transactions/{}", transaction_id))
+        .header(Header::new("Authorization", bearer.clone()))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    let owned_elsewhere: rocket::serde::json::Value = response.into_json().await.unwrap();
+
+    let response = client
+        .get(format!("/api/transactions/{}", Uuid::new_v4()))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    let missing: rocket::serde::json::Value = response.into_json().await.unwrap();
+
+    assert_eq!(owned_elsewhere["status_code"].as_u64().unwrap(), 404);
+    assert_eq!(owned_elsewhere, missing);
+}
+
+#[tokio::test]
+async fn test_other_users_transaction_is_forbidden_when_policy_disabled() {
+    let (client, transaction_id, bearer) = ownership_client(Some(OwnershipPolicy::Forbidden)).await;
+
+    let response = client
+        .delete(format!("/api/transactions/{}", transaction_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[tokio::test]
+async fn test_retry_payment_rejects_transaction_that_did_not_fail() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":750,"description":"Ticket","payment_method":"credit_card"}}"#,
+        user_id
+    );
+    let body = post_json(&client, "/api/transactions", &bearer, body).await;
+    let path = format!("/api/transactions/{}/retry-payment", body["data"]["id"].as_str().unwrap());
+
+    let body = post_json(&client, &path, &bearer, String::new()).await;
+    assert!(!body["success"].as_bool().unwrap());
+    assert_eq!(body["status_code"].as_u64().unwrap(), 400);
+    assert_eq!(body["message"], "Only failed transactions can be retried");
+}
+
+#[tokio::test]
+async fn test_transaction_status_returns_minimal_shape() {
+    let (client, user_id, bearer) = rocket_client().await;
+    let body = format!(
+        r#"{{"user_id":"{}","ticket_id":null,"amount":750,"description":"Ticket","payment_method":"credit_card"}}"#,
+        user_id
+    );
+    let body = post_json(&client, "/api/transactions", &bearer, body).await;
+    let transaction_id = body["data"]["id"].as_str().unwrap().to_string();
+
+    let response = client
+        .get(format!("/api/transactions/{}/status", transaction_id))
+        .header(Header::new("Authorization", bearer.clone()))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+
+    let data = body["data"].as_object().unwrap();
+    let mut keys: Vec<&str> = data.keys().map(String::as_str).collect();
+    keys.sort();
+    assert_eq!(keys, vec!["id", "status", "updated_at"]);
+    assert_eq!(data["id"], transaction_id.as_str());
+    assert_eq!(data["status"], "Pending");
+
+    let response = client
+        .get(format!("/api/transactions/{}/status", Uuid::new_v4()))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+    let body: rocket::serde::json::Value = response.into_json().await.unwrap();
+    assert_eq!(body["status_code"].as_u64().unwrap(), 404);
+}
+
+#[tokio::test]
+async fn test_transaction_status_of_other_user_is_forbidden() {
+    let (client, transaction_id, bearer) = ownership_client(Some(OwnershipPolicy::Forbidden)).await;
+
+    let response = client
+        .get(format!("/api/transactions/{}/status", transaction_id))
+        .header(Header::new("Authorization", bearer))
+        .dispatch()
+        .await;
+
+    assert_eq!(response.status(), Status::Forbidden);
+}
+
+#[tokio::test]
+async fn test_large_listing_is_gzipped_when_accepted() {
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    let (auth_service, service) = setup_rocket_client_state();
+    let user = User::new(
+        "Compression User".to_string(),
+        "compression@example.com".to_string(),
+        "hash".to_string(),
+        UserRole::Attendee,
+    );
+    let token = auth_service.generate_token(&user).await.unwrap().access_token;
+    for i in 0..30 {
+        service
+            .create_transaction(user.id, None, 1000 + i, format!("Ticket {}", i), "credit_card".to_string())
+            .await
+            .unwrap();
+    }
+
+    let rocket = rocket::build()
+        .manage(auth_service)
+        .manage(service)
+        .attach(CompressionFairing::new(512))
+        .mount("/api/users", user_routes());
+    let client = Client::tracked(rocket).await.expect("valid rocket instance");
+    let path = format!("/api/users/{}/transactions?per_page=30", user.id);
+    let bearer = format!("Bearer {}", token);
+
+    let response = client
+        .get(path.clone())
+        .header(Header::new("Authorization", bearer.clone()))
+        .dispatch()
+        .await;
+    assert!(response.headers().get_one("Content-Encoding").is_none());
+    let plain: rocket::serde::json::Value = response.into_json().await.unwrap();
+
+    let response = client
+        .get(path)
+        .header(Header::new("Authorization", bearer))
+        .header(Header::new("Accept-Encoding", "br;q=1.0, gzip;q=0.8"))
+        .dispatch()
+        .await;
+    assert_eq!(response.headers().get_one("Content-Encoding"), Some("gzip"));
+    let compressed = response.into_bytes().await.unwrap();
+
+    let mut decoded = String::new();
+    GzDecoder::new(compressed.as_slice()).read_to_string(&mut decoded).unwrap();
+    let decoded: rocket::serde::json::Value = serde_json::from_str(&decoded).unwrap();
+    assert_eq!(decoded["data"]["items"].as_array().unwrap().len(), 30);
+    assert_eq!(decoded, plain);
+}
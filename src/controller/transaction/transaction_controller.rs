@@ -1,5 +1,6 @@
+use chrono::{DateTime, Utc};
 use rocket::http::uri::fmt::{FromUriParam, Part, UriDisplay};
-use rocket::request::FromParam;
+use rocket::request::{FromParam, FromRequest, Outcome, Request};
 use rocket::{Route, State, delete, get, http::Status, post, put, routes, serde::json::Json};
 use serde::{Deserialize, Serialize};
 use std::fmt::Write;
@@ -7,8 +8,29 @@ use std::str::FromStr;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::model::transaction::{Transaction, Balance};
-use crate::service::transaction::transaction_service::TransactionService;
+use crate::common::pagination::{Page, Pagination};
+use crate::common::sort::SortOrder;
+use crate::controller::json_body::JsonBody;
+use crate::common::validation::{DESCRIPTION_MAX_LENGTH, DESCRIPTION_MIN_LENGTH, validate_text_field};
+use crate::error::ValidationError;
+use crate::middleware::ownership::OwnershipPolicy;
+use crate::model::transaction::{
+    normalize_payment_method, Balance, BalanceAdjustment, RefundPolicy, RefundReason, RefundReasonCategory, Transaction, TransactionCategory,
+    TransactionStatus, TransactionStatusSnapshot,
+};
+use crate::repository::transaction::transaction_repo::{
+    AlreadyRefunded, TRANSACTION_SORT_KEYS, TransactionFilter,
+};
+use crate::service::transaction::balance_adjustment_service::{
+    AdjustmentError, BalanceAdjustmentService,
+};
+use crate::service::transaction::payment_circuit_breaker::PaymentUnavailable;
+use crate::service::transaction::payment_webhook_service::{
+    PaymentWebhookEvent, PaymentWebhookService, WebhookOutcome,
+};
+use crate::service::transaction::transaction_service::{
+    PaymentNotRetryable, TooManyPendingCheckouts, TransactionService,
+};
 
 pub struct UuidParam(pub Uuid);
 
@@ -57,9 +79,39 @@ where
     pub data: Option<T>,
 }
 
+/// Payload types an `ApiResponse` can carry. `empty` is the `data` value of a
+/// response without a payload: collections stay `[]` so clients can iterate
+/// without a null check, single resources become `null`.
+pub trait ResponseData: Serialize {
+    fn empty() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+impl<T: Serialize> ResponseData for Vec<T> {
+    fn empty() -> Option<Self> {
+        Some(Vec::new())
+    }
+}
+
+impl ResponseData for () {}
+impl ResponseData for bool {}
+impl ResponseData for Transaction {}
+impl ResponseData for TransactionStatusSnapshot {}
+impl ResponseData for Balance {}
+impl ResponseData for BalanceAdjustment {}
+impl ResponseData for BalanceResponse {}
+impl ResponseData for UserDashboardResponse {}
+impl ResponseData for ResumePurchaseResponse {}
+impl ResponseData for CheckoutPreviewResponse {}
+impl<T: Serialize> ResponseData for Page<T> {}
+
 impl<T> ApiResponse<T>
 where
-    T: Serialize,
+    T: ResponseData,
 {
     pub fn success(message: &str, data: T) -> Json<Self> {
         Json(Self {
@@ -75,7 +127,7 @@ where
             success: true,
             status_code,
             message: message.to_string(),
-            data: None,
+            data: T::empty(),
         })
     }
 
@@ -84,7 +136,7 @@ where
             success: false,
             status_code,
             message: message.to_string(),
-            data: None,
+            data: T::empty(),
         })
     }
 }
@@ -93,9 +145,13 @@ where
 pub struct CreateTransactionRequest {
     pub user_id: Uuid,
     pub ticket_id: Option<Uuid>,
+    #[serde(deserialize_with = "crate::common::amount::deserialize")]
     pub amount: i64,
     pub description: String,
     pub payment_method: String,
+    /// Set for payment links; the transaction can't be paid after this
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -106,6 +162,7 @@ pub struct ProcessPaymentRequest {
 #[derive(Debug, Deserialize)]
 pub struct AddFundsRequest {
     pub user_id: Uuid,
+    #[serde(deserialize_with = "crate::common::amount::deserialize")]
     pub amount: i64,
     pub payment_method: String,
 }
@@ -113,22 +170,301 @@ pub struct AddFundsRequest {
 #[derive(Debug, Deserialize)]
 pub struct WithdrawFundsRequest {
     pub user_id: Uuid,
+    #[serde(deserialize_with = "crate::common::amount::deserialize")]
     pub amount: i64,
     pub description: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AdjustBalanceRequest {
+    #[serde(deserialize_with = "crate::common::amount::deserialize")]
+    pub delta: i64,
+    pub reason: String,
+    #[serde(default)]
+    pub allow_negative: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefundTransactionRequest {
+    pub reason: RefundReasonCategory,
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Admin-only override of the configured refund policy
+    #[serde(default)]
+    pub refund_to: Option<RefundPolicy>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchGetTransactionsRequest {
+    pub ids: Vec<Uuid>,
+}
+
+/// Upper bound on ids per batch-get call
+pub const MAX_BATCH_TRANSACTION_IDS: usize = 100;
+
+fn check_positive_amount(amount: i64, errors: &mut Vec<ValidationError>) {
+    if amount <= 0 {
+        errors.push(ValidationError {
+            field: "amount".to_string(),
+            message: "must be greater than zero".to_string(),
+        });
+    }
+}
+
+fn check_description(description: &str, errors: &mut Vec<ValidationError>) {
+    if let Err(e) = validate_text_field(
+        description,
+        DESCRIPTION_MIN_LENGTH,
+        DESCRIPTION_MAX_LENGTH,
+        "description",
+    ) {
+        errors.push(e);
+    }
+}
+
+fn check_payment_method(payment_method: &str, accepted: &[String], errors: &mut Vec<ValidationError>) {
+    let normalized = normalize_payment_method(payment_method);
+    if !accepted.contains(&normalized) {
+        errors.push(ValidationError {
+            field: "payment_method".to_string(),
+            message: format!("must be one of: {}", accepted.join(", ")),
+        });
+    }
+}
+
+fn into_result(errors: Vec<ValidationError>) -> Result<(), Vec<ValidationError>> {
+    if errors.is_empty() { Ok(()) } else { Err(errors) }
+}
+
+impl CreateTransactionRequest {
+    pub fn validate(&self, payment_methods: &[String]) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        check_positive_amount(self.amount, &mut errors);
+        check_description(&self.description, &mut errors);
+        check_payment_method(&self.payment_method, payment_methods, &mut errors);
+        if self.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+            errors.push(ValidationError {
+                field: "expires_at".to_string(),
+                message: "must be in the future".to_string(),
+            });
+        }
+        into_result(errors)
+    }
+}
+
+impl AddFundsRequest {
+    pub fn validate(&self, payment_methods: &[String]) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        check_positive_amount(self.amount, &mut errors);
+        check_payment_method(&self.payment_method, payment_methods, &mut errors);
+        into_result(errors)
+    }
+}
+
+impl WithdrawFundsRequest {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        check_positive_amount(self.amount, &mut errors);
+        check_description(&self.description, &mut errors);
+        into_result(errors)
+    }
+}
+
+impl AdjustBalanceRequest {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if self.delta == 0 {
+            errors.push(ValidationError {
+                field: "delta".to_string(),
+                message: "must not be zero".to_string(),
+            });
+        }
+        if let Err(e) = validate_text_field(&self.reason, DESCRIPTION_MIN_LENGTH, DESCRIPTION_MAX_LENGTH, "reason") {
+            errors.push(e);
+        }
+        into_result(errors)
+    }
+}
+
+impl RefundTransactionRequest {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if let Some(Err(e)) = self
+            .note
+            .as_deref()
+            .map(|note| validate_text_field(note, DESCRIPTION_MIN_LENGTH, DESCRIPTION_MAX_LENGTH, "note"))
+        {
+            errors.push(e);
+        }
+        into_result(errors)
+    }
+
+    pub fn into_reason(self) -> RefundReason {
+        RefundReason {
+            category: self.reason,
+            note: self.note.map(|note| note.trim().to_string()),
+        }
+    }
+}
+
+impl BatchGetTransactionsRequest {
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        if self.ids.len() > MAX_BATCH_TRANSACTION_IDS {
+            errors.push(ValidationError {
+                field: "ids".to_string(),
+                message: format!("must contain at most {} entries", MAX_BATCH_TRANSACTION_IDS),
+            });
+        }
+        into_result(errors)
+    }
+}
+
+fn parse_category(raw: &str) -> Result<TransactionCategory, ValidationError> {
+    raw.parse().map_err(|_| ValidationError {
+        field: "category".to_string(),
+        message: "must be one of: ticket, topup, withdrawal, fee, refund, comp".to_string(),
+    })
+}
+
+fn parse_status(raw: &str) -> Result<TransactionStatus, ValidationError> {
+    raw.parse().map_err(|_| ValidationError {
+        field: "status".to_string(),
+        message: "must be one of: Pending, Success, Failed, Refunded".to_string(),
+    })
+}
+
+fn parse_date(raw: &str, field: &str) -> Result<DateTime<Utc>, ValidationError> {
+    DateTime::parse_from_rfc3339(raw)
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(|_| ValidationError {
+            field: field.to_string(),
+            message: "must be an RFC3339 timestamp".to_string(),
+        })
+}
+
+/// Builds the history filter from the raw query values, collecting every
+/// problem rather than stopping at the first
+fn parse_transaction_filter(
+    status: Option<&str>,
+    from_date: Option<&str>,
+    to_date: Option<&str>,
+) -> Result<TransactionFilter, Vec<ValidationError>> {
+    let mut errors = Vec::new();
+    let mut filter = TransactionFilter::default();
+
+    match status.map(parse_status).transpose() {
+        Ok(status) => filter.status = status,
+        Err(e) => errors.push(e),
+    }
+    match from_date.map(|raw| parse_date(raw, "from_date")).transpose() {
+        Ok(from_date) => filter.from_date = from_date,
+        Err(e) => errors.push(e),
+    }
+    match to_date.map(|raw| parse_date(raw, "to_date")).transpose() {
+        Ok(to_date) => filter.to_date = to_date,
+        Err(e) => errors.push(e),
+    }
+    if let (Some(from), Some(to)) = (filter.from_date, filter.to_date)
+        && from > to
+    {
+        errors.push(ValidationError {
+            field: "from_date".to_string(),
+            message: "must not be after to_date".to_string(),
+        });
+    }
+
+    if errors.is_empty() { Ok(filter) } else { Err(errors) }
+}
+
+fn validation_error<T: ResponseData>(errors: &[ValidationError]) -> Json<ApiResponse<T>> {
+    let details: Vec<String> = errors
+        .iter()
+        .map(|e| format!("{} {}", e.field, e.message))
+        .collect();
+    ApiResponse::error(400, &format!("Validation failed: {}", details.join("; ")))
+}
+
+/// Answer for a transaction the caller doesn't own, per the configured policy
+fn transaction_not_owned<T: ResponseData>(policy: OwnershipPolicy) -> Result<Json<ApiResponse<T>>, Status> {
+    match policy {
+        OwnershipPolicy::Forbidden => Err(Status::Forbidden),
+        OwnershipPolicy::NotFound => Ok(ApiResponse::error(404, "Transaction not found")),
+    }
+}
+
 #[derive(Debug, Serialize)]
 pub struct BalanceResponse {
     pub balance: i64,
 }
 
+/// Where an interrupted purchase stands
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurchaseStep {
+    AwaitingPayment,
+    Expired,
+    Completed,
+    Failed,
+    Refunded,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResumePurchaseResponse {
+    pub step: PurchaseStep,
+    /// Request that moves the purchase forward, if it can still move
+    pub next_action: Option<String>,
+    pub transaction: Transaction,
+}
+
+impl ResumePurchaseResponse {
+    fn for_transaction(transaction: Transaction, now: DateTime<Utc>) -> Self {
+        let step = match transaction.status {
+            TransactionStatus::Pending if transaction.is_expired(now) => PurchaseStep::Expired,
+            TransactionStatus::Pending => PurchaseStep::AwaitingPayment,
+            TransactionStatus::Success => PurchaseStep::Completed,
+            TransactionStatus::Failed => PurchaseStep::Failed,
+            TransactionStatus::Refunded => PurchaseStep::Refunded,
+        };
+        let next_action = (step == PurchaseStep::AwaitingPayment)
+            .then(|| format!("PUT /api/transactions/{}/process", transaction.id));
+
+        Self { step, next_action, transaction }
+    }
+}
+
+/// What a checkout would charge, computed without creating anything. There is
+/// no promo engine yet, so `discount` is always zero.
+#[derive(Debug, Serialize)]
+pub struct CheckoutPreviewResponse {
+    pub subtotal: i64,
+    pub discount: i64,
+    pub fees: i64,
+    pub total: i64,
+}
+
+/// Number of transactions bundled into the dashboard response
+pub const DASHBOARD_RECENT_TRANSACTIONS: usize = 10;
+
+#[derive(Debug, Serialize)]
+pub struct UserDashboardResponse {
+    pub balance: Balance,
+    pub recent_transactions: Vec<Transaction>,
+    pub pending_count: usize,
+}
+
 pub fn transaction_routes() -> Vec<Route> {
     routes![
         create_transaction_handler,
+        preview_checkout_handler,
         process_payment_handler,
+        retry_payment_handler,
         validate_payment_handler,
         refund_transaction_handler,
         get_transaction_handler,
+        get_transaction_status_handler,
+        resume_purchase_handler,
+        batch_get_transactions_handler,
         delete_transaction_handler
     ]
 }
@@ -140,17 +476,32 @@ pub fn balance_routes() -> Vec<Route> {
     ]
 }
 
+pub fn admin_balance_routes() -> Vec<Route> {
+    routes![adjust_balance_handler]
+}
+
+pub fn admin_transaction_routes() -> Vec<Route> {
+    routes![get_transaction_by_reference_handler]
+}
+
+pub fn webhook_routes() -> Vec<Route> {
+    routes![payment_webhook_handler]
+}
+
 pub fn user_routes() -> Vec<Route> {
     routes![
         get_user_transactions_handler,
-        get_user_balance_handler
+        get_user_transactions_by_category_handler,
+        get_user_balance_handler,
+        get_user_dashboard_handler,
+        get_user_refunds_handler
     ]
 }
 
 #[post("/", data = "<req>")]
 pub async fn create_transaction_handler(
     token: crate::middleware::auth::JwtToken,
-    req: Json<CreateTransactionRequest>,
+    req: JsonBody<CreateTransactionRequest>,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<Transaction>>, Status> {
     // Verify the authenticated user matches the user_id in the request or is admin
@@ -163,22 +514,46 @@ pub async fn create_transaction_handler(
         return Err(Status::Forbidden);
     }
 
-    match service
-        .create_transaction(
-            req.user_id,
-            req.ticket_id,
-            req.amount,
-            req.description.clone(),
-            req.payment_method.clone(),
-        )
-        .await
-    {
+    if let Err(errors) = req.validate(service.payment_methods()) {
+        return Ok(validation_error(&errors));
+    }
+
+    let created = match req.expires_at {
+        Some(expires_at) => {
+            service
+                .create_payment_link(
+                    req.user_id,
+                    req.ticket_id,
+                    req.amount,
+                    req.description.clone(),
+                    req.payment_method.clone(),
+                    expires_at,
+                )
+                .await
+        }
+        None => {
+            service
+                .create_transaction(
+                    req.user_id,
+                    req.ticket_id,
+                    req.amount,
+                    req.description.clone(),
+                    req.payment_method.clone(),
+                )
+                .await
+        }
+    };
+
+    match created {
         Ok(transaction) => Ok(ApiResponse::success(
             "Transaction created successfully",
             transaction,
         )),
         Err(e) => {
             eprintln!("Failed to create transaction: {:?}", e);
+            if e.downcast_ref::<TooManyPendingCheckouts>().is_some() {
+                return Ok(ApiResponse::error(429, &e.to_string()));
+            }
             Ok(ApiResponse::error(
                 500,
                 &format!("Failed to create transaction: {}", e),
@@ -187,11 +562,48 @@ pub async fn create_transaction_handler(
     }
 }
 
+/// Takes the same body as `POST /` and returns the charge breakdown that
+/// checkout would produce
+#[post("/preview", data = "<req>")]
+pub async fn preview_checkout_handler(
+    token: crate::middleware::auth::JwtToken,
+    req: JsonBody<CreateTransactionRequest>,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<CheckoutPreviewResponse>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    if token_user_id != req.user_id && !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    if let Err(errors) = req.validate(service.payment_methods()) {
+        return Ok(validation_error(&errors));
+    }
+
+    let subtotal = req.amount;
+    let discount = 0;
+    let fees = service.quote_fee(subtotal - discount, &req.payment_method);
+
+    Ok(ApiResponse::success(
+        "Checkout preview calculated",
+        CheckoutPreviewResponse {
+            subtotal,
+            discount,
+            fees,
+            total: subtotal - discount + fees,
+        },
+    ))
+}
+
 #[put("/<transaction_id>/process", data = "<req>")]
 pub async fn process_payment_handler(
     token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
     transaction_id: UuidParam,
-    req: Json<ProcessPaymentRequest>,
+    req: JsonBody<ProcessPaymentRequest>,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<Transaction>>, Status> {
     // Check if the transaction belongs to the authenticated user or user is admin
@@ -208,7 +620,7 @@ pub async fn process_payment_handler(
     };
 
     if transaction.user_id != token_user_id && !token.is_admin() {
-        return Err(Status::Forbidden);
+        return transaction_not_owned(policy);
     }
 
     match service
@@ -221,6 +633,9 @@ pub async fn process_payment_handler(
         )),
         Err(e) => {
             eprintln!("Failed to process payment: {:?}", e);
+            if e.downcast_ref::<PaymentUnavailable>().is_some() {
+                return Ok(ApiResponse::error(503, &e.to_string()));
+            }
             Ok(ApiResponse::error(
                 500,
                 &format!("Failed to process payment: {}", e),
@@ -229,9 +644,54 @@ pub async fn process_payment_handler(
     }
 }
 
+#[post("/<transaction_id>/retry-payment")]
+pub async fn retry_payment_handler(
+    token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
+    transaction_id: UuidParam,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<Transaction>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    let transaction = match service.get_transaction(transaction_id.0).await {
+        Ok(Some(t)) => t,
+        Ok(None) => return Ok(ApiResponse::error(404, "Transaction not found")),
+        Err(e) => return Ok(ApiResponse::error(500, &format!("Failed to get transaction: {}", e))),
+    };
+
+    if transaction.user_id != token_user_id && !token.is_admin() {
+        return transaction_not_owned(policy);
+    }
+
+    match service.retry_payment(transaction_id.0).await {
+        Ok(transaction) if transaction.status == TransactionStatus::Success => Ok(ApiResponse::success(
+            "Payment processed successfully",
+            transaction,
+        )),
+        Ok(transaction) => Ok(ApiResponse::success("Payment was declined again", transaction)),
+        Err(e) => {
+            if e.downcast_ref::<PaymentNotRetryable>().is_some() {
+                return Ok(ApiResponse::error(400, &e.to_string()));
+            }
+            eprintln!("Failed to retry payment: {:?}", e);
+            if e.downcast_ref::<PaymentUnavailable>().is_some() {
+                return Ok(ApiResponse::error(503, &e.to_string()));
+            }
+            Ok(ApiResponse::error(
+                500,
+                &format!("Failed to retry payment: {}", e),
+            ))
+        }
+    }
+}
+
 #[get("/<transaction_id>/validate")]
 pub async fn validate_payment_handler(
     token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
     transaction_id: UuidParam,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<bool>>, Status> {
@@ -249,7 +709,7 @@ pub async fn validate_payment_handler(
     };
 
     if transaction.user_id != token_user_id && !token.is_admin() {
-        return Err(Status::Forbidden);
+        return transaction_not_owned(policy);
     }
 
     match service.validate_payment(transaction_id.0).await {
@@ -267,10 +727,12 @@ pub async fn validate_payment_handler(
     }
 }
 
-#[put("/<transaction_id>/refund")]
+#[put("/<transaction_id>/refund", data = "<req>")]
 pub async fn refund_transaction_handler(
     token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
     transaction_id: UuidParam,
+    req: JsonBody<RefundTransactionRequest>,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<Transaction>>, Status> {
     // Check if the transaction belongs to the authenticated user or user is admin
@@ -287,16 +749,31 @@ pub async fn refund_transaction_handler(
     };
 
     if transaction.user_id != token_user_id && !token.is_admin() {
+        return transaction_not_owned(policy);
+    }
+
+    if req.refund_to.is_some() && !token.is_admin() {
         return Err(Status::Forbidden);
     }
 
-    match service.refund_transaction(transaction_id.0).await {
+    if let Err(errors) = req.validate() {
+        return Ok(validation_error(&errors));
+    }
+
+    let refund_to = req.refund_to;
+    match service.refund_transaction(transaction_id.0, req.0.into_reason(), refund_to).await {
         Ok(transaction) => Ok(ApiResponse::success(
             "Transaction refunded successfully",
             transaction,
         )),
         Err(e) => {
+            if e.downcast_ref::<AlreadyRefunded>().is_some() {
+                return Ok(ApiResponse::error(409, &e.to_string()));
+            }
             eprintln!("Failed to refund transaction: {:?}", e);
+            if e.downcast_ref::<PaymentUnavailable>().is_some() {
+                return Ok(ApiResponse::error(503, &e.to_string()));
+            }
             Ok(ApiResponse::error(
                 500,
                 &format!("Failed to refund transaction: {}", e),
@@ -308,6 +785,7 @@ pub async fn refund_transaction_handler(
 #[get("/<transaction_id>")]
 pub async fn get_transaction_handler(
     token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
     transaction_id: UuidParam,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<Transaction>>, Status> {
@@ -320,7 +798,7 @@ pub async fn get_transaction_handler(
         Ok(Some(transaction)) => {
             // Verify the transaction belongs to the authenticated user or user is admin
             if transaction.user_id != token_user_id && !token.is_admin() {
-                return Err(Status::Forbidden);
+                return transaction_not_owned(policy);
             }
             Ok(ApiResponse::success("Transaction found", transaction))
         },
@@ -335,12 +813,123 @@ pub async fn get_transaction_handler(
     }
 }
 
-#[get("/<user_id>/transactions")]
+/// Cheap alternative to fetching the whole transaction when polling
+#[get("/<transaction_id>/status")]
+pub async fn get_transaction_status_handler(
+    token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
+    transaction_id: UuidParam,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<TransactionStatusSnapshot>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    match service.get_transaction_status(transaction_id.0).await {
+        Ok(Some(snapshot)) => {
+            if snapshot.user_id != token_user_id && !token.is_admin() {
+                return transaction_not_owned(policy);
+            }
+            Ok(ApiResponse::success("Transaction status found", snapshot))
+        }
+        Ok(None) => Ok(ApiResponse::error(404, "Transaction not found")),
+        Err(e) => {
+            eprintln!("Failed to get transaction status: {:?}", e);
+            Ok(ApiResponse::error(
+                500,
+                &format!("Failed to get transaction status: {}", e),
+            ))
+        }
+    }
+}
+
+/// Lets a client that lost track of a purchase pick it up again from any
+/// session the owner holds
+#[get("/<transaction_id>/resume")]
+pub async fn resume_purchase_handler(
+    token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
+    transaction_id: UuidParam,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<ResumePurchaseResponse>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    match service.get_transaction(transaction_id.0).await {
+        Ok(Some(transaction)) => {
+            if transaction.user_id != token_user_id && !token.is_admin() {
+                return transaction_not_owned(policy);
+            }
+            Ok(ApiResponse::success(
+                "Purchase state found",
+                ResumePurchaseResponse::for_transaction(transaction, Utc::now()),
+            ))
+        }
+        Ok(None) => Ok(ApiResponse::error(404, "Transaction not found")),
+        Err(e) => {
+            eprintln!("Failed to get transaction: {:?}", e);
+            Ok(ApiResponse::error(
+                500,
+                &format!("Failed to get transaction: {}", e),
+            ))
+        }
+    }
+}
+
+#[post("/batch-get", data = "<req>")]
+pub async fn batch_get_transactions_handler(
+    token: crate::middleware::auth::JwtToken,
+    req: JsonBody<BatchGetTransactionsRequest>,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<Vec<Transaction>>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    if let Err(errors) = req.validate() {
+        return Ok(validation_error(&errors));
+    }
+
+    match service.get_transactions_by_ids(&req.ids).await {
+        Ok(transactions) => {
+            // Other users' transactions are dropped rather than failing the batch
+            let visible: Vec<Transaction> = transactions
+                .into_iter()
+                .filter(|t| t.user_id == token_user_id || token.is_admin())
+                .collect();
+            Ok(ApiResponse::success("Transactions found", visible))
+        }
+        Err(e) => {
+            eprintln!("Failed to get transactions: {:?}", e);
+            Ok(ApiResponse::error(
+                500,
+                &format!("Failed to get transactions: {}", e),
+            ))
+        }
+    }
+}
+
+/// One page of the user's history, newest first unless `sort` says otherwise.
+/// `limit` is accepted as another name for `per_page`.
+#[get("/<user_id>/transactions?<sort>&<category>&<status>&<from_date>&<to_date>&<page>&<per_page>&<limit>")]
+#[allow(clippy::too_many_arguments)]
 pub async fn get_user_transactions_handler(
     token: crate::middleware::auth::JwtToken,
     user_id: UuidParam,
+    sort: Option<String>,
+    category: Option<String>,
+    status: Option<String>,
+    from_date: Option<String>,
+    to_date: Option<String>,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    limit: Option<usize>,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
-) -> Result<Json<ApiResponse<Vec<Transaction>>>, Status> {
+) -> Result<Json<ApiResponse<Page<Transaction>>>, Status> {
     let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
         Ok(id) => id,
         Err(_) => return Err(Status::Unauthorized),
@@ -351,18 +940,94 @@ pub async fn get_user_transactions_handler(
         return Err(Status::Forbidden);
     }
 
-    match service.get_user_transactions(user_id.0).await {
-        Ok(transactions) => Ok(ApiResponse::success(
-            "User transactions found",
-            transactions,
-        )),
+    let sort = match sort {
+        Some(raw) => match SortOrder::parse(&raw, &TRANSACTION_SORT_KEYS) {
+            Ok(sort) => sort,
+            Err(e) => return Ok(validation_error(&[e])),
+        },
+        None => SortOrder::descending("created_at"),
+    };
+
+    let category = match category.as_deref().map(parse_category).transpose() {
+        Ok(category) => category,
+        Err(e) => return Ok(validation_error(&[e])),
+    };
+
+    let mut filter = match parse_transaction_filter(
+        status.as_deref(),
+        from_date.as_deref(),
+        to_date.as_deref(),
+    ) {
+        Ok(filter) => filter,
+        Err(errors) => return Ok(validation_error(&errors)),
+    };
+    filter.category = category;
+
+    let pagination = match Pagination::parse(page, per_page.or(limit)) {
+        Ok(pagination) => pagination,
+        Err(errors) => return Ok(validation_error(&errors)),
+    };
+
+    match service
+        .get_user_transactions_paged(user_id.0, &filter, &sort, &pagination)
+        .await
+    {
+        Ok(page) => Ok(ApiResponse::success("User transactions found", page)),
+        Err(e) => {
+            eprintln!("Failed to get user transactions: {:?}", e);
+            Ok(ApiResponse::error(
+                500,
+                &format!("Failed to get user transactions: {}", e),
+            ))
+        }
+    }
+}
+
+#[get("/<user_id>/transactions/by-category/<category>?<page>&<per_page>")]
+pub async fn get_user_transactions_by_category_handler(
+    token: crate::middleware::auth::JwtToken,
+    user_id: UuidParam,
+    category: &str,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<Page<Transaction>>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    // Verify the requested user_id matches the authenticated user or user is admin
+    if user_id.0 != token_user_id && !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    let category = match parse_category(category) {
+        Ok(category) => category,
+        Err(e) => return Ok(validation_error(&[e])),
+    };
+    let pagination = match Pagination::parse(page, per_page) {
+        Ok(pagination) => pagination,
+        Err(errors) => return Ok(validation_error(&errors)),
+    };
+
+    let filter = TransactionFilter {
+        category: Some(category),
+        ..Default::default()
+    };
+    match service
+        .get_user_transactions_paged(user_id.0, &filter, &SortOrder::descending("created_at"), &pagination)
+        .await
+    {
+        Ok(page) => Ok(ApiResponse::success("User transactions found", page)),
         Err(e) => {
             eprintln!("Failed to get user transactions: {:?}", e);
             Ok(ApiResponse::error(
                 500,
                 &format!("Failed to get user transactions: {}", e),
             ))
-        }    }
+        }
+    }
 }
 
 #[get("/<user_id>/balance")]
@@ -394,10 +1059,112 @@ pub async fn get_user_balance_handler(
     }
 }
 
+#[get("/<user_id>/dashboard")]
+pub async fn get_user_dashboard_handler(
+    token: crate::middleware::auth::JwtToken,
+    user_id: UuidParam,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<UserDashboardResponse>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    // Verify the requested user_id matches the authenticated user or user is admin
+    if user_id.0 != token_user_id && !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    let balance = match service.get_user_balance(user_id.0).await {
+        Ok(balance) => balance,
+        Err(e) => {
+            eprintln!("Failed to get user balance: {:?}", e);
+            return Ok(ApiResponse::error(500, &format!("Failed to get user dashboard: {}", e)));
+        }
+    };
+
+    let recent_transactions = match service
+        .get_user_transactions_paged(
+            user_id.0,
+            &TransactionFilter::default(),
+            &SortOrder::descending("created_at"),
+            &Pagination::first(DASHBOARD_RECENT_TRANSACTIONS),
+        )
+        .await
+    {
+        Ok(page) => page.items,
+        Err(e) => {
+            eprintln!("Failed to get user transactions: {:?}", e);
+            return Ok(ApiResponse::error(500, &format!("Failed to get user dashboard: {}", e)));
+        }
+    };
+
+    let pending_count = match service.count_active_pending(user_id.0).await {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Failed to count pending transactions: {:?}", e);
+            return Ok(ApiResponse::error(500, &format!("Failed to get user dashboard: {}", e)));
+        }
+    };
+
+    Ok(ApiResponse::success(
+        "User dashboard found",
+        UserDashboardResponse {
+            balance,
+            recent_transactions,
+            pending_count,
+        },
+    ))
+}
+
+#[get("/<user_id>/refunds?<page>&<per_page>")]
+pub async fn get_user_refunds_handler(
+    token: crate::middleware::auth::JwtToken,
+    user_id: UuidParam,
+    page: Option<usize>,
+    per_page: Option<usize>,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<Page<Transaction>>>, Status> {
+    let token_user_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    // Verify the requested user_id matches the authenticated user or user is admin
+    if user_id.0 != token_user_id && !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    let pagination = match Pagination::parse(page, per_page) {
+        Ok(pagination) => pagination,
+        Err(errors) => return Ok(validation_error(&errors)),
+    };
+
+    // Refunds flip the purchase itself to Refunded, so each entry is also the
+    // original purchase
+    let filter = TransactionFilter {
+        status: Some(TransactionStatus::Refunded),
+        ..Default::default()
+    };
+    match service
+        .get_user_transactions_paged(user_id.0, &filter, &SortOrder::descending("updated_at"), &pagination)
+        .await
+    {
+        Ok(page) => Ok(ApiResponse::success("User refunds found", page)),
+        Err(e) => {
+            eprintln!("Failed to get user refunds: {:?}", e);
+            Ok(ApiResponse::error(
+                500,
+                &format!("Failed to get user refunds: {}", e),
+            ))
+        }
+    }
+}
+
 #[post("/add", data = "<req>")]
 pub async fn add_funds_handler(
     token: crate::middleware::auth::JwtToken,
-    req: Json<AddFundsRequest>,
+    req: JsonBody<AddFundsRequest>,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<BalanceResponse>>, Status> {
     // Verify the authenticated user matches the user_id in the request or is admin
@@ -408,7 +1175,13 @@ pub async fn add_funds_handler(
     
     if token_user_id != req.user_id && !token.is_admin() {
         return Err(Status::Forbidden);
-    }    match service
+    }
+
+    if let Err(errors) = req.validate(service.payment_methods()) {
+        return Ok(validation_error(&errors));
+    }
+
+    match service
         .add_funds_to_balance(req.user_id, req.amount, req.payment_method.clone())
         .await
     {
@@ -431,7 +1204,7 @@ pub async fn add_funds_handler(
 #[post("/withdraw", data = "<req>")]
 pub async fn withdraw_funds_handler(
     token: crate::middleware::auth::JwtToken,
-    req: Json<WithdrawFundsRequest>,
+    req: JsonBody<WithdrawFundsRequest>,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<BalanceResponse>>, Status> {
     // Verify the authenticated user matches the user_id in the request or is admin
@@ -442,7 +1215,13 @@ pub async fn withdraw_funds_handler(
     
     if token_user_id != req.user_id && !token.is_admin() {
         return Err(Status::Forbidden);
-    }    match service
+    }
+
+    if let Err(errors) = req.validate() {
+        return Ok(validation_error(&errors));
+    }
+
+    match service
         .withdraw_funds(req.user_id, req.amount, req.description.clone())
         .await
     {
@@ -468,6 +1247,7 @@ pub async fn withdraw_funds_handler(
 #[delete("/<transaction_id>")]
 pub async fn delete_transaction_handler(
     token: crate::middleware::auth::JwtToken,
+    policy: OwnershipPolicy,
     transaction_id: UuidParam,
     service: &State<Arc<dyn TransactionService + Send + Sync>>,
 ) -> Result<Json<ApiResponse<()>>, Status> {
@@ -485,7 +1265,7 @@ pub async fn delete_transaction_handler(
     };
 
     if transaction.user_id != token_user_id && !token.is_admin() {
-        return Err(Status::Forbidden);
+        return transaction_not_owned(policy);
     }
 
     match service.delete_transaction(transaction_id.0).await {
@@ -499,3 +1279,103 @@ pub async fn delete_transaction_handler(
         }
     }
 }
+
+#[get("/admin/transactions/by-reference/<reference>")]
+pub async fn get_transaction_by_reference_handler(
+    token: crate::middleware::auth::JwtToken,
+    reference: &str,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<Transaction>>, Status> {
+    if !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    match service.get_transaction_by_reference(reference).await {
+        Ok(Some(transaction)) => Ok(ApiResponse::success("Transaction found", transaction)),
+        Ok(None) => Ok(ApiResponse::error(404, "Transaction not found")),
+        Err(e) => {
+            eprintln!("Failed to get transaction by reference: {:?}", e);
+            Ok(ApiResponse::error(
+                500,
+                &format!("Failed to get transaction: {}", e),
+            ))
+        }
+    }
+}
+
+#[post("/admin/users/<user_id>/balance/adjust", data = "<req>")]
+pub async fn adjust_balance_handler(
+    token: crate::middleware::auth::JwtToken,
+    user_id: UuidParam,
+    req: JsonBody<AdjustBalanceRequest>,
+    adjustment_service: &State<Arc<dyn BalanceAdjustmentService + Send + Sync>>,
+) -> Result<Json<ApiResponse<BalanceAdjustment>>, Status> {
+    if !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+    let admin_id = match uuid::Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    if let Err(errors) = req.validate() {
+        return Ok(validation_error(&errors));
+    }
+
+    match adjustment_service
+        .adjust_balance(user_id.0, admin_id, req.delta, req.reason.trim().to_string(), req.allow_negative)
+        .await
+    {
+        Ok(adjustment) => Ok(ApiResponse::success("Balance adjusted successfully", adjustment)),
+        Err(e) => match e.downcast_ref::<AdjustmentError>() {
+            Some(rejection) => Ok(ApiResponse::error(400, &rejection.to_string())),
+            None => {
+                eprintln!("Failed to adjust balance: {:?}", e);
+                Ok(ApiResponse::error(500, &format!("Failed to adjust balance: {}", e)))
+            }
+        },
+    }
+}
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-Signature";
+
+/// Signature header sent with provider callbacks; checked against the raw body
+pub struct WebhookSignature(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for WebhookSignature {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match req.headers().get_one(WEBHOOK_SIGNATURE_HEADER) {
+            Some(signature) => Outcome::Success(WebhookSignature(signature.to_string())),
+            None => Outcome::Error((Status::Unauthorized, ())),
+        }
+    }
+}
+
+#[post("/webhooks/payments", data = "<body>")]
+pub async fn payment_webhook_handler(
+    signature: WebhookSignature,
+    body: String,
+    webhook_service: &State<Arc<dyn PaymentWebhookService + Send + Sync>>,
+) -> Result<Json<ApiResponse<()>>, Status> {
+    if !webhook_service.verify_signature(body.as_bytes(), &signature.0) {
+        return Err(Status::Unauthorized);
+    }
+
+    let event: PaymentWebhookEvent = match serde_json::from_str(&body) {
+        Ok(event) => event,
+        Err(_) => return Ok(ApiResponse::error(400, "Invalid webhook payload")),
+    };
+
+    match webhook_service.handle_event(event).await {
+        Ok(WebhookOutcome::Applied) => Ok(ApiResponse::success_no_data("Event applied", 200)),
+        Ok(WebhookOutcome::Duplicate) => Ok(ApiResponse::success_no_data("Event already processed", 200)),
+        Ok(WebhookOutcome::Ignored) => Ok(ApiResponse::success_no_data("Event ignored", 200)),
+        Err(e) => {
+            eprintln!("Failed to handle payment webhook: {:?}", e);
+            Ok(ApiResponse::error(500, "Failed to handle payment webhook"))
+        }
+    }
+}
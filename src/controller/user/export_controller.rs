@@ -0,0 +1,134 @@
+use rocket::http::{ContentType, Status};
+use rocket::response::stream::TextStream;
+use rocket::{Either, Route, State, get, routes, serde::json::Json};
+use serde::Serialize;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::common::sort::SortOrder;
+use crate::controller::auth::auth_controller::UserResponse;
+use crate::controller::transaction::transaction_controller::{ApiResponse, UuidParam};
+use crate::model::transaction::{Transaction, TransactionStatus};
+use crate::repository::user::user_repo::UserRepository;
+use crate::service::transaction::balance_adjustment_service::BalanceAdjustmentService;
+use crate::service::transaction::transaction_service::TransactionService;
+
+pub fn export_routes() -> Vec<Route> {
+    routes![export_user_data_handler]
+}
+
+/// A ticket the user bought, as recorded on its purchase transaction
+#[derive(Debug, Serialize)]
+pub struct ExportedTicket {
+    pub ticket_id: Uuid,
+    pub transaction_id: Uuid,
+    pub status: TransactionStatus,
+}
+
+impl ExportedTicket {
+    fn from_transactions(transactions: &[Transaction]) -> Vec<Self> {
+        transactions
+            .iter()
+            .filter_map(|t| {
+                t.ticket_id.map(|ticket_id| ExportedTicket {
+                    ticket_id,
+                    transaction_id: t.id,
+                    status: t.status,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Everything held about a user, in the usual `ApiResponse` envelope. The
+/// transaction list is the part that grows without bound, so it goes last and
+/// is written one transaction per chunk instead of as a single buffered body.
+#[get("/<user_id>/export")]
+pub async fn export_user_data_handler(
+    token: crate::middleware::auth::JwtToken,
+    user_id: UuidParam,
+    user_repository: &State<Arc<dyn UserRepository>>,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+    adjustment_service: &State<Arc<dyn BalanceAdjustmentService + Send + Sync>>,
+) -> Result<Either<(ContentType, TextStream![String]), Json<ApiResponse<()>>>, Status> {
+    let token_user_id = match Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+
+    if user_id.0 != token_user_id && !token.is_admin() {
+        return Err(Status::Forbidden);
+    }
+
+    let user = match user_repository.find_by_id(user_id.0).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Ok(Either::Right(ApiResponse::error(404, "User not found"))),
+        Err(e) => {
+            eprintln!("Failed to load user for export: {:?}", e);
+            return Ok(Either::Right(ApiResponse::error(500, &format!("Failed to export user data: {}", e))));
+        }
+    };
+
+    let balance = match service.get_user_balance(user.id).await {
+        Ok(balance) => balance,
+        Err(e) => {
+            eprintln!("Failed to get user balance: {:?}", e);
+            return Ok(Either::Right(ApiResponse::error(500, &format!("Failed to export user data: {}", e))));
+        }
+    };
+
+    let adjustments = match adjustment_service.get_user_adjustments(user.id).await {
+        Ok(adjustments) => adjustments,
+        Err(e) => {
+            eprintln!("Failed to get balance adjustments: {:?}", e);
+            return Ok(Either::Right(ApiResponse::error(500, &format!("Failed to export user data: {}", e))));
+        }
+    };
+
+    let transactions = match service
+        .get_user_transactions(user.id, &SortOrder::ascending("created_at"))
+        .await
+    {
+        Ok(transactions) => transactions,
+        Err(e) => {
+            eprintln!("Failed to get user transactions: {:?}", e);
+            return Ok(Either::Right(ApiResponse::error(500, &format!("Failed to export user data: {}", e))));
+        }
+    };
+
+    let profile = UserResponse {
+        id: user.id,
+        name: user.name,
+        email: user.email,
+        role: user.role,
+        created_at: user.created_at.to_rfc3339(),
+        updated_at: user.updated_at.to_rfc3339(),
+        last_login: user.last_login.map(|dt| dt.to_rfc3339()),
+    };
+    let tickets = ExportedTicket::from_transactions(&transactions);
+
+    let head = match (
+        serde_json::to_string(&profile),
+        serde_json::to_string(&balance),
+        serde_json::to_string(&adjustments),
+        serde_json::to_string(&tickets),
+    ) {
+        (Ok(profile), Ok(balance), Ok(adjustments), Ok(tickets)) => format!(
+            r#"{{"success":true,"status_code":200,"message":"User data exported","data":{{"profile":{},"balance":{},"balance_adjustments":{},"tickets":{},"transactions":["#,
+            profile, balance, adjustments, tickets
+        ),
+        _ => return Ok(Either::Right(ApiResponse::error(500, "Failed to export user data"))),
+    };
+
+    let body = TextStream! {
+        yield head;
+        for (i, transaction) in transactions.into_iter().enumerate() {
+            // Transaction is plain data with string keys, so this can't fail
+            let json = serde_json::to_string(&transaction).expect("transaction serializes");
+            yield if i == 0 { json } else { format!(",{}", json) };
+        }
+        yield "]}}".to_string();
+    };
+
+    Ok(Either::Left((ContentType::JSON, body)))
+}
@@ -0,0 +1,106 @@
+use rocket::{Route, State, get, http::Status, routes, serde::json::Json};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::common::sort::SortOrder;
+use crate::controller::transaction::transaction_controller::{
+    ApiResponse, DASHBOARD_RECENT_TRANSACTIONS, ResponseData,
+};
+use crate::model::transaction::{Balance, Transaction};
+use crate::model::user::UserRole;
+use crate::repository::user::user_repo::UserRepository;
+use crate::service::transaction::transaction_service::TransactionService;
+
+pub fn home_routes() -> Vec<Route> {
+    routes![get_home_handler]
+}
+
+/// Landing data for the signed-in user, tagged with `role` so the frontend
+/// can render it without asking for the role separately
+#[derive(Debug, Serialize)]
+#[serde(tag = "role", rename_all = "snake_case")]
+pub enum HomeResponse {
+    Attendee {
+        balance: Balance,
+        recent_transactions: Vec<Transaction>,
+    },
+    /// Events and payouts live outside this service, so organizers only see
+    /// their balance here for now
+    Organizer { balance: Balance },
+    Admin {
+        user_count: usize,
+        users_by_role: BTreeMap<String, usize>,
+    },
+}
+
+impl ResponseData for HomeResponse {}
+
+#[get("/me/home")]
+pub async fn get_home_handler(
+    token: crate::middleware::auth::JwtToken,
+    user_repository: &State<Arc<dyn UserRepository>>,
+    service: &State<Arc<dyn TransactionService + Send + Sync>>,
+) -> Result<Json<ApiResponse<HomeResponse>>, Status> {
+    let user_id = match Uuid::parse_str(&token.user_id) {
+        Ok(id) => id,
+        Err(_) => return Err(Status::Unauthorized),
+    };
+    let role = match token.role.parse::<UserRole>() {
+        Ok(role) => role,
+        Err(_) => return Err(Status::Forbidden),
+    };
+
+    let home = match role {
+        UserRole::Admin => {
+            let users = match user_repository.find_all().await {
+                Ok(users) => users,
+                Err(e) => {
+                    eprintln!("Failed to count users: {:?}", e);
+                    return Ok(ApiResponse::error(500, &format!("Failed to load home: {}", e)));
+                }
+            };
+            let mut users_by_role = BTreeMap::new();
+            for user in &users {
+                *users_by_role.entry(user.role.to_string().to_lowercase()).or_insert(0) += 1;
+            }
+            HomeResponse::Admin {
+                user_count: users.len(),
+                users_by_role,
+            }
+        }
+        UserRole::Organizer | UserRole::Attendee => {
+            let balance = match service.get_user_balance(user_id).await {
+                Ok(balance) => balance,
+                Err(e) => {
+                    eprintln!("Failed to get user balance: {:?}", e);
+                    return Ok(ApiResponse::error(500, &format!("Failed to load home: {}", e)));
+                }
+            };
+            if role == UserRole::Organizer {
+                HomeResponse::Organizer { balance }
+            } else {
+                let recent_transactions = match service
+                    .get_user_transactions(user_id, &SortOrder::descending("created_at"))
+                    .await
+                {
+                    Ok(transactions) => transactions
+                        .into_iter()
+                        .take(DASHBOARD_RECENT_TRANSACTIONS)
+                        .collect(),
+                    Err(e) => {
+                        eprintln!("Failed to get user transactions: {:?}", e);
+                        return Ok(ApiResponse::error(500, &format!("Failed to load home: {}", e)));
+                    }
+                };
+                HomeResponse::Attendee {
+                    balance,
+                    recent_transactions,
+                }
+            }
+        }
+    };
+
+    Ok(ApiResponse::success("Home data found", home))
+}
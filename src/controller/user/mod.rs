@@ -0,0 +1,5 @@
+pub mod export_controller;
+pub mod home_controller;
+
+#[cfg(test)]
+pub mod tests;
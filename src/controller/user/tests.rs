@@ -0,0 +1,203 @@
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::controller::user::export_controller::export_routes;
+use crate::controller::user::home_controller::home_routes;
+use crate::model::user::{User, UserRole};
+use crate::repository::transaction::balance_adjustment_repo::InMemoryBalanceAdjustmentRepository;
+use crate::repository::user::user_repo::{DbUserRepository, InMemoryUserPersistence, UserRepository};
+use crate::service::auth::auth_service::AuthService;
+use crate::service::transaction::balance_adjustment_service::{
+    BalanceAdjustmentService, DefaultBalanceAdjustmentService,
+};
+use crate::service::transaction::tests::common::{
+    create_transaction_service_with_balance_repository, MockBalanceRepository,
+};
+use crate::service::transaction::TransactionService;
+use rocket::http::{Header, Status};
+use rocket::local::asynchronous::Client;
+
+struct UserRoutesFixture {
+    client: Client,
+    auth_service: Arc<AuthService>,
+    service: Arc<dyn TransactionService + Send + Sync>,
+    adjustment_service: Arc<dyn BalanceAdjustmentService + Send + Sync>,
+    user_repository: Arc<dyn UserRepository>,
+}
+
+impl UserRoutesFixture {
+    async fn new() -> Self {
+        let auth_service = Arc::new(AuthService::new(
+            "test_secret".to_string(),
+            "test_refresh_secret".to_string(),
+            "test_pepper".to_string(),
+        ));
+        let balances = Arc::new(MockBalanceRepository::new());
+        let (service, balance_service) = create_transaction_service_with_balance_repository(balances.clone());
+        let service: Arc<dyn TransactionService + Send + Sync> = Arc::new(service);
+        let adjustment_service: Arc<dyn BalanceAdjustmentService + Send + Sync> =
+            Arc::new(DefaultBalanceAdjustmentService::new(
+                balance_service,
+                Arc::new(InMemoryBalanceAdjustmentRepository::new(balances)),
+            ));
+        let user_repository: Arc<dyn UserRepository> =
+            Arc::new(DbUserRepository::new(InMemoryUserPersistence::new()));
+
+        let rocket = rocket::build()
+            .manage(auth_service.clone())
+            .manage(service.clone())
+            .manage(adjustment_service.clone())
+            .manage(user_repository.clone())
+            .mount("/api/users", export_routes())
+            .mount("/api", home_routes());
+        let client = Client::tracked(rocket).await.expect("valid rocket instance");
+
+        Self { client, auth_service, service, adjustment_service, user_repository }
+    }
+
+    async fn user(&self, email: &str, role: UserRole) -> (User, String) {
+        let user = User::new("Export User".to_string(), email.to_string(), "hash".to_string(), role);
+        self.user_repository.create(&user).await.unwrap();
+        let token = self.auth_service.generate_token(&user).await.unwrap().access_token;
+        (user, format!("Bearer {}", token))
+    }
+
+    async fn export(&self, user_id: Uuid, bearer: &str) -> (Status, Option<serde_json::Value>) {
+        let response = self
+            .client
+            .get(format!("/api/users/{}/export", user_id))
+            .header(Header::new("Authorization", bearer.to_string()))
+            .dispatch()
+            .await;
+        let status = response.status();
+        let body = response.into_string().await.and_then(|b| serde_json::from_str(&b).ok());
+        (status, body)
+    }
+}
+
+#[tokio::test]
+async fn test_export_contains_own_data_only() {
+    let fixture = UserRoutesFixture::new().await;
+    let (user, bearer) = fixture.user("exporter@example.com", UserRole::Attendee).await;
+    let (other, _) = fixture.user("bystander@example.com", UserRole::Attendee).await;
+    let admin_id = Uuid::new_v4();
+
+    let ticket_id = Uuid::new_v4();
+    let purchase = fixture
+        .service
+        .create_transaction(user.id, Some(ticket_id), 5000, "Concert ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    fixture
+        .adjustment_service
+        .adjust_balance(user.id, admin_id, 700, "Goodwill credit".to_string(), false)
+        .await
+        .unwrap();
+
+    let other_ticket_id = Uuid::new_v4();
+    let other_purchase = fixture
+        .service
+        .create_transaction(other.id, Some(other_ticket_id), 3000, "Other ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+    fixture
+        .adjustment_service
+        .adjust_balance(other.id, admin_id, 100, "Other credit".to_string(), false)
+        .await
+        .unwrap();
+
+    let (status, body) = fixture.export(user.id, &bearer).await;
+    assert_eq!(status, Status::Ok);
+    let body = body.unwrap();
+    assert!(body["success"].as_bool().unwrap());
+
+    let data = &body["data"];
+    assert_eq!(data["profile"]["email"], "exporter@example.com");
+    assert!(data["profile"].get("password").is_none());
+    assert_eq!(data["balance"]["amount"].as_i64().unwrap(), 700);
+
+    let transaction_ids: Vec<&str> = data["transactions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["id"].as_str().unwrap())
+        .collect();
+    assert_eq!(transaction_ids, vec![purchase.id.to_string()]);
+    assert!(!transaction_ids.contains(&other_purchase.id.to_string().as_str()));
+
+    let tickets = data["tickets"].as_array().unwrap();
+    assert_eq!(tickets.len(), 1);
+    assert_eq!(tickets[0]["ticket_id"], ticket_id.to_string());
+    assert_eq!(tickets[0]["transaction_id"], purchase.id.to_string());
+
+    let adjustments = data["balance_adjustments"].as_array().unwrap();
+    assert_eq!(adjustments.len(), 1);
+    assert_eq!(adjustments[0]["user_id"], user.id.to_string());
+}
+
+#[tokio::test]
+async fn test_export_of_another_user_is_forbidden() {
+    let fixture = UserRoutesFixture::new().await;
+    let (_, bearer) = fixture.user("curious@example.com", UserRole::Attendee).await;
+    let (other, _) = fixture.user("private@example.com", UserRole::Attendee).await;
+
+    let (status, _) = fixture.export(other.id, &bearer).await;
+    assert_eq!(status, Status::Forbidden);
+}
+
+#[tokio::test]
+async fn test_admin_can_export_any_user() {
+    let fixture = UserRoutesFixture::new().await;
+    let (_, bearer) = fixture.user("admin@example.com", UserRole::Admin).await;
+    let (other, _) = fixture.user("member@example.com", UserRole::Attendee).await;
+
+    let (status, body) = fixture.export(other.id, &bearer).await;
+    assert_eq!(status, Status::Ok);
+    let body = body.unwrap();
+    assert_eq!(body["data"]["profile"]["email"], "member@example.com");
+    assert_eq!(body["data"]["transactions"].as_array().unwrap().len(), 0);
+}
+
+impl UserRoutesFixture {
+    async fn home(&self, bearer: &str) -> serde_json::Value {
+        let response = self
+            .client
+            .get("/api/me/home")
+            .header(Header::new("Authorization", bearer.to_string()))
+            .dispatch()
+            .await;
+        assert_eq!(response.status(), Status::Ok);
+        let body: serde_json::Value = response.into_json().await.unwrap();
+        assert!(body["success"].as_bool().unwrap(), "{}", body);
+        body["data"].clone()
+    }
+}
+
+#[tokio::test]
+async fn test_home_shape_depends_on_role() {
+    let fixture = UserRoutesFixture::new().await;
+    let (attendee, attendee_bearer) = fixture.user("attendee@example.com", UserRole::Attendee).await;
+    let (_, organizer_bearer) = fixture.user("organizer@example.com", UserRole::Organizer).await;
+    let (_, admin_bearer) = fixture.user("root@example.com", UserRole::Admin).await;
+    fixture
+        .service
+        .create_transaction(attendee.id, None, 5000, "Concert ticket".to_string(), "credit_card".to_string())
+        .await
+        .unwrap();
+
+    let home = fixture.home(&attendee_bearer).await;
+    assert_eq!(home["role"], "attendee");
+    assert_eq!(home["balance"]["user_id"], attendee.id.to_string());
+    assert_eq!(home["recent_transactions"].as_array().unwrap().len(), 1);
+
+    let home = fixture.home(&organizer_bearer).await;
+    assert_eq!(home["role"], "organizer");
+    assert!(home["balance"].is_object());
+    assert!(home.get("recent_transactions").is_none());
+
+    let home = fixture.home(&admin_bearer).await;
+    assert_eq!(home["role"], "admin");
+    assert_eq!(home["user_count"].as_u64().unwrap(), 3);
+    assert_eq!(home["users_by_role"]["attendee"].as_u64().unwrap(), 1);
+    assert!(home.get("balance").is_none());
+}
@@ -0,0 +1,51 @@
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::ContentType;
+use rocket::{Request, Response};
+use std::io::Cursor;
+
+use crate::common::amount::stringify_amounts;
+
+/// Rewrites money fields in JSON responses as strings for clients that opt in
+/// with `?amounts=string` or an `Accept: application/json; amounts=string` profile
+pub struct AmountFormatFairing;
+
+pub fn wants_string_amounts(request: &Request<'_>) -> bool {
+    let query_flag = request
+        .query_value::<&str>("amounts")
+        .and_then(Result::ok)
+        .is_some_and(|mode| mode.eq_ignore_ascii_case("string"));
+    let accept_profile = request.accept().is_some_and(|accept| {
+        accept
+            .iter()
+            .any(|media| media.params().any(|(key, value)| key == "amounts" && value == "string"))
+    });
+    query_flag || accept_profile
+}
+
+#[rocket::async_trait]
+impl Fairing for AmountFormatFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "String Amount Format",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if response.content_type() != Some(ContentType::JSON) || !wants_string_amounts(request) {
+            return;
+        }
+
+        let Ok(body) = response.body_mut().to_string().await else {
+            return;
+        };
+        let rewritten = match serde_json::from_str::<serde_json::Value>(&body) {
+            Ok(mut value) => {
+                stringify_amounts(&mut value);
+                value.to_string()
+            }
+            Err(_) => body,
+        };
+        response.set_sized_body(rewritten.len(), Cursor::new(rewritten));
+    }
+}
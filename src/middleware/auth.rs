@@ -1,21 +1,29 @@
 use rocket::{request::{self, FromRequest, Request}, outcome::Outcome, State};
 use rocket::http::Status;
-use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
+use jsonwebtoken::{decode, DecodingKey};
 use serde::{Deserialize, Serialize};
 use crate::service::auth::auth_service::AuthService;
 use std::sync::Arc;
+use uuid::Uuid;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: String,
     pub role: String,
+    #[serde(default)]
+    pub iat: i64,
     pub exp: usize,
+    /// Owner's `token_epoch` when the token was issued
+    #[serde(default)]
+    pub epoch: i32,
 }
 
 #[derive(Debug)]
 pub struct JwtToken {
     pub user_id: String,
     pub role: String,
+    /// `exp` claim of the access token, as a Unix timestamp
+    pub expires_at: i64,
 }
 
 impl JwtToken {
@@ -50,7 +58,7 @@ impl<'r> FromRequest<'r> for JwtToken {
         let token_data = match decode::<Claims>(
             &token,
             &DecodingKey::from_secret(secret.as_bytes()),
-            &Validation::new(Algorithm::HS256),
+            &auth_service.jwt_validation(),
         ) {
             Ok(c) => c,
             Err(e) => {
@@ -58,9 +66,26 @@ impl<'r> FromRequest<'r> for JwtToken {
             },
         };
         
+        match auth_service.is_issued_after_epoch(token_data.claims.iat).await {
+            Ok(true) => {}
+            Ok(false) => return Outcome::Error((Status::Unauthorized, ())),
+            Err(_) => return Outcome::Error((Status::InternalServerError, ())),
+        }
+
+        let current_epoch = match Uuid::parse_str(&token_data.claims.sub) {
+            Ok(user_id) => auth_service.is_current_token_epoch(user_id, token_data.claims.epoch).await,
+            Err(_) => return Outcome::Error((Status::Unauthorized, ())),
+        };
+        match current_epoch {
+            Ok(true) => {}
+            Ok(false) => return Outcome::Error((Status::Unauthorized, ())),
+            Err(_) => return Outcome::Error((Status::InternalServerError, ())),
+        }
+        
         let jwt_token = JwtToken {
             user_id: token_data.claims.sub,
             role: token_data.claims.role,
+            expires_at: token_data.claims.exp as i64,
         };
         
         Outcome::Success(jwt_token)
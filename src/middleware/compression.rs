@@ -0,0 +1,117 @@
+use flate2::Compression;
+use flate2::write::{GzEncoder, ZlibEncoder};
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{ContentType, Header};
+use rocket::{Request, Response};
+use std::io::{Cursor, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Gzip,
+    Deflate,
+}
+
+impl Encoding {
+    fn name(self) -> &'static str {
+        match self {
+            Encoding::Gzip => "gzip",
+            Encoding::Deflate => "deflate",
+        }
+    }
+
+    /// Picks gzip over deflate when the client takes both; a `q=0` entry
+    /// counts as a refusal
+    fn negotiate(accept_encoding: &str) -> Option<Self> {
+        let accepted: Vec<&str> = accept_encoding
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';').map(str::trim);
+                let name = parts.next()?;
+                let refused = parts.any(|param| {
+                    param
+                        .strip_prefix("q=")
+                        .and_then(|q| q.parse::<f32>().ok())
+                        .is_some_and(|q| q == 0.0)
+                });
+                (!refused).then_some(name)
+            })
+            .collect();
+
+        let takes = |name: &str| accepted.iter().any(|a| a.eq_ignore_ascii_case(name));
+        if takes("gzip") {
+            Some(Encoding::Gzip)
+        } else if takes("deflate") {
+            Some(Encoding::Deflate)
+        } else {
+            None
+        }
+    }
+
+    fn encode(self, body: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            Encoding::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+            Encoding::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(body)?;
+                encoder.finish()
+            }
+        }
+    }
+}
+
+/// Compresses JSON responses of at least `min_bytes` for clients that send a
+/// matching `Accept-Encoding`. Attach after fairings that rewrite the body.
+pub struct CompressionFairing {
+    min_bytes: usize,
+}
+
+impl CompressionFairing {
+    pub fn new(min_bytes: usize) -> Self {
+        Self { min_bytes }
+    }
+}
+
+#[rocket::async_trait]
+impl Fairing for CompressionFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "JSON Response Compression",
+            kind: Kind::Response,
+        }
+    }
+
+    async fn on_response<'r>(&self, request: &'r Request<'_>, response: &mut Response<'r>) {
+        if response.content_type() != Some(ContentType::JSON) || response.headers().contains("Content-Encoding") {
+            return;
+        }
+        let Some(encoding) = request
+            .headers()
+            .get_one("Accept-Encoding")
+            .and_then(Encoding::negotiate)
+        else {
+            return;
+        };
+
+        // The answer depends on Accept-Encoding even when it ends up uncompressed
+        response.adjoin_header(Header::new("Vary", "Accept-Encoding"));
+        let Ok(body) = response.body_mut().to_bytes().await else {
+            return;
+        };
+        if body.len() < self.min_bytes {
+            response.set_sized_body(body.len(), Cursor::new(body));
+            return;
+        }
+
+        match encoding.encode(&body) {
+            Ok(compressed) => {
+                response.set_header(Header::new("Content-Encoding", encoding.name()));
+                response.set_sized_body(compressed.len(), Cursor::new(compressed));
+            }
+            Err(_) => response.set_sized_body(body.len(), Cursor::new(body)),
+        }
+    }
+}
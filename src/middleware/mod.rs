@@ -1 +1,5 @@
-pub mod auth;
\ No newline at end of file
+pub mod amount_format;
+pub mod auth;
+pub mod compression;
+pub mod ownership;
+pub mod rate_limit;
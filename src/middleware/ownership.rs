@@ -0,0 +1,33 @@
+use rocket::request::{self, FromRequest, Request};
+use rocket::outcome::Outcome;
+
+/// How a handler answers when a user asks for a resource they don't own.
+///
+/// `NotFound` makes someone else's resource indistinguishable from a missing
+/// one, so valid ids can't be enumerated by probing for 403s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OwnershipPolicy {
+    Forbidden,
+    #[default]
+    NotFound,
+}
+
+impl OwnershipPolicy {
+    pub fn from_enumeration_safe(enabled: bool) -> Self {
+        if enabled {
+            OwnershipPolicy::NotFound
+        } else {
+            OwnershipPolicy::Forbidden
+        }
+    }
+}
+
+/// Reads the managed policy, falling back to the enumeration-safe default
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for OwnershipPolicy {
+    type Error = ();
+
+    async fn from_request(req: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+        Outcome::Success(req.rocket().state::<OwnershipPolicy>().copied().unwrap_or_default())
+    }
+}
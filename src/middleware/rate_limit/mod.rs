@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Sliding-window limiter keyed by an arbitrary string (email, IP, ...)
+pub struct RateLimiter {
+    max_requests: usize,
+    window: Duration,
+    hits: Mutex<HashMap<String, Vec<Instant>>>,
+    last_sweep: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: usize, window: Duration) -> Self {
+        Self {
+            max_requests,
+            window,
+            hits: Mutex::new(HashMap::new()),
+            last_sweep: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Records a hit for `key` and returns false once the key is over its limit
+    pub fn try_acquire(&self, key: &str) -> bool {
+        let now = Instant::now();
+        let mut hits = self.hits.lock().unwrap();
+
+        // Forget keys with no hit left in the window, at most once per window,
+        // so one-off keys (every probing IP or email) don't pile up forever
+        let mut last_sweep = self.last_sweep.lock().unwrap();
+        if now.duration_since(*last_sweep) >= self.window {
+            hits.retain(|_, key_hits| {
+                key_hits.last().is_some_and(|hit| now.duration_since(*hit) < self.window)
+            });
+            *last_sweep = now;
+        }
+        drop(last_sweep);
+
+        let entry = hits.entry(key.to_string()).or_default();
+        entry.retain(|hit| now.duration_since(*hit) < self.window);
+
+        if entry.len() >= self.max_requests {
+            return false;
+        }
+        entry.push(now);
+        true
+    }
+}
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests {
+    use super::super::RateLimiter;
+    use std::time::Duration;
+
+    #[test]
+    fn test_idle_keys_are_evicted() {
+        let limiter = RateLimiter::new(1, Duration::from_millis(20));
+        for i in 0..5 {
+            assert!(limiter.try_acquire(&format!("ip:{}", i)));
+        }
+        assert!(!limiter.try_acquire("ip:0"));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(limiter.try_acquire("ip:new"));
+
+        let hits = limiter.hits.lock().unwrap();
+        assert_eq!(hits.len(), 1);
+        assert!(hits.contains_key("ip:new"));
+    }
+}
@@ -1,6 +1,8 @@
+mod password_reset;
 mod token;
 
+pub use password_reset::{PasswordResetToken, RESET_TOKEN_LIFETIME_MINUTES};
 pub use token::RefreshToken;
 
 #[cfg(test)]
-pub mod tests;
\ No newline at end of file
+pub mod tests;
@@ -0,0 +1,35 @@
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+use serde::{Serialize, Deserialize};
+
+/// How long a forgot-password token can be redeemed for
+pub const RESET_TOKEN_LIFETIME_MINUTES: i64 = 30;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PasswordResetToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+    /// Set once the token has reset a password; it can't be used again
+    pub used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl PasswordResetToken {
+    pub fn new(user_id: Uuid, token: String, lifetime: Duration) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            token,
+            expires_at: now + lifetime,
+            used_at: None,
+            created_at: now,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.used_at.is_none() && self.expires_at > Utc::now()
+    }
+}
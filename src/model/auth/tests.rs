@@ -31,6 +31,7 @@ mod token_tests {
             expires_at: Utc::now() + chrono::Duration::days(1),
             is_revoked: false,
             created_at: Utc::now(),
+            rotated_at: None,
         };
         assert!(valid_token.is_valid());
         
@@ -41,6 +42,7 @@ mod token_tests {
             expires_at: Utc::now() - chrono::Duration::hours(1),
             is_revoked: false,
             created_at: Utc::now() - chrono::Duration::days(7),
+            rotated_at: None,
         };
         assert!(!expired_token.is_valid());
         
@@ -51,6 +53,7 @@ mod token_tests {
             expires_at: Utc::now() + chrono::Duration::days(1),
             is_revoked: true,
             created_at: Utc::now(),
+            rotated_at: None,
         };
         assert!(!revoked_token.is_valid());
         
@@ -61,6 +64,7 @@ mod token_tests {
             expires_at: Utc::now() - chrono::Duration::hours(1),
             is_revoked: true,
             created_at: Utc::now() - chrono::Duration::days(7),
+            rotated_at: None,
         };
         assert!(!expired_revoked_token.is_valid());
     }
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 
@@ -10,6 +10,9 @@ pub struct RefreshToken {
     pub expires_at: DateTime<Utc>,
     pub is_revoked: bool,
     pub created_at: DateTime<Utc>,
+    /// When this token was exchanged for its successor; `None` for tokens
+    /// that are still live or were revoked some other way
+    pub rotated_at: Option<DateTime<Utc>>,
 }
 
 impl RefreshToken {
@@ -19,13 +22,31 @@ impl RefreshToken {
             id: Uuid::new_v4(),
             user_id,
             token,
-            expires_at: now + chrono::Duration::days(expires_in_days),
+            expires_at: now + Duration::days(expires_in_days),
             is_revoked: false,
             created_at: now,
+            rotated_at: None,
         }
     }
 
     pub fn is_valid(&self) -> bool {
         !self.is_revoked && self.expires_at > Utc::now()
     }
+
+    /// Expiry for a token used at `now` under sliding renewal: `now + lifetime`
+    /// once it is within `renew_within` of expiring, never past
+    /// `created_at + max_lifetime`. `None` when it stays as it is.
+    pub fn renewed_expiry(
+        &self,
+        now: DateTime<Utc>,
+        lifetime: Duration,
+        renew_within: Duration,
+        max_lifetime: Duration,
+    ) -> Option<DateTime<Utc>> {
+        if self.expires_at - now > renew_within {
+            return None;
+        }
+        let renewed = (now + lifetime).min(self.created_at + max_lifetime);
+        (renewed > self.expires_at).then_some(renewed)
+    }
 }
\ No newline at end of file
@@ -8,6 +8,10 @@ pub struct Balance {
     pub user_id: Uuid,
     pub amount: i64,
     pub updated_at: DateTime<Utc>,
+    /// Set by an adjustment allowed to go below zero; the database only
+    /// accepts a negative amount while this is set
+    #[serde(skip)]
+    pub overdraft_allowed: bool,
 }
 
 impl Balance {
@@ -17,6 +21,7 @@ impl Balance {
             user_id,
             amount: 0,
             updated_at: Utc::now(),
+            overdraft_allowed: false,
         }
     }
 
@@ -26,6 +31,9 @@ impl Balance {
         }
         
         self.amount += amount;
+        if self.amount >= 0 {
+            self.overdraft_allowed = false;
+        }
         self.updated_at = Utc::now();
         Ok(self.amount)
     }
@@ -43,4 +51,24 @@ impl Balance {
         self.updated_at = Utc::now();
         Ok(self.amount)
     }
+
+    /// Applies a signed correction; only an explicit override may take the balance below zero
+    pub fn adjust(&mut self, delta: i64, allow_negative: bool) -> Result<i64, String> {
+        if delta == 0 {
+            return Err("Adjustment must not be zero".to_string());
+        }
+
+        let new_amount = self
+            .amount
+            .checked_add(delta)
+            .ok_or_else(|| "Adjustment overflows the balance".to_string())?;
+        if new_amount < 0 && !allow_negative {
+            return Err("Adjustment would make the balance negative".to_string());
+        }
+
+        self.amount = new_amount;
+        self.overdraft_allowed = new_amount < 0;
+        self.updated_at = Utc::now();
+        Ok(self.amount)
+    }
 }
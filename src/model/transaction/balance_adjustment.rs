@@ -0,0 +1,35 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Audit record of a manual balance correction made by an admin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceAdjustment {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub admin_id: Uuid,
+    pub delta: i64,
+    pub reason: String,
+    pub resulting_balance: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BalanceAdjustment {
+    pub fn new(
+        user_id: Uuid,
+        admin_id: Uuid,
+        delta: i64,
+        reason: String,
+        resulting_balance: i64,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            user_id,
+            admin_id,
+            delta,
+            reason,
+            resulting_balance,
+            created_at: Utc::now(),
+        }
+    }
+}
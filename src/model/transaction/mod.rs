@@ -1,11 +1,22 @@
 mod transaction;
 mod balance;
+mod balance_adjustment;
 
 #[cfg(test)]
 pub mod tests;
 
 pub use transaction::{
+    BALANCE_PAYMENT_METHOD,
+    DEFAULT_PAYMENT_METHODS,
+    normalize_payment_method,
+    RefundPolicy,
+    RefundReason,
+    RefundReasonCategory,
+    TOPUP_DESCRIPTION,
     Transaction,
+    TransactionCategory,
     TransactionStatus,
+    TransactionStatusSnapshot,
 };
 pub use balance::Balance;
+pub use balance_adjustment::BalanceAdjustment;
@@ -1,5 +1,5 @@
 use uuid::Uuid;
-use crate::model::transaction::{Transaction, Balance, TransactionStatus};
+use crate::model::transaction::{Transaction, Balance, RefundReason, RefundReasonCategory, TransactionStatus};
 
 #[cfg(test)]
 pub mod model_tests {
@@ -57,13 +57,20 @@ pub mod model_tests {
             "balance".to_string()
         );
         
+        let reason = RefundReason {
+            category: RefundReasonCategory::CustomerRequest,
+            note: None,
+        };
+
         // Check if transaction is successful or not
-        assert!(transaction.refund().is_err());
+        assert!(transaction.refund(reason.clone()).is_err());
+        assert!(transaction.refund_reason.is_none());
         
         transaction.process(true, None);
         
-        assert!(transaction.refund().is_ok());
+        assert!(transaction.refund(reason.clone()).is_ok());
         assert_eq!(transaction.status, TransactionStatus::Refunded);
+        assert_eq!(transaction.refund_reason, Some(reason));
     }
     
     #[test]
@@ -2,6 +2,22 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::fmt;
+use std::str::FromStr;
+
+/// Payment method recorded on anything settled against the user's stored balance
+pub const BALANCE_PAYMENT_METHOD: &str = "balance";
+
+/// Payment methods accepted when no `PAYMENT_METHODS` list is configured
+pub const DEFAULT_PAYMENT_METHODS: [&str; 4] = ["credit_card", "bank_transfer", "e_wallet", BALANCE_PAYMENT_METHOD];
+
+/// Form a payment method is validated and stored in
+pub fn normalize_payment_method(payment_method: &str) -> String {
+    payment_method.trim().to_lowercase()
+}
+
+/// Description of the record a balance top-up leaves in the history; clients
+/// should label rows by `category` rather than by this text
+pub const TOPUP_DESCRIPTION: &str = "Balance top-up";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransactionStatus {
@@ -34,18 +50,197 @@ impl fmt::Display for TransactionStatus {
     }
 }
 
+impl FromStr for TransactionStatus {
+    type Err = ();
+
+    /// Strict counterpart of `from_string`: unknown values are an error
+    /// rather than `Pending`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pending" => Ok(TransactionStatus::Pending),
+            "success" => Ok(TransactionStatus::Success),
+            "failed" => Ok(TransactionStatus::Failed),
+            "refunded" => Ok(TransactionStatus::Refunded),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Reporting category, set by the flow that created the transaction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionCategory {
+    #[default]
+    Ticket,
+    Topup,
+    Withdrawal,
+    Fee,
+    Refund,
+    Comp,
+}
+
+impl TransactionCategory {
+    /// Only purchases can be refunded; balance movements are reversed by
+    /// another top-up or withdrawal instead
+    pub fn is_refundable(&self) -> bool {
+        matches!(self, TransactionCategory::Ticket | TransactionCategory::Comp)
+    }
+}
+
+impl fmt::Display for TransactionCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactionCategory::Ticket => write!(f, "Ticket"),
+            TransactionCategory::Topup => write!(f, "Topup"),
+            TransactionCategory::Withdrawal => write!(f, "Withdrawal"),
+            TransactionCategory::Fee => write!(f, "Fee"),
+            TransactionCategory::Refund => write!(f, "Refund"),
+            TransactionCategory::Comp => write!(f, "Comp"),
+        }
+    }
+}
+
+impl FromStr for TransactionCategory {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ticket" => Ok(TransactionCategory::Ticket),
+            "topup" => Ok(TransactionCategory::Topup),
+            "withdrawal" => Ok(TransactionCategory::Withdrawal),
+            "fee" => Ok(TransactionCategory::Fee),
+            "refund" => Ok(TransactionCategory::Refund),
+            "comp" => Ok(TransactionCategory::Comp),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Why a purchase was refunded, for refund analytics
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundReasonCategory {
+    CustomerRequest,
+    EventCancelled,
+    EventChanged,
+    Duplicate,
+    Fraud,
+    Other,
+}
+
+impl fmt::Display for RefundReasonCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefundReasonCategory::CustomerRequest => write!(f, "customer_request"),
+            RefundReasonCategory::EventCancelled => write!(f, "event_cancelled"),
+            RefundReasonCategory::EventChanged => write!(f, "event_changed"),
+            RefundReasonCategory::Duplicate => write!(f, "duplicate"),
+            RefundReasonCategory::Fraud => write!(f, "fraud"),
+            RefundReasonCategory::Other => write!(f, "other"),
+        }
+    }
+}
+
+impl FromStr for RefundReasonCategory {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "customer_request" => Ok(RefundReasonCategory::CustomerRequest),
+            "event_cancelled" => Ok(RefundReasonCategory::EventCancelled),
+            "event_changed" => Ok(RefundReasonCategory::EventChanged),
+            "duplicate" => Ok(RefundReasonCategory::Duplicate),
+            "fraud" => Ok(RefundReasonCategory::Fraud),
+            "other" => Ok(RefundReasonCategory::Other),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Where a refund for a gateway payment goes; balance-funded purchases are
+/// always credited back to the balance
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefundPolicy {
+    /// Back through the payment gateway that took the money
+    #[default]
+    Original,
+    /// As store credit on the payer's balance
+    Balance,
+}
+
+impl fmt::Display for RefundPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RefundPolicy::Original => write!(f, "original"),
+            RefundPolicy::Balance => write!(f, "balance"),
+        }
+    }
+}
+
+impl FromStr for RefundPolicy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "original" => Ok(RefundPolicy::Original),
+            "balance" => Ok(RefundPolicy::Balance),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RefundReason {
+    pub category: RefundReasonCategory,
+    /// Free-text detail from whoever requested the refund
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Transaction {
     pub id: Uuid,
     pub user_id: Uuid,
     pub ticket_id: Option<Uuid>,
     pub amount: i64,
+    #[serde(default)]
+    pub fee_amount: i64,
     pub status: TransactionStatus,
+    #[serde(default)]
+    pub category: TransactionCategory,
     pub description: String,
     pub payment_method: String,
     pub external_reference: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// End of the payment window for payment-link transactions
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Set once the transaction is refunded
+    #[serde(default)]
+    pub refund_reason: Option<RefundReason>,
+}
+
+/// Just enough of a transaction to poll its progress
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionStatusSnapshot {
+    pub id: Uuid,
+    /// Kept for the ownership check, not part of the response
+    #[serde(skip)]
+    pub user_id: Uuid,
+    pub status: TransactionStatus,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&Transaction> for TransactionStatusSnapshot {
+    fn from(transaction: &Transaction) -> Self {
+        Self {
+            id: transaction.id,
+            user_id: transaction.user_id,
+            status: transaction.status,
+            updated_at: transaction.updated_at,
+        }
+    }
 }
 
 impl Transaction {
@@ -62,12 +257,16 @@ impl Transaction {
             user_id,
             ticket_id,
             amount,
+            fee_amount: 0,
             status: TransactionStatus::Pending,
+            category: TransactionCategory::Ticket,
             description,
             payment_method,
             external_reference: None,
             created_at: now,
             updated_at: now,
+            expires_at: None,
+            refund_reason: None,
         }
     }
 
@@ -77,16 +276,34 @@ impl Transaction {
         self.updated_at = Utc::now();
     }
 
-    pub fn refund(&mut self) -> Result<(), String> {
+    pub fn refund(&mut self, reason: RefundReason) -> Result<(), String> {
+        if !self.category.is_refundable() {
+            return Err(format!("{} transactions cannot be refunded", self.category));
+        }
         if self.status != TransactionStatus::Success {
             return Err("Only successful transactions can be refunded".to_string());
         }
         
         self.status = TransactionStatus::Refunded;
+        self.refund_reason = Some(reason);
         self.updated_at = Utc::now();
         Ok(())
     }
 
+    /// What the payer is charged: the amount plus any processing fee
+    pub fn total_charged(&self) -> i64 {
+        self.amount + self.fee_amount
+    }
+
+    /// Paid out of the user's stored balance rather than through the payment gateway
+    pub fn is_balance_funded(&self) -> bool {
+        self.payment_method.trim().eq_ignore_ascii_case(BALANCE_PAYMENT_METHOD)
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+
     pub fn is_finalized(&self) -> bool {
         matches!(self.status, TransactionStatus::Success | TransactionStatus::Failed | TransactionStatus::Refunded)
     }
@@ -1,8 +1,10 @@
+mod permission;
 mod user;
 
 #[cfg(test)]
 pub mod tests;
 
+pub use permission::Permission;
 pub use user::{
     User,
     UserRole,
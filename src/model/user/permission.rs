@@ -0,0 +1,58 @@
+use serde::Serialize;
+
+use super::UserRole;
+
+/// Capabilities the frontend uses to decide which actions to show. Route
+/// guards remain the source of truth; this only mirrors them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Permission {
+    #[serde(rename = "can_purchase_tickets")]
+    PurchaseTickets,
+    #[serde(rename = "can_manage_balance")]
+    ManageBalance,
+    #[serde(rename = "can_create_event")]
+    CreateEvent,
+    #[serde(rename = "can_validate_tickets")]
+    ValidateTickets,
+    #[serde(rename = "can_manage_ads")]
+    ManageAds,
+    #[serde(rename = "can_adjust_balances")]
+    AdjustBalances,
+    #[serde(rename = "can_view_all_transactions")]
+    ViewAllTransactions,
+    #[serde(rename = "can_revoke_tokens")]
+    RevokeTokens,
+}
+
+const ATTENDEE_PERMISSIONS: &[Permission] = &[
+    Permission::PurchaseTickets,
+    Permission::ManageBalance,
+];
+
+const ORGANIZER_PERMISSIONS: &[Permission] = &[
+    Permission::PurchaseTickets,
+    Permission::ManageBalance,
+    Permission::CreateEvent,
+    Permission::ValidateTickets,
+];
+
+const ADMIN_PERMISSIONS: &[Permission] = &[
+    Permission::PurchaseTickets,
+    Permission::ManageBalance,
+    Permission::CreateEvent,
+    Permission::ValidateTickets,
+    Permission::ManageAds,
+    Permission::AdjustBalances,
+    Permission::ViewAllTransactions,
+    Permission::RevokeTokens,
+];
+
+impl UserRole {
+    pub fn permissions(&self) -> &'static [Permission] {
+        match self {
+            UserRole::Admin => ADMIN_PERMISSIONS,
+            UserRole::Organizer => ORGANIZER_PERMISSIONS,
+            UserRole::Attendee => ATTENDEE_PERMISSIONS,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::model::user::{User, UserRole};
+use crate::model::user::{Permission, User, UserRole};
 
 #[cfg(test)]
 pub mod model_tests {
@@ -59,6 +59,7 @@ pub mod model_tests {
         user.update_password(new_password.clone());
 
         assert_eq!(user.password, new_password);
+        assert_eq!(user.token_epoch, 1);
         assert!(user.updated_at > user.created_at);
     }
 
@@ -114,4 +115,18 @@ pub mod model_tests {
         assert_eq!(user_info.updated_at, user.updated_at);
         assert_eq!(user_info.last_login, user.last_login);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_role_permissions_widen_with_role() {
+        let attendee = UserRole::Attendee.permissions();
+        let organizer = UserRole::Organizer.permissions();
+        let admin = UserRole::Admin.permissions();
+
+        assert!(attendee.contains(&Permission::PurchaseTickets));
+        assert!(!attendee.contains(&Permission::CreateEvent));
+        assert!(organizer.contains(&Permission::ValidateTickets));
+        assert!(!organizer.contains(&Permission::ManageAds));
+        assert!(attendee.iter().all(|p| organizer.contains(p)));
+        assert!(organizer.iter().all(|p| admin.contains(p)));
+    }
+}
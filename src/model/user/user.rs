@@ -46,6 +46,9 @@ pub struct User {
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub last_login: Option<DateTime<Utc>>,
+    /// Bumped on every password change; access tokens carrying an older
+    /// epoch are rejected
+    pub token_epoch: i32,
 }
 
 impl User {
@@ -60,6 +63,7 @@ impl User {
             created_at: now,
             updated_at: now,
             last_login: None,
+            token_epoch: 0,
         }
     }
 
@@ -69,6 +73,7 @@ impl User {
 
     pub fn update_password(&mut self, new_password: String) {
         self.password = new_password;
+        self.token_epoch += 1;
         self.updated_at = Utc::now();
     }
 
@@ -1,3 +1,4 @@
+pub mod password_reset_repo;
 pub mod token_repo;
 
 #[cfg(test)]
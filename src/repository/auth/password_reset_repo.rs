@@ -0,0 +1,75 @@
+use crate::model::auth::PasswordResetToken;
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use std::error::Error;
+use std::sync::Arc;
+use uuid::Uuid;
+
+#[async_trait]
+pub trait PasswordResetTokenRepository: Send + Sync {
+    async fn create(&self, token: &PasswordResetToken) -> Result<(), Box<dyn Error>>;
+    async fn find_by_token(&self, token: &str) -> Result<Option<PasswordResetToken>, Box<dyn Error>>;
+    /// Marks the token used; `false` if it already was, so only one caller
+    /// can redeem it
+    async fn mark_used(&self, token_id: Uuid) -> Result<bool, Box<dyn Error>>;
+}
+
+pub struct PostgresPasswordResetTokenRepository {
+    pool: Arc<PgPool>,
+}
+
+impl PostgresPasswordResetTokenRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PasswordResetTokenRepository for PostgresPasswordResetTokenRepository {
+    async fn create(&self, token: &PasswordResetToken) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            r#"
+            INSERT INTO password_reset_tokens (id, user_id, token, expires_at, used_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(token.id)
+        .bind(token.user_id)
+        .bind(&token.token)
+        .bind(token.expires_at)
+        .bind(token.used_at)
+        .bind(token.created_at)
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn find_by_token(&self, token: &str) -> Result<Option<PasswordResetToken>, Box<dyn Error>> {
+        let row = sqlx::query(
+            "SELECT id, user_id, token, expires_at, used_at, created_at FROM password_reset_tokens WHERE token = $1",
+        )
+        .bind(token)
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(row.map(|row| PasswordResetToken {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            token: row.get("token"),
+            expires_at: row.get("expires_at"),
+            used_at: row.get("used_at"),
+            created_at: row.get("created_at"),
+        }))
+    }
+
+    async fn mark_used(&self, token_id: Uuid) -> Result<bool, Box<dyn Error>> {
+        let result = sqlx::query(
+            "UPDATE password_reset_tokens SET used_at = NOW() WHERE id = $1 AND used_at IS NULL",
+        )
+        .bind(token_id)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+}
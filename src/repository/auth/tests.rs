@@ -1,7 +1,10 @@
 #[cfg(test)]
 mod token_repository_tests {
+    use super::super::password_reset_repo::{
+        PasswordResetTokenRepository, PostgresPasswordResetTokenRepository,
+    };
     use super::super::token_repo::{PostgresRefreshTokenRepository, TokenRepository};
-    use crate::model::auth::RefreshToken;
+    use crate::model::auth::{PasswordResetToken, RefreshToken};
     use chrono::{DateTime, Utc};
     use serial_test::serial;
     use sqlx::{PgPool, postgres::PgPoolOptions};
@@ -194,6 +197,33 @@ mod token_repository_tests {
         cleanup_test_db(&pool).await;
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_rotate_replaces_token_once() {
+        let pool = setup_test_db().await;
+        let repo = PostgresRefreshTokenRepository::new(pool.clone());
+
+        let user_id = create_test_user(&pool, None).await;
+
+        let token = RefreshToken::new(user_id, "rotate-token".to_string(), 7);
+        repo.create(&token).await.expect("Failed to insert token");
+
+        let successor = RefreshToken::new(user_id, "rotate-successor".to_string(), 7);
+        assert!(repo.rotate(token.id, &successor).await.expect("Rotate failed"));
+
+        let replaced = repo.find_by_token("rotate-token").await.expect("Query failed").unwrap();
+        assert!(replaced.is_revoked, "Rotated token should be revoked");
+        assert!(replaced.rotated_at.is_some(), "Rotated token should record when");
+        let stored = repo.find_by_token("rotate-successor").await.expect("Query failed").unwrap();
+        assert!(stored.is_valid(), "Successor should be valid");
+
+        let second = RefreshToken::new(user_id, "rotate-second".to_string(), 7);
+        assert!(!repo.rotate(token.id, &second).await.expect("Rotate failed"), "A token should only rotate once");
+        assert!(repo.find_by_token("rotate-second").await.expect("Query failed").is_none());
+
+        cleanup_test_db(&pool).await;
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_revoke_all_for_user() {
@@ -215,8 +245,11 @@ mod token_repository_tests {
             .await
             .expect("Failed to insert other token");
 
-        let result = repo.revoke_all_for_user(user_id).await;
-        assert!(result.is_ok(), "Revoke all for user query failed");
+        let revoked = repo
+            .revoke_all_for_user(user_id)
+            .await
+            .expect("Revoke all for user query failed");
+        assert_eq!(revoked, 2, "Should revoke both user tokens");
 
         let user_tokens = repo.find_by_user_id(user_id).await.expect("Query failed");
         for token in user_tokens {
@@ -238,6 +271,50 @@ mod token_repository_tests {
         );
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_revoke_all() {
+        let pool = setup_test_db().await;
+        let repo = PostgresRefreshTokenRepository::new(pool.clone());
+
+        let user_id = create_test_user(&pool, None).await;
+        let other_user_id = create_test_user(&pool, None).await;
+
+        let token = RefreshToken::new(user_id, "global-token1".to_string(), 7);
+        let other_token = RefreshToken::new(other_user_id, "global-token2".to_string(), 7);
+
+        repo.create(&token).await.expect("Failed to insert token");
+        repo.create(&other_token)
+            .await
+            .expect("Failed to insert other token");
+
+        let result = repo.revoke_all().await;
+        assert!(result.is_ok(), "Revoke all query failed");
+
+        for id in [user_id, other_user_id] {
+            let tokens = repo.find_by_user_id(id).await.expect("Query failed");
+            assert_eq!(tokens.len(), 1, "Should find 1 token per user");
+            assert!(tokens[0].is_revoked, "Every token should be revoked");
+        }
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_tokens_not_before_is_overwritten() {
+        let pool = setup_test_db().await;
+        let repo = PostgresRefreshTokenRepository::new(pool.clone());
+
+        // Past instants, so tokens issued by concurrent tests stay valid
+        let first = DateTime::from_timestamp(946_684_800, 0).unwrap();
+        let second = DateTime::from_timestamp(978_307_200, 0).unwrap();
+
+        repo.set_tokens_not_before(first).await.expect("Failed to set epoch");
+        repo.set_tokens_not_before(second).await.expect("Failed to set epoch");
+
+        let stored = repo.tokens_not_before().await.expect("Query failed");
+        assert_eq!(stored, Some(second), "Latest epoch should be stored");
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_token_validity() {
@@ -288,4 +365,34 @@ mod token_repository_tests {
 
         cleanup_test_db(&pool).await;
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn test_reset_token_can_be_marked_used_once() {
+        let pool = setup_test_db().await;
+        sqlx::query("DELETE FROM password_reset_tokens")
+            .execute(pool.as_ref())
+            .await
+            .expect("Failed to clean up test reset tokens");
+        let repo = PostgresPasswordResetTokenRepository::new(pool.clone());
+
+        let user_id = create_test_user(&pool, None).await;
+        let token = PasswordResetToken::new(user_id, "reset-token".to_string(), chrono::Duration::minutes(30));
+        repo.create(&token).await.expect("Failed to insert reset token");
+
+        let found = repo.find_by_token("reset-token").await.expect("Query failed").unwrap();
+        assert!(found.is_valid(), "Fresh reset token should be valid");
+
+        assert!(repo.mark_used(token.id).await.expect("Update failed"));
+        assert!(!repo.mark_used(token.id).await.expect("Update failed"), "Token should only be claimed once");
+
+        let found = repo.find_by_token("reset-token").await.expect("Query failed").unwrap();
+        assert!(!found.is_valid(), "Used reset token should not be valid");
+
+        sqlx::query("DELETE FROM password_reset_tokens")
+            .execute(pool.as_ref())
+            .await
+            .expect("Failed to clean up test reset tokens");
+        cleanup_test_db(&pool).await;
+    }
 }
@@ -1,4 +1,5 @@
 use crate::model::auth::RefreshToken;
+use chrono::{DateTime, Utc};
 use async_trait::async_trait;
 use sqlx::PgPool;
 use std::error::Error;
@@ -11,7 +12,18 @@ pub trait TokenRepository: Send + Sync {
     async fn find_by_token(&self, token: &str) -> Result<Option<RefreshToken>, Box<dyn Error>>;
     async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<RefreshToken>, Box<dyn Error>>;
     async fn revoke(&self, token_id: Uuid) -> Result<(), Box<dyn Error>>;
-    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), Box<dyn Error>>;
+    /// Revokes `token_id` as rotated and stores `successor`, in one
+    /// transaction. `false`, with nothing stored, if `token_id` was no
+    /// longer active.
+    async fn rotate(&self, token_id: Uuid, successor: &RefreshToken) -> Result<bool, Box<dyn Error>>;
+    async fn update_expiry(&self, token_id: Uuid, expires_at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+    /// Returns how many tokens were still active and are now revoked
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<u64, Box<dyn Error>>;
+    async fn revoke_all(&self) -> Result<(), Box<dyn Error>>;
+    /// Access tokens issued before this instant are rejected; `None` until
+    /// the first revoke-all that bumps it
+    async fn tokens_not_before(&self) -> Result<Option<DateTime<Utc>>, Box<dyn Error>>;
+    async fn set_tokens_not_before(&self, at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
 }
 
 pub struct PostgresRefreshTokenRepository {
@@ -47,7 +59,7 @@ impl TokenRepository for PostgresRefreshTokenRepository {
     async fn find_by_token(&self, token: &str) -> Result<Option<RefreshToken>, Box<dyn Error>> {
         let result = sqlx::query_as!(
             RefreshToken,
-            "SELECT id, user_id, token, expires_at, is_revoked, created_at FROM refresh_tokens WHERE token = $1",
+            "SELECT id, user_id, token, expires_at, is_revoked, created_at, rotated_at FROM refresh_tokens WHERE token = $1",
             token
         )
         .fetch_optional(&*self.pool)
@@ -59,7 +71,7 @@ impl TokenRepository for PostgresRefreshTokenRepository {
     async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<RefreshToken>, Box<dyn Error>> {
         let result = sqlx::query_as!(
             RefreshToken,
-            "SELECT id, user_id, token, expires_at, is_revoked, created_at FROM refresh_tokens WHERE user_id = $1",
+            "SELECT id, user_id, token, expires_at, is_revoked, created_at, rotated_at FROM refresh_tokens WHERE user_id = $1",
             user_id
         )
         .fetch_all(&*self.pool)
@@ -77,12 +89,87 @@ impl TokenRepository for PostgresRefreshTokenRepository {
         Ok(())
     }
 
-    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), Box<dyn Error>> {
-        sqlx::query("UPDATE refresh_tokens SET is_revoked = TRUE WHERE user_id = $1")
-            .bind(user_id)
+    async fn rotate(&self, token_id: Uuid, successor: &RefreshToken) -> Result<bool, Box<dyn Error>> {
+        let mut tx = self.pool.begin().await?;
+
+        let claimed = sqlx::query(
+            "UPDATE refresh_tokens SET is_revoked = TRUE, rotated_at = NOW() WHERE id = $1 AND is_revoked = FALSE",
+        )
+        .bind(token_id)
+        .execute(&mut *tx)
+        .await?;
+        if claimed.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, token, expires_at, is_revoked, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(successor.id)
+        .bind(successor.user_id)
+        .bind(&successor.token)
+        .bind(successor.expires_at)
+        .bind(successor.is_revoked)
+        .bind(successor.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn update_expiry(&self, token_id: Uuid, expires_at: DateTime<Utc>) -> Result<(), Box<dyn Error>> {
+        sqlx::query("UPDATE refresh_tokens SET expires_at = $1 WHERE id = $2")
+            .bind(expires_at)
+            .bind(token_id)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<u64, Box<dyn Error>> {
+        let result = sqlx::query(
+            "UPDATE refresh_tokens SET is_revoked = TRUE WHERE user_id = $1 AND is_revoked = FALSE",
+        )
+        .bind(user_id)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn revoke_all(&self) -> Result<(), Box<dyn Error>> {
+        sqlx::query("UPDATE refresh_tokens SET is_revoked = TRUE WHERE is_revoked = FALSE")
             .execute(&*self.pool)
             .await?;
 
         Ok(())
     }
+
+    async fn tokens_not_before(&self) -> Result<Option<DateTime<Utc>>, Box<dyn Error>> {
+        let result: Option<Option<DateTime<Utc>>> =
+            sqlx::query_scalar("SELECT tokens_not_before FROM auth_settings WHERE id = TRUE")
+                .fetch_optional(&*self.pool)
+                .await?;
+
+        Ok(result.flatten())
+    }
+
+    async fn set_tokens_not_before(&self, at: DateTime<Utc>) -> Result<(), Box<dyn Error>> {
+        sqlx::query(
+            r#"
+            INSERT INTO auth_settings (id, tokens_not_before) VALUES (TRUE, $1)
+            ON CONFLICT (id) DO UPDATE SET tokens_not_before = EXCLUDED.tokens_not_before
+            "#,
+        )
+        .bind(at)
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
 }
\ No newline at end of file
@@ -0,0 +1,165 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::model::transaction::BalanceAdjustment;
+use crate::repository::transaction::balance_repo::BalanceRepository;
+
+/// Append-only audit trail of admin balance corrections
+#[async_trait]
+pub trait BalanceAdjustmentRepository {
+    /// Adds `delta` to the user's existing balance and records the
+    /// adjustment with the resulting amount, both or neither. `None`, with
+    /// nothing written, if the balance would go negative and
+    /// `allow_negative` is not set.
+    async fn apply(
+        &self,
+        user_id: Uuid,
+        admin_id: Uuid,
+        delta: i64,
+        reason: &str,
+        allow_negative: bool,
+    ) -> Result<Option<BalanceAdjustment>, Box<dyn Error + Send + Sync>>;
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<BalanceAdjustment>, Box<dyn Error + Send + Sync>>;
+}
+
+pub struct InMemoryBalanceAdjustmentRepository {
+    adjustments: Mutex<Vec<BalanceAdjustment>>,
+    balances: Arc<dyn BalanceRepository + Send + Sync>,
+}
+
+impl InMemoryBalanceAdjustmentRepository {
+    pub fn new(balances: Arc<dyn BalanceRepository + Send + Sync>) -> Self {
+        Self {
+            adjustments: Mutex::new(Vec::new()),
+            balances,
+        }
+    }
+}
+
+#[async_trait]
+impl BalanceAdjustmentRepository for InMemoryBalanceAdjustmentRepository {
+    async fn apply(
+        &self,
+        user_id: Uuid,
+        admin_id: Uuid,
+        delta: i64,
+        reason: &str,
+        allow_negative: bool,
+    ) -> Result<Option<BalanceAdjustment>, Box<dyn Error + Send + Sync>> {
+        let mut balance = self
+            .balances
+            .find_by_user_id(user_id)
+            .await?
+            .ok_or("Balance not found")?;
+        if balance.amount.saturating_add(delta) < 0 && !allow_negative {
+            return Ok(None);
+        }
+        let resulting_balance = balance.adjust(delta, allow_negative)?;
+        self.balances.save(&balance).await?;
+
+        let adjustment = BalanceAdjustment::new(user_id, admin_id, delta, reason.to_string(), resulting_balance);
+        self.adjustments.lock().unwrap().push(adjustment.clone());
+        Ok(Some(adjustment))
+    }
+
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<BalanceAdjustment>, Box<dyn Error + Send + Sync>> {
+        let adjustments = self.adjustments.lock().unwrap();
+        Ok(adjustments
+            .iter()
+            .filter(|a| a.user_id == user_id)
+            .cloned()
+            .collect())
+    }
+}
+
+pub struct PostgresBalanceAdjustmentRepository {
+    pool: PgPool,
+}
+
+impl PostgresBalanceAdjustmentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl BalanceAdjustmentRepository for PostgresBalanceAdjustmentRepository {
+    async fn apply(
+        &self,
+        user_id: Uuid,
+        admin_id: Uuid,
+        delta: i64,
+        reason: &str,
+        allow_negative: bool,
+    ) -> Result<Option<BalanceAdjustment>, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        // Applied relative to the stored amount, so concurrent credits and
+        // debits are never overwritten
+        let resulting_balance: Option<i64> = sqlx::query_scalar(
+            "UPDATE balances SET amount = amount + $1, overdraft_allowed = (amount + $1 < 0), updated_at = NOW()
+             WHERE user_id = $2 AND (amount + $1 >= 0 OR $3) RETURNING amount",
+        )
+        .bind(delta)
+        .bind(user_id)
+        .bind(allow_negative)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(resulting_balance) = resulting_balance else {
+            return Ok(None);
+        };
+
+        let adjustment = BalanceAdjustment::new(user_id, admin_id, delta, reason.to_string(), resulting_balance);
+        sqlx::query(
+            "INSERT INTO balance_adjustments (id, user_id, admin_id, delta, reason, resulting_balance, created_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)",
+        )
+        .bind(adjustment.id)
+        .bind(adjustment.user_id)
+        .bind(adjustment.admin_id)
+        .bind(adjustment.delta)
+        .bind(&adjustment.reason)
+        .bind(adjustment.resulting_balance)
+        .bind(adjustment.created_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(adjustment))
+    }
+
+    async fn find_by_user(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<BalanceAdjustment>, Box<dyn Error + Send + Sync>> {
+        let rows = sqlx::query(
+            "SELECT id, user_id, admin_id, delta, reason, resulting_balance, created_at
+             FROM balance_adjustments WHERE user_id = $1 ORDER BY created_at",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| BalanceAdjustment {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                admin_id: row.get("admin_id"),
+                delta: row.get("delta"),
+                reason: row.get("reason"),
+                resulting_balance: row.get("resulting_balance"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+}
@@ -10,6 +10,9 @@ use crate::model::transaction::Balance;
 #[async_trait]
 pub trait BalancePersistenceStrategy {
     async fn save(&self, balance: &Balance) -> Result<(), Box<dyn Error + Send + Sync>>;
+    /// Stores `balance` unless the user already has one, and returns whichever
+    /// row ends up stored. Safe to call concurrently for the same user.
+    async fn create_if_absent(&self, balance: &Balance) -> Result<Balance, Box<dyn Error + Send + Sync>>;
     async fn find_by_user_id(
         &self,
         user_id: Uuid,
@@ -36,6 +39,11 @@ impl BalancePersistenceStrategy for InMemoryBalancePersistence {
         Ok(())
     }
 
+    async fn create_if_absent(&self, balance: &Balance) -> Result<Balance, Box<dyn Error + Send + Sync>> {
+        let mut balances = self.balances.write().unwrap();
+        Ok(balances.entry(balance.user_id).or_insert_with(|| balance.clone()).clone())
+    }
+
     async fn find_by_user_id(
         &self,
         user_id: Uuid,
@@ -48,6 +56,9 @@ impl BalancePersistenceStrategy for InMemoryBalancePersistence {
 #[async_trait]
 pub trait BalanceRepository {
     async fn save(&self, balance: &Balance) -> Result<(), Box<dyn Error + Send + Sync>>;
+    /// Stores `balance` unless the user already has one, and returns whichever
+    /// row ends up stored. Safe to call concurrently for the same user.
+    async fn create_if_absent(&self, balance: &Balance) -> Result<Balance, Box<dyn Error + Send + Sync>>;
     async fn find_by_user_id(
         &self,
         user_id: Uuid,
@@ -70,6 +81,10 @@ impl<S: BalancePersistenceStrategy + Send + Sync> BalanceRepository for DbBalanc
         self.strategy.save(balance).await
     }
 
+    async fn create_if_absent(&self, balance: &Balance) -> Result<Balance, Box<dyn Error + Send + Sync>> {
+        self.strategy.create_if_absent(balance).await
+    }
+
     async fn find_by_user_id(
         &self,
         user_id: Uuid,
@@ -91,16 +106,17 @@ impl PostgresBalancePersistence {
 #[async_trait]
 impl BalancePersistenceStrategy for PostgresBalancePersistence {
     async fn save(&self, balance: &Balance) -> Result<(), Box<dyn Error + Send + Sync>> {
-        let query = "INSERT INTO balances (id, user_id, amount, updated_at) 
-                    VALUES ($1, $2, $3, $4) 
+        let query = "INSERT INTO balances (id, user_id, amount, updated_at, overdraft_allowed) 
+                    VALUES ($1, $2, $3, $4, $5) 
                     ON CONFLICT (user_id) 
-                    DO UPDATE SET amount = EXCLUDED.amount, updated_at = EXCLUDED.updated_at";
+                    DO UPDATE SET amount = EXCLUDED.amount, updated_at = EXCLUDED.updated_at, overdraft_allowed = EXCLUDED.overdraft_allowed";
 
         let result = sqlx::query(query)
             .bind(balance.id)
             .bind(balance.user_id)
             .bind(balance.amount)
             .bind(balance.updated_at)
+            .bind(balance.overdraft_allowed)
             .execute(&self.pool)
             .await?;
 
@@ -111,6 +127,36 @@ impl BalancePersistenceStrategy for PostgresBalancePersistence {
         Ok(())
     }
 
+    async fn create_if_absent(&self, balance: &Balance) -> Result<Balance, Box<dyn Error + Send + Sync>> {
+        let query = "INSERT INTO balances (id, user_id, amount, updated_at)
+                    VALUES ($1, $2, $3, $4)
+                    ON CONFLICT (user_id) DO NOTHING
+                    RETURNING *";
+
+        let row = sqlx::query(query)
+            .bind(balance.id)
+            .bind(balance.user_id)
+            .bind(balance.amount)
+            .bind(balance.updated_at)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Balance {
+                id: row.get("id"),
+                user_id: row.get("user_id"),
+                amount: row.get("amount"),
+                updated_at: row.get("updated_at"),
+                overdraft_allowed: row.get("overdraft_allowed"),
+            }),
+            // Another request created it first; its row is the one to use
+            None => self
+                .find_by_user_id(balance.user_id)
+                .await?
+                .ok_or_else(|| "Balance disappeared after insert conflict".into()),
+        }
+    }
+
     async fn find_by_user_id(
         &self,
         user_id: Uuid,
@@ -128,6 +174,7 @@ impl BalancePersistenceStrategy for PostgresBalancePersistence {
                 user_id: row.get("user_id"),
                 amount: row.get("amount"),
                 updated_at: row.get("updated_at"),
+                overdraft_allowed: row.get("overdraft_allowed"),
             };
             Ok(Some(balance))
         } else {
@@ -1,2 +1,7 @@
 pub mod transaction_repo;
-pub mod balance_repo;
\ No newline at end of file
+pub mod balance_repo;
+pub mod payment_event_repo;
+pub mod balance_adjustment_repo;
+
+#[cfg(test)]
+pub mod tests;
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+use std::collections::HashSet;
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+use crate::model::transaction::TransactionStatus;
+use crate::repository::transaction::transaction_repo::{TransactionRepository, SETTLE_PENDING_SQL};
+
+/// Remembers which payment provider events have already been applied
+#[async_trait]
+pub trait PaymentEventRepository {
+    /// Claims `event_id` and, with it, moves the transaction to `status` and
+    /// stores `reference` if it is still pending. `false`, with nothing
+    /// changed, if the event had already been claimed.
+    async fn apply(
+        &self,
+        event_id: &str,
+        transaction_id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>>;
+}
+
+pub struct InMemoryPaymentEventRepository {
+    event_ids: Mutex<HashSet<String>>,
+    transactions: Arc<dyn TransactionRepository + Send + Sync>,
+}
+
+impl InMemoryPaymentEventRepository {
+    pub fn new(transactions: Arc<dyn TransactionRepository + Send + Sync>) -> Self {
+        Self {
+            event_ids: Mutex::new(HashSet::new()),
+            transactions,
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentEventRepository for InMemoryPaymentEventRepository {
+    async fn apply(
+        &self,
+        event_id: &str,
+        transaction_id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        if !self.event_ids.lock().unwrap().insert(event_id.to_string()) {
+            return Ok(false);
+        }
+
+        if let Err(e) = self.transactions.settle_pending(transaction_id, status, reference).await {
+            // Give the claim back, as a rolled back database transaction would
+            self.event_ids.lock().unwrap().remove(event_id);
+            return Err(e);
+        }
+        Ok(true)
+    }
+}
+
+pub struct PostgresPaymentEventRepository {
+    pool: PgPool,
+}
+
+impl PostgresPaymentEventRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl PaymentEventRepository for PostgresPaymentEventRepository {
+    async fn apply(
+        &self,
+        event_id: &str,
+        transaction_id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        let claimed = sqlx::query("INSERT INTO payment_webhook_events (event_id) VALUES ($1) ON CONFLICT (event_id) DO NOTHING")
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+        if claimed.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query(SETTLE_PENDING_SQL)
+            .bind(status.to_string().to_lowercase())
+            .bind(reference)
+            .bind(transaction_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,3 @@
+mod balance_adjustment_repo_tests;
+mod balance_repo_tests;
+mod payment_event_repo_tests;mod transaction_repo_tests;
@@ -1,12 +1,95 @@
 use async_trait::async_trait;
-use chrono::Utc;
-use sqlx::{PgPool, Row};
+use chrono::{DateTime, Utc};
+use sqlx::postgres::{PgArguments, PgRow};
+use sqlx::query::Query;
+use sqlx::{PgPool, Postgres, Row};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::error::Error;
-use std::sync::RwLock;
+use std::fmt;
+use std::sync::{Arc, RwLock};
 use uuid::Uuid;
 
-use crate::model::transaction::{Transaction, TransactionStatus};
+use crate::common::sort::SortOrder;
+use crate::repository::transaction::balance_repo::BalanceRepository;
+use crate::model::transaction::{
+    RefundReason, Transaction, TransactionCategory, TransactionStatus, TransactionStatusSnapshot,
+};
+
+/// Columns a transaction listing may be ordered by
+pub const TRANSACTION_SORT_KEYS: [&str; 3] = ["created_at", "updated_at", "amount"];
+
+/// System-wide transaction aggregates
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TransactionStats {
+    pub pending_count: i64,
+    /// Amount plus fees of successful ticket purchases; refunded ones no
+    /// longer count
+    pub revenue: i64,
+}
+
+/// In-memory counterpart of the Postgres aggregate in `stats`
+impl<'a> FromIterator<&'a Transaction> for TransactionStats {
+    fn from_iter<I: IntoIterator<Item = &'a Transaction>>(transactions: I) -> Self {
+        let mut stats = TransactionStats::default();
+        for transaction in transactions {
+            match transaction.status {
+                TransactionStatus::Pending => stats.pending_count += 1,
+                TransactionStatus::Success if transaction.category == TransactionCategory::Ticket => {
+                    stats.revenue += transaction.total_charged()
+                }
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
+/// Returned by `mark_refunded` when the transaction is no longer `Success`,
+/// i.e. a concurrent request already refunded it
+#[derive(Debug, PartialEq)]
+pub struct AlreadyRefunded;
+
+impl fmt::Display for AlreadyRefunded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Transaction already refunded")
+    }
+}
+
+impl Error for AlreadyRefunded {}
+
+/// Optional narrowing of a user's transaction history; unset fields match
+/// everything and both date bounds are inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct TransactionFilter {
+    pub status: Option<TransactionStatus>,
+    pub category: Option<TransactionCategory>,
+    pub from_date: Option<DateTime<Utc>>,
+    pub to_date: Option<DateTime<Utc>>,
+}
+
+impl TransactionFilter {
+    /// In-memory counterpart of `USER_FILTER_SQL`
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        self.status.is_none_or(|status| transaction.status == status)
+            && self.category.is_none_or(|category| transaction.category == category)
+            && self.from_date.is_none_or(|from| transaction.created_at >= from)
+            && self.to_date.is_none_or(|to| transaction.created_at <= to)
+    }
+}
+
+/// In-memory counterpart of the Postgres `ORDER BY` for `TRANSACTION_SORT_KEYS`
+pub fn sort_transactions(transactions: &mut [Transaction], sort: &SortOrder) {
+    transactions.sort_by(|a, b| {
+        let ordering = match sort.key() {
+            "amount" => a.amount.cmp(&b.amount),
+            "updated_at" => a.updated_at.cmp(&b.updated_at),
+            "created_at" => a.created_at.cmp(&b.created_at),
+            _ => Ordering::Equal,
+        };
+        if sort.is_descending() { ordering.reverse() } else { ordering }
+    });
+}
 
 #[async_trait]
 pub trait TransactionPersistenceStrategy {
@@ -14,32 +97,107 @@ pub trait TransactionPersistenceStrategy {
         &self,
         transaction: &Transaction,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    /// Saves `transaction` unless its user already holds `max_pending` live
+    /// pending transactions, counting and inserting as one step; `None` if
+    /// the cap was reached
+    async fn save_within_pending_cap(
+        &self,
+        transaction: &Transaction,
+        max_pending: usize,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
     async fn find_by_id(
         &self,
         id: Uuid,
     ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Status columns only, for cheap polling
+    async fn find_status(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync>>;
     async fn find_by_user(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// At most `limit` matching transactions after skipping `offset`, plus
+    /// how many match in total
+    async fn find_by_user_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<Transaction>, usize), Box<dyn Error + Send + Sync>>;
+    /// Pending transactions of the user whose payment window is still open
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync>>;
+    async fn find_by_external_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Transactions matching any of `ids`; unknown ids are skipped
+    async fn find_by_ids(
+        &self,
+        ids: &[Uuid],
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Leaving `Refunded` also drops the recorded refund reason
     async fn update_status(
         &self,
         id: Uuid,
         status: TransactionStatus,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    /// Moves a `Failed` transaction back to `Pending`; `None` if it was not
+    /// `Failed`
+    async fn reopen_failed(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Moves a `Pending` transaction to `status`, storing `reference` when
+    /// given, in one write; `None` if it was missing or already finalized
+    async fn settle_pending(
+        &self,
+        id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Debits the payer's balance by the amount charged and moves the
+    /// pending transaction to `Success`, both or neither
+    async fn pay_from_balance(
+        &self,
+        id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    /// Moves a `Success` transaction to `Refunded` and records why, in one
+    /// write; fails with `AlreadyRefunded` if it has already left `Success`
+    async fn mark_refunded(
+        &self,
+        id: Uuid,
+        reason: &RefundReason,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    async fn stats(&self) -> Result<TransactionStats, Box<dyn Error + Send + Sync>>;
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error + Send + Sync>>;
 }
 
 pub struct InMemoryTransactionPersistence {
     transactions: RwLock<HashMap<Uuid, Transaction>>,
+    balances: Option<Arc<dyn BalanceRepository + Send + Sync>>,
 }
 
 impl InMemoryTransactionPersistence {
     pub fn new() -> Self {
         Self {
             transactions: RwLock::new(HashMap::new()),
+            balances: None,
         }
     }
+
+    /// Balance store debited by `pay_from_balance`
+    pub fn with_balances(mut self, balances: Arc<dyn BalanceRepository + Send + Sync>) -> Self {
+        self.balances = Some(balances);
+        self
+    }
 }
 
 #[async_trait]
@@ -53,6 +211,25 @@ impl TransactionPersistenceStrategy for InMemoryTransactionPersistence {
         transactions.insert(transaction.id, transaction_clone.clone());
         Ok(transaction_clone)
     }
+
+    async fn save_within_pending_cap(
+        &self,
+        transaction: &Transaction,
+        max_pending: usize,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now();
+        let mut transactions = self.transactions.write().unwrap();
+        let pending = transactions
+            .values()
+            .filter(|t| t.user_id == transaction.user_id && t.status == TransactionStatus::Pending && !t.is_expired(now))
+            .count();
+        if pending >= max_pending {
+            return Ok(None);
+        }
+
+        transactions.insert(transaction.id, transaction.clone());
+        Ok(Some(transaction.clone()))
+    }
     async fn find_by_id(
         &self,
         id: Uuid,
@@ -64,16 +241,76 @@ impl TransactionPersistenceStrategy for InMemoryTransactionPersistence {
     async fn find_by_user(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
         let transactions = self.transactions.read().unwrap();
-        let user_transactions = transactions
+        let mut user_transactions: Vec<Transaction> = transactions
             .values()
             .filter(|t| t.user_id == user_id)
             .cloned()
             .collect();
+        sort_transactions(&mut user_transactions, sort);
         Ok(user_transactions)
     }
 
+    async fn find_by_user_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<Transaction>, usize), Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.read().unwrap();
+        let mut matching: Vec<Transaction> = transactions
+            .values()
+            .filter(|t| t.user_id == user_id && filter.matches(t))
+            .cloned()
+            .collect();
+        sort_transactions(&mut matching, sort);
+        let total = matching.len();
+        Ok((matching.into_iter().skip(offset).take(limit).collect(), total))
+    }
+
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now();
+        let transactions = self.transactions.read().unwrap();
+        Ok(transactions
+            .values()
+            .filter(|t| t.user_id == user_id && t.status == TransactionStatus::Pending && !t.is_expired(now))
+            .count())
+    }
+
+    async fn find_status(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.read().unwrap();
+        Ok(transactions.get(&id).map(TransactionStatusSnapshot::from))
+    }
+
+    async fn find_by_external_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.read().unwrap();
+        Ok(transactions
+            .values()
+            .find(|t| t.external_reference.as_deref() == Some(reference))
+            .cloned())
+    }
+
+    async fn find_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.read().unwrap();
+        Ok(ids.iter().filter_map(|id| transactions.get(id).cloned()).collect())
+    }
+
     async fn update_status(
         &self,
         id: Uuid,
@@ -83,6 +320,9 @@ impl TransactionPersistenceStrategy for InMemoryTransactionPersistence {
 
         if let Some(transaction) = transactions.get_mut(&id) {
             transaction.status = status;
+            if status != TransactionStatus::Refunded {
+                transaction.refund_reason = None;
+            }
             transaction.updated_at = Utc::now();
             Ok(transaction.clone())
         } else {
@@ -90,6 +330,92 @@ impl TransactionPersistenceStrategy for InMemoryTransactionPersistence {
         }
     }
 
+    async fn reopen_failed(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let mut transactions = self.transactions.write().unwrap();
+
+        match transactions.get_mut(&id) {
+            Some(transaction) if transaction.status == TransactionStatus::Failed => {
+                transaction.status = TransactionStatus::Pending;
+                transaction.updated_at = Utc::now();
+                Ok(Some(transaction.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn settle_pending(
+        &self,
+        id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let mut transactions = self.transactions.write().unwrap();
+
+        match transactions.get_mut(&id) {
+            Some(transaction) if transaction.status == TransactionStatus::Pending => {
+                transaction.status = status;
+                if let Some(reference) = reference {
+                    transaction.external_reference = Some(reference.to_string());
+                }
+                transaction.updated_at = Utc::now();
+                Ok(Some(transaction.clone()))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    async fn pay_from_balance(
+        &self,
+        id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        let balances = self.balances.as_ref().ok_or("No balance store configured")?;
+        let pending = match self.transactions.read().unwrap().get(&id) {
+            Some(t) if t.status == TransactionStatus::Pending => t.clone(),
+            Some(_) => return Err("Transaction is already finalized".into()),
+            None => return Err("Transaction not found".into()),
+        };
+
+        let mut balance = balances
+            .find_by_user_id(pending.user_id)
+            .await?
+            .ok_or("Insufficient funds")?;
+        balance.withdraw(pending.total_charged())?;
+        balances.save(&balance).await?;
+
+        let mut transactions = self.transactions.write().unwrap();
+        let transaction = transactions.get_mut(&id).ok_or("Transaction not found")?;
+        transaction.status = TransactionStatus::Success;
+        transaction.updated_at = Utc::now();
+        Ok(transaction.clone())
+    }
+
+    async fn mark_refunded(
+        &self,
+        id: Uuid,
+        reason: &RefundReason,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        let mut transactions = self.transactions.write().unwrap();
+
+        match transactions.get_mut(&id) {
+            Some(transaction) if transaction.status == TransactionStatus::Success => {
+                transaction.status = TransactionStatus::Refunded;
+                transaction.refund_reason = Some(reason.clone());
+                transaction.updated_at = Utc::now();
+                Ok(transaction.clone())
+            }
+            Some(_) => Err(Box::new(AlreadyRefunded)),
+            None => Err("Transaction not found".into()),
+        }
+    }
+
+    async fn stats(&self) -> Result<TransactionStats, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.read().unwrap();
+        Ok(transactions.values().collect())
+    }
+
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error + Send + Sync>> {
         let mut transactions = self.transactions.write().unwrap();
 
@@ -107,19 +433,86 @@ pub trait TransactionRepository {
         &self,
         transaction: &Transaction,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    /// Saves `transaction` unless its user already holds `max_pending` live
+    /// pending transactions, counting and inserting as one step; `None` if
+    /// the cap was reached
+    async fn save_within_pending_cap(
+        &self,
+        transaction: &Transaction,
+        max_pending: usize,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
     async fn find_by_id(
         &self,
         id: Uuid,
     ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Status columns only, for cheap polling
+    async fn find_status(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync>>;
     async fn find_by_user(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// At most `limit` matching transactions after skipping `offset`, plus
+    /// how many match in total
+    async fn find_by_user_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<Transaction>, usize), Box<dyn Error + Send + Sync>>;
+    /// Pending transactions of the user whose payment window is still open
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync>>;
+    async fn find_by_external_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Transactions matching any of `ids`; unknown ids are skipped
+    async fn find_by_ids(
+        &self,
+        ids: &[Uuid],
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Leaving `Refunded` also drops the recorded refund reason
     async fn update_status(
         &self,
         id: Uuid,
         status: TransactionStatus,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    /// Moves a `Failed` transaction back to `Pending`; `None` if it was not
+    /// `Failed`
+    async fn reopen_failed(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Moves a `Pending` transaction to `status`, storing `reference` when
+    /// given, in one write; `None` if it was missing or already finalized
+    async fn settle_pending(
+        &self,
+        id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>>;
+    /// Debits the payer's balance by the amount charged and moves the
+    /// pending transaction to `Success`, both or neither
+    async fn pay_from_balance(
+        &self,
+        id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    /// Moves a `Success` transaction to `Refunded` and records why, in one
+    /// write; fails with `AlreadyRefunded` if it has already left `Success`
+    async fn mark_refunded(
+        &self,
+        id: Uuid,
+        reason: &RefundReason,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>>;
+    async fn stats(&self) -> Result<TransactionStats, Box<dyn Error + Send + Sync>>;
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error + Send + Sync>>;
 }
 
@@ -144,6 +537,14 @@ impl<S: TransactionPersistenceStrategy + Send + Sync> TransactionRepository
         self.strategy.save(transaction).await
     }
 
+    async fn save_within_pending_cap(
+        &self,
+        transaction: &Transaction,
+        max_pending: usize,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        self.strategy.save_within_pending_cap(transaction, max_pending).await
+    }
+
     async fn find_by_id(
         &self,
         id: Uuid,
@@ -151,11 +552,53 @@ impl<S: TransactionPersistenceStrategy + Send + Sync> TransactionRepository
         self.strategy.find_by_id(id).await
     }
 
+    async fn find_status(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync>> {
+        self.strategy.find_status(id).await
+    }
+
     async fn find_by_user(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
-        self.strategy.find_by_user(user_id).await
+        self.strategy.find_by_user(user_id, sort).await
+    }
+
+    async fn find_by_user_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<Transaction>, usize), Box<dyn Error + Send + Sync>> {
+        self.strategy
+            .find_by_user_paged(user_id, filter, sort, limit, offset)
+            .await
+    }
+
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        self.strategy.count_active_pending(user_id).await
+    }
+
+    async fn find_by_external_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        self.strategy.find_by_external_reference(reference).await
+    }
+
+    async fn find_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
+        self.strategy.find_by_ids(ids).await
     }
 
     async fn update_status(
@@ -166,11 +609,114 @@ impl<S: TransactionPersistenceStrategy + Send + Sync> TransactionRepository
         self.strategy.update_status(id, status).await
     }
 
+    async fn reopen_failed(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        self.strategy.reopen_failed(id).await
+    }
+
+    async fn settle_pending(
+        &self,
+        id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        self.strategy.settle_pending(id, status, reference).await
+    }
+
+    async fn pay_from_balance(
+        &self,
+        id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        self.strategy.pay_from_balance(id).await
+    }
+
+    async fn mark_refunded(
+        &self,
+        id: Uuid,
+        reason: &RefundReason,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        self.strategy.mark_refunded(id, reason).await
+    }
+
+    async fn stats(&self) -> Result<TransactionStats, Box<dyn Error + Send + Sync>> {
+        self.strategy.stats().await
+    }
+
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error + Send + Sync>> {
         self.strategy.delete(id).await
     }
 }
 
+/// Maps a `SELECT *` or `RETURNING *` row of `transactions`
+fn transaction_from_row(row: &PgRow) -> Transaction {
+    Transaction {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        ticket_id: row.get("ticket_id"),
+        amount: row.get("amount"),
+        fee_amount: row.get("fee_amount"),
+        description: row.get("description"),
+        payment_method: row.get("payment_method"),
+        external_reference: row.get("external_reference"),
+        // `transaction_status` is a Postgres enum, which sqlx refuses to
+        // decode as text even though its wire format is the label
+        status: TransactionStatus::from_string(row.try_get_unchecked("status").unwrap()),
+        category: row.get::<String, _>("category").parse().unwrap_or_default(),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        expires_at: row.get("expires_at"),
+        refund_reason: refund_reason_from_row(row),
+    }
+}
+
+/// `None` unless the row carries a recognised refund reason category
+fn refund_reason_from_row(row: &PgRow) -> Option<RefundReason> {
+    let category: Option<String> = row.get("refund_reason_category");
+    category.and_then(|c| c.parse().ok()).map(|category| RefundReason {
+        category,
+        note: row.get("refund_reason_note"),
+    })
+}
+
+/// `WHERE` clause for a `TransactionFilter` over one user's transactions;
+/// binds user id, status, from date, to date and category as `$1`..`$5`
+const USER_FILTER_SQL: &str = "user_id = $1 \
+    AND ($2::transaction_status IS NULL OR status = $2::transaction_status) \
+    AND ($3::timestamptz IS NULL OR created_at >= $3) \
+    AND ($4::timestamptz IS NULL OR created_at <= $4) \
+    AND ($5::text IS NULL OR category = $5)";
+
+/// `INSERT ... RETURNING *` of a new transaction, ready to run on a pool or
+/// inside a database transaction
+fn insert_transaction(transaction: &Transaction) -> Query<'_, Postgres, PgArguments> {
+    sqlx::query("INSERT INTO transactions (id, user_id, ticket_id, amount, fee_amount, description, payment_method, external_reference, status, created_at, updated_at, expires_at, category) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9::transaction_status, $10, $11, $12, $13) RETURNING *")
+        .bind(transaction.id)
+        .bind(transaction.user_id)
+        .bind(transaction.ticket_id)
+        .bind(transaction.amount)
+        .bind(transaction.fee_amount)
+        .bind(&transaction.description)
+        .bind(&transaction.payment_method)
+        .bind(&transaction.external_reference)
+        .bind(transaction.status.to_string().to_lowercase())
+        .bind(transaction.created_at)
+        .bind(transaction.updated_at)
+        .bind(transaction.expires_at)
+        .bind(transaction.category.to_string().to_lowercase())
+}
+
+/// Moves a pending transaction to `$1`, keeping the stored reference when
+/// `$2` is null; binds status, reference and id
+pub(crate) const SETTLE_PENDING_SQL: &str = "UPDATE transactions \
+    SET status = $1::transaction_status, external_reference = COALESCE($2, external_reference), updated_at = NOW() \
+    WHERE id = $3 AND status = 'pending' RETURNING *";
+
+/// Counts the pending transactions of user `$1` that have not expired
+const ACTIVE_PENDING_COUNT_SQL: &str = "SELECT COUNT(*) FILTER (WHERE status = 'pending' AND (expires_at IS NULL OR expires_at > NOW())) \
+    FROM transactions WHERE user_id = $1";
+
 pub struct PostgresTransactionPersistence {
     pool: PgPool,
 }
@@ -187,34 +733,40 @@ impl TransactionPersistenceStrategy for PostgresTransactionPersistence {
         &self,
         transaction: &Transaction,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
-        let query = "INSERT INTO transactions (id, user_id, ticket_id, amount, description, payment_method, external_reference, status, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8::transaction_status, $9, $10) RETURNING *";
-        let row = sqlx::query(query)
-            .bind(transaction.id)
-            .bind(transaction.user_id)
-            .bind(transaction.ticket_id)
-            .bind(transaction.amount)            .bind(&transaction.description)
-            .bind(&transaction.payment_method)
-            .bind(&transaction.external_reference)
-            .bind(transaction.status.to_string().to_lowercase())
-            .bind(transaction.created_at)
-            .bind(transaction.updated_at)
+        let row = insert_transaction(transaction)
             .fetch_one(&self.pool)
             .await?;
 
-        let saved_transaction = Transaction {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            ticket_id: row.get("ticket_id"),
-            amount: row.get("amount"),
-            description: row.get("description"),
-            payment_method: row.get("payment_method"),
-            external_reference: row.get("external_reference"),
-            status: TransactionStatus::from_string(row.get("status")),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-        };
+        Ok(transaction_from_row(&row))
+    }
+
+    async fn save_within_pending_cap(
+        &self,
+        transaction: &Transaction,
+        max_pending: usize,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        // Concurrent checkouts of one user queue here until this transaction
+        // ends, so none of them counts before another's insert is visible
+        sqlx::query("SELECT pg_advisory_xact_lock(hashtextextended($1::text, 0))")
+            .bind(transaction.user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let pending: i64 = sqlx::query_scalar(ACTIVE_PENDING_COUNT_SQL)
+            .bind(transaction.user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        if pending as usize >= max_pending {
+            return Ok(None);
+        }
 
-        Ok(saved_transaction)
+        let row = insert_transaction(transaction)
+            .fetch_one(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        Ok(Some(transaction_from_row(&row)))
     }
 
     async fn find_by_id(
@@ -226,57 +778,147 @@ impl TransactionPersistenceStrategy for PostgresTransactionPersistence {
             .bind(id)
             .fetch_optional(&self.pool)
             .await?;
-        if let Some(row) = row {
-            let transaction = Transaction {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                ticket_id: row.get("ticket_id"),
-                amount: row.get("amount"),
-                description: row.get("description"),
-                payment_method: row.get("payment_method"),
-                external_reference: row.get("external_reference"),
-                status: TransactionStatus::from_string(row.get("status")),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            };
-            Ok(Some(transaction))
-        } else {
-            Ok(None)
-        }
+        Ok(row.as_ref().map(transaction_from_row))
     }
+
+    async fn find_status(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync>> {
+        let query = "SELECT id, user_id, status::text AS status, updated_at FROM transactions WHERE id = $1";
+        let row = sqlx::query(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|row| TransactionStatusSnapshot {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            status: TransactionStatus::from_string(row.get("status")),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+
     async fn find_by_user(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
-        let query = "SELECT * FROM transactions WHERE user_id = $1";
-        let rows = sqlx::query(query)
+        let query = format!(
+            "SELECT * FROM transactions WHERE user_id = $1 ORDER BY {}",
+            sort.to_sql()
+        );
+        let rows = sqlx::query(&query)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let transactions = rows
+            .iter()
+            .map(transaction_from_row)
+            .collect();
+
+        Ok(transactions)
+    }
+
+    async fn find_by_user_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        limit: usize,
+        offset: usize,
+    ) -> Result<(Vec<Transaction>, usize), Box<dyn Error + Send + Sync>> {
+        let status = filter.status.map(|status| status.to_string().to_lowercase());
+        let category = filter.category.map(|category| category.to_string().to_lowercase());
+
+        let total: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM transactions WHERE {}",
+            USER_FILTER_SQL
+        ))
+        .bind(user_id)
+        .bind(&status)
+        .bind(filter.from_date)
+        .bind(filter.to_date)
+        .bind(&category)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let query = format!(
+            "SELECT * FROM transactions WHERE {} ORDER BY {} LIMIT $6 OFFSET $7",
+            USER_FILTER_SQL,
+            sort.to_sql()
+        );
+        let rows = sqlx::query(&query)
             .bind(user_id)
+            .bind(&status)
+            .bind(filter.from_date)
+            .bind(filter.to_date)
+            .bind(&category)
+            .bind(limit as i64)
+            .bind(offset as i64)
             .fetch_all(&self.pool)
             .await?;
 
         let transactions = rows
             .iter()
-            .map(|row| Transaction {
-                id: row.get("id"),
-                user_id: row.get("user_id"),
-                ticket_id: row.get("ticket_id"),
-                amount: row.get("amount"),
-                description: row.get("description"),
-                payment_method: row.get("payment_method"),
-                external_reference: row.get("external_reference"),
-                status: TransactionStatus::from_string(row.get("status")),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            })
+            .map(transaction_from_row)
+            .collect();
+
+        Ok((transactions, total as usize))
+    }
+
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let count: i64 = sqlx::query_scalar(ACTIVE_PENDING_COUNT_SQL)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+
+    async fn find_by_external_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let query = "SELECT * FROM transactions WHERE external_reference = $1 LIMIT 1";
+        let row = sqlx::query(query)
+            .bind(reference)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(transaction_from_row))
+    }
+
+    async fn find_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
+        let query = "SELECT * FROM transactions WHERE id = ANY($1)";
+        let rows = sqlx::query(query)
+            .bind(ids)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let transactions = rows
+            .iter()
+            .map(transaction_from_row)
             .collect();
 
         Ok(transactions)
-    }    async fn update_status(
+    }
+
+    async fn update_status(
         &self,
         id: Uuid,
         status: TransactionStatus,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
-        let query = "UPDATE transactions SET status = $1::transaction_status WHERE id = $2 RETURNING *";
+        let query = "UPDATE transactions SET status = $1::transaction_status, \
+                refund_reason_category = CASE WHEN $1::transaction_status = 'refunded' THEN refund_reason_category END, \
+                refund_reason_note = CASE WHEN $1::transaction_status = 'refunded' THEN refund_reason_note END \
+            WHERE id = $2 RETURNING *";
 
         let row = sqlx::query(query)
             .bind(status.to_string().to_lowercase())
@@ -285,24 +927,102 @@ impl TransactionPersistenceStrategy for PostgresTransactionPersistence {
             .await?;
 
         match row {
-            Some(row) => {
-                let transaction = Transaction {
-                    id: row.get("id"),
-                    user_id: row.get("user_id"),
-                    ticket_id: row.get("ticket_id"),
-                    amount: row.get("amount"),
-                    description: row.get("description"),
-                    payment_method: row.get("payment_method"),
-                    external_reference: row.get("external_reference"),
-                    status: TransactionStatus::from_string(row.get("status")),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                };
-                Ok(transaction)
-            }
+            Some(row) => Ok(transaction_from_row(&row)),
             None => Err("Transaction not found".into()),
         }
     }
+
+    async fn reopen_failed(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let query = "UPDATE transactions SET status = 'pending'::transaction_status, updated_at = NOW() WHERE id = $1 AND status = 'failed' RETURNING *";
+        let row = sqlx::query(query)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(transaction_from_row))
+    }
+
+    async fn settle_pending(
+        &self,
+        id: Uuid,
+        status: TransactionStatus,
+        reference: Option<&str>,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let row = sqlx::query(SETTLE_PENDING_SQL)
+            .bind(status.to_string().to_lowercase())
+            .bind(reference)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.as_ref().map(transaction_from_row))
+    }
+
+    async fn pay_from_balance(
+        &self,
+        id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        let mut tx = self.pool.begin().await?;
+
+        let query = "UPDATE transactions SET status = 'success'::transaction_status, updated_at = NOW() WHERE id = $1 AND status = 'pending' RETURNING *";
+        let row = match sqlx::query(query).bind(id).fetch_optional(&mut *tx).await? {
+            Some(row) => row,
+            None => return Err("Transaction is already finalized".into()),
+        };
+        let transaction = transaction_from_row(&row);
+
+        // Dropping `tx` on an early return rolls the status change back
+        let debited = sqlx::query(
+            "UPDATE balances SET amount = amount - $1, updated_at = NOW() WHERE user_id = $2 AND amount >= $1",
+        )
+        .bind(transaction.total_charged())
+        .bind(transaction.user_id)
+        .execute(&mut *tx)
+        .await?;
+        if debited.rows_affected() == 0 {
+            return Err("Insufficient funds".into());
+        }
+
+        tx.commit().await?;
+        Ok(transaction)
+    }
+
+    async fn mark_refunded(
+        &self,
+        id: Uuid,
+        reason: &RefundReason,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        let query = "UPDATE transactions SET status = 'refunded'::transaction_status, refund_reason_category = $1, refund_reason_note = $2 WHERE id = $3 AND status = 'success' RETURNING *";
+
+        let row = sqlx::query(query)
+            .bind(reason.category.to_string())
+            .bind(&reason.note)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(transaction_from_row(&row)),
+            None => Err(Box::new(AlreadyRefunded)),
+        }
+    }
+
+    async fn stats(&self) -> Result<TransactionStats, Box<dyn Error + Send + Sync>> {
+        let query = "SELECT \
+                COUNT(*) FILTER (WHERE status = 'pending') AS pending_count, \
+                COALESCE(SUM(amount + fee_amount) FILTER (WHERE status = 'success' AND category = 'ticket'), 0)::BIGINT AS revenue \
+            FROM transactions";
+        let row = sqlx::query(query).fetch_one(&self.pool).await?;
+
+        Ok(TransactionStats {
+            pending_count: row.get("pending_count"),
+            revenue: row.get("revenue"),
+        })
+    }
+
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error + Send + Sync>> {
         let query = "DELETE FROM transactions WHERE id = $1";
 
@@ -26,6 +26,17 @@ async fn test_find_by_email() {
     assert_eq!(found.unwrap().id, user.id);
 }
 
+#[tokio::test]
+async fn test_find_by_email_ignores_case() {
+    let repo = create_test_repo();
+    let user = create_test_user("Mixed.Case@danilliman.com");
+
+    repo.create(&user).await.unwrap();
+
+    let found = repo.find_by_email("mixed.case@DANILLIMAN.com").await.unwrap();
+    assert_eq!(found.map(|u| u.id), Some(user.id));
+}
+
 #[tokio::test]
 async fn test_update_user() {
     let repo = create_test_repo();
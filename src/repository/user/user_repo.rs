@@ -10,12 +10,14 @@ use std::str::FromStr;
 
 #[async_trait]
 pub trait UserRepository: Send + Sync {
+    /// Emails are matched case-insensitively
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>>;
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, Box<dyn Error>>;
     async fn create(&self, user: &User) -> Result<(), Box<dyn Error>>;
     async fn update(&self, user: &User) -> Result<(), Box<dyn Error>>;
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error>>;
     async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>>;
+    async fn count(&self) -> Result<i64, Box<dyn Error>>;
 }
 
 #[async_trait]
@@ -26,6 +28,7 @@ pub trait UserPersistenceStrategy: Send + Sync {
     async fn update(&self, user: &User) -> Result<(), Box<dyn Error>>;
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error>>;
     async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>>;
+    async fn count(&self) -> Result<i64, Box<dyn Error>>;
 }
 
 pub struct InMemoryUserPersistence {
@@ -44,7 +47,7 @@ impl InMemoryUserPersistence {
 impl UserPersistenceStrategy for InMemoryUserPersistence {
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>> {
         let users = self.users.read().unwrap();
-        let user = users.values().find(|u| u.email == email).cloned();
+        let user = users.values().find(|u| u.email.eq_ignore_ascii_case(email)).cloned();
         Ok(user)
     }
 
@@ -85,6 +88,10 @@ impl UserPersistenceStrategy for InMemoryUserPersistence {
         let all_users = users.values().cloned().collect();
         Ok(all_users)
     }
+
+    async fn count(&self) -> Result<i64, Box<dyn Error>> {
+        Ok(self.users.read().unwrap().len() as i64)
+    }
 }
 
 pub struct DbUserRepository<S: UserPersistenceStrategy> {
@@ -122,6 +129,10 @@ impl<S: UserPersistenceStrategy + Send + Sync> UserRepository for DbUserReposito
     async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>> {
         self.strategy.find_all().await
     }
+
+    async fn count(&self) -> Result<i64, Box<dyn Error>> {
+        self.strategy.count().await
+    }
 }
 
 pub struct PostgresUserRepository {
@@ -138,7 +149,7 @@ impl PostgresUserRepository {
 impl UserPersistenceStrategy for PostgresUserRepository {
     async fn find_by_email(&self, email: &str) -> Result<Option<User>, Box<dyn Error>> {
         // Modified query to cast role to text
-        let query = "SELECT id, name, email, password, role::text as role, created_at, updated_at, last_login FROM users WHERE email = $1";
+        let query = "SELECT id, name, email, password, role::text as role, created_at, updated_at, last_login, token_epoch FROM users WHERE LOWER(email) = LOWER($1)";
         
         let row = sqlx::query(query)
             .bind(email)
@@ -154,13 +165,14 @@ impl UserPersistenceStrategy for PostgresUserRepository {
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
             last_login: row.get("last_login"),
+            token_epoch: row.get("token_epoch"),
         });
         
         Ok(user)
     }
 
     async fn find_by_id(&self, id: Uuid) -> Result<Option<User>, Box<dyn Error>> {
-        let query = "SELECT id, name, email, password, role::text as role, created_at, updated_at, last_login FROM users WHERE id = $1";
+        let query = "SELECT id, name, email, password, role::text as role, created_at, updated_at, last_login, token_epoch FROM users WHERE id = $1";
         
         let row = sqlx::query(query)
             .bind(id)
@@ -176,13 +188,14 @@ impl UserPersistenceStrategy for PostgresUserRepository {
             created_at: row.get("created_at"),
             updated_at: row.get("updated_at"),
             last_login: row.get("last_login"),
+            token_epoch: row.get("token_epoch"),
         });
         
         Ok(user)
     }
     
     async fn create(&self, user: &User) -> Result<(), Box<dyn Error>> {
-        let query = "INSERT INTO users (id, name, email, password, role, created_at, updated_at, last_login) VALUES ($1, $2, $3, $4, $5::user_role, $6, $7, $8)";
+        let query = "INSERT INTO users (id, name, email, password, role, created_at, updated_at, last_login, token_epoch) VALUES ($1, $2, $3, $4, $5::user_role, $6, $7, $8, $9)";
         
         sqlx::query(query)
             .bind(user.id)
@@ -193,6 +206,7 @@ impl UserPersistenceStrategy for PostgresUserRepository {
             .bind(user.created_at)
             .bind(user.updated_at)
             .bind(user.last_login)
+            .bind(user.token_epoch)
             .execute(&*self.pool)
             .await?;
         
@@ -200,7 +214,7 @@ impl UserPersistenceStrategy for PostgresUserRepository {
     }
 
     async fn update(&self, user: &User) -> Result<(), Box<dyn Error>> {
-        let query = "UPDATE users SET name = $1, email = $2, password = $3, role = $4::user_role, updated_at = $5, last_login = $6 WHERE id = $7";
+        let query = "UPDATE users SET name = $1, email = $2, password = $3, role = $4::user_role, updated_at = $5, last_login = $6, token_epoch = $7 WHERE id = $8";
         
         let result = sqlx::query(query)
             .bind(&user.name)
@@ -209,6 +223,7 @@ impl UserPersistenceStrategy for PostgresUserRepository {
             .bind(user.role.to_string())
             .bind(user.updated_at)
             .bind(user.last_login)
+            .bind(user.token_epoch)
             .bind(user.id)
             .execute(&*self.pool)
             .await?;
@@ -235,7 +250,7 @@ impl UserPersistenceStrategy for PostgresUserRepository {
 
     async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>> {
         // Modified query to cast role to text
-        let query = "SELECT id, name, email, password, role::text as role, created_at, updated_at, last_login FROM users";
+        let query = "SELECT id, name, email, password, role::text as role, created_at, updated_at, last_login, token_epoch FROM users";
         
         let rows = sqlx::query(query)
             .fetch_all(&*self.pool)
@@ -251,9 +266,17 @@ impl UserPersistenceStrategy for PostgresUserRepository {
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
                 last_login: row.get("last_login"),
+                token_epoch: row.get("token_epoch"),
             })
             .collect();
         
         Ok(users)
     }
+
+    async fn count(&self) -> Result<i64, Box<dyn Error>> {
+        let row = sqlx::query("SELECT COUNT(*) AS count FROM users")
+            .fetch_one(&*self.pool)
+            .await?;
+        Ok(row.get("count"))
+    }
 }
\ No newline at end of file
@@ -1,5 +1,9 @@
+use crate::common::password::PasswordPolicy;
+use crate::error::ValidationError;
 use crate::model::user::User;
-use crate::model::auth::RefreshToken;
+use crate::model::auth::{PasswordResetToken, RESET_TOKEN_LIFETIME_MINUTES, RefreshToken};
+use crate::middleware::rate_limit::RateLimiter;
+use crate::repository::auth::password_reset_repo::PasswordResetTokenRepository;
 use crate::repository::auth::token_repo::TokenRepository;
 use crate::repository::user::user_repo::UserRepository;
 use argon2::{self, Argon2, PasswordHash, PasswordVerifier};
@@ -11,22 +15,70 @@ use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header,
 use rocket::fairing::Result;
 use serde::{Serialize, Deserialize};
 use std::error::Error;
+use std::fmt;
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
 use uuid::Uuid;
 
+/// Returned by `logout` for a value that isn't a refresh token this service issues
+#[derive(Debug, PartialEq)]
+pub struct MalformedRefreshToken;
+
+impl fmt::Display for MalformedRefreshToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Malformed refresh token")
+    }
+}
+
+impl Error for MalformedRefreshToken {}
+
+/// Returned by `reset_password` for a reset token that is unknown, expired or already used
+#[derive(Debug, PartialEq)]
+pub struct InvalidResetToken;
+
+impl fmt::Display for InvalidResetToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid or expired reset token")
+    }
+}
+
+impl Error for InvalidResetToken {}
+
 pub struct AuthService {
     jwt_secret: String,
     jwt_refresh_secret: String,
     pepper: String,
+    jwt_leeway_secs: u64,
+    max_active_sessions: Option<usize>,
+    refresh_token_lifetime_days: i64,
+    sliding_refresh: Option<SlidingRefresh>,
+    tokens_not_before: AtomicI64,
+    password_reset_limiter: RateLimiter,
+    email_check_limiter: RateLimiter,
+    registration_enabled: bool,
+    password_policy: PasswordPolicy,
     token_repository: Option<Arc<dyn TokenRepository>>,
     user_repository: Option<Arc<dyn UserRepository>>,
+    reset_token_repository: Option<Arc<dyn PasswordResetTokenRepository>>,
+}
+
+/// Extends a stored refresh token used close to its expiry, up to an
+/// absolute cap counted from when the token was issued
+#[derive(Debug, Clone, Copy)]
+pub struct SlidingRefresh {
+    pub renew_within: Duration,
+    pub max_lifetime: Duration,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 struct Claims {
     sub: String,
     role: String,
+    iat: i64,
     exp: i64,
+    #[serde(default)]
+    epoch: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -49,8 +101,18 @@ impl AuthService {
             jwt_secret, 
             jwt_refresh_secret, 
             pepper,
+            jwt_leeway_secs: 30,
+            max_active_sessions: None,
+            refresh_token_lifetime_days: 7,
+            sliding_refresh: None,
+            tokens_not_before: AtomicI64::new(0),
+            password_reset_limiter: RateLimiter::new(3, std::time::Duration::from_secs(3600)),
+            email_check_limiter: RateLimiter::new(10, std::time::Duration::from_secs(60)),
+            registration_enabled: true,
+            password_policy: PasswordPolicy::new(0),
             token_repository: None,
             user_repository: None,
+            reset_token_repository: None,
         }
     }
 
@@ -64,6 +126,67 @@ impl AuthService {
         self
     }
 
+    pub fn with_reset_token_repository(mut self, repo: Arc<dyn PasswordResetTokenRepository>) -> Self {
+        self.reset_token_repository = Some(repo);
+        self
+    }
+
+    /// Caps forgot-password requests per email and per client IP within `window`
+    pub fn with_password_reset_limit(mut self, max_requests: usize, window: std::time::Duration) -> Self {
+        self.password_reset_limiter = RateLimiter::new(max_requests, window);
+        self
+    }
+
+    /// Caps email availability checks per client IP within `window`
+    pub fn with_email_check_limit(mut self, max_requests: usize, window: std::time::Duration) -> Self {
+        self.email_check_limiter = RateLimiter::new(max_requests, window);
+        self
+    }
+
+    /// Closes self-service signups; login and every other flow keep working
+    pub fn with_registration_enabled(mut self, enabled: bool) -> Self {
+        self.registration_enabled = enabled;
+        self
+    }
+
+    pub fn registration_enabled(&self) -> bool {
+        self.registration_enabled
+    }
+
+    /// Strength floor for passwords chosen at registration or password change;
+    /// by default any password is accepted
+    pub fn with_password_policy(mut self, policy: PasswordPolicy) -> Self {
+        self.password_policy = policy;
+        self
+    }
+
+    pub fn check_password_strength(&self, password: &str, field_name: &str) -> Result<(), ValidationError> {
+        self.password_policy.check(password, field_name)
+    }
+
+    /// Seconds of clock skew tolerated when checking `exp`/`nbf` on access tokens
+    pub fn with_jwt_leeway(mut self, leeway_secs: u64) -> Self {
+        self.jwt_leeway_secs = leeway_secs;
+        self
+    }
+
+    /// Caps concurrent refresh tokens per user; the oldest session is revoked
+    /// when a new one would exceed the cap
+    pub fn with_max_active_sessions(mut self, max_sessions: usize) -> Self {
+        self.max_active_sessions = Some(max_sessions);
+        self
+    }
+
+    pub fn with_refresh_token_lifetime_days(mut self, days: i64) -> Self {
+        self.refresh_token_lifetime_days = days;
+        self
+    }
+
+    pub fn with_sliding_refresh(mut self, sliding: SlidingRefresh) -> Self {
+        self.sliding_refresh = Some(sliding);
+        self
+    }
+
     pub fn hash_password(&self, password: &str) -> Result<String, Box<dyn Error>> {
         let salt = SaltString::generate(&mut OsRng);
         let argon2 = Argon2::default();
@@ -79,18 +202,18 @@ impl AuthService {
         Ok(argon2.verify_password(password_with_pepper.as_bytes(), &parsed_hash).is_ok())
     }
 
-    pub async fn generate_token(&self, user: &User) -> Result<TokenPair, Box<dyn Error>> {
-        // Access Token
+    fn issue_access_token(&self, user: &User) -> Result<(String, i64), Box<dyn Error>> {
         let expiration = Utc::now()
             .checked_add_signed(Duration::hours(24))
             .expect("valid timestamp")
             .timestamp();
 
-        
         let claims = Claims {
             sub: user.id.to_string(),
             role: user.role.to_string(),
+            iat: Utc::now().timestamp(),
             exp: expiration,
+            epoch: user.token_epoch,
         };
 
         let token = encode(
@@ -98,12 +221,16 @@ impl AuthService {
             &claims,
             &EncodingKey::from_secret(self.jwt_secret.as_bytes())
         )?;
-        
 
+        Ok((token, expiration))
+    }
+
+    pub async fn generate_token(&self, user: &User) -> Result<TokenPair, Box<dyn Error>> {
+        let (token, expiration) = self.issue_access_token(user)?;
 
         // Refresh Token
         let refresh_exp = Utc::now()
-            .checked_add_signed(Duration::days(7))
+            .checked_add_signed(Duration::days(self.refresh_token_lifetime_days))
             .expect("valid timestamp")
             .timestamp();
 
@@ -111,10 +238,14 @@ impl AuthService {
 
         // Store refresh token in database if repository is configured
         if let Some(repo) = &self.token_repository {
+            if let Some(max_sessions) = self.max_active_sessions {
+                self.enforce_session_limit(repo.as_ref(), user.id, max_sessions).await?;
+            }
+
             let refresh_token = RefreshToken::new(
                 user.id,
                 refresh_token_str.clone(),
-                7 // 7 days expiration
+                self.refresh_token_lifetime_days,
             );
             repo.create(&refresh_token).await?;
         }
@@ -143,40 +274,118 @@ impl AuthService {
         })
     }
 
-    pub fn verify_token(&self, token: &str) -> Result<Uuid, Box<dyn Error>> {
+    pub fn jwt_validation(&self) -> Validation {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.leeway = self.jwt_leeway_secs;
+        validation.validate_nbf = true;
+        validation
+    }
+
+    async fn enforce_session_limit(
+        &self,
+        repo: &dyn TokenRepository,
+        user_id: Uuid,
+        max_sessions: usize,
+    ) -> Result<(), Box<dyn Error>> {
+        let mut active: Vec<RefreshToken> = repo
+            .find_by_user_id(user_id)
+            .await?
+            .into_iter()
+            .filter(|t| t.is_valid())
+            .collect();
+
+        if active.len() < max_sessions {
+            return Ok(());
+        }
+
+        // Make room for the session about to be created
+        active.sort_by_key(|t| t.created_at);
+        let excess = active.len() + 1 - max_sessions.max(1);
+        for token in active.iter().take(excess) {
+            repo.revoke(token.id).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn verify_token(&self, token: &str) -> Result<Uuid, Box<dyn Error>> {
         let decoding_key = DecodingKey::from_secret(self.jwt_secret.as_bytes());
-        let validation = Validation::default();
+        let validation = self.jwt_validation();
         let token_data = decode::<Claims>(token, &decoding_key, &validation)?;
+        if !self.is_issued_after_epoch(token_data.claims.iat).await? {
+            return Err("Token has been revoked".into());
+        }
         let user_id = Uuid::parse_str(&token_data.claims.sub)?;
         Ok(user_id)
     }
 
+    /// Stored refresh tokens are single use: each refresh rotates the
+    /// presented token into a new one that keeps the session's issue time
+    /// and expiry, pushed later under sliding renewal. An already rotated
+    /// token presented again means it leaked, so every session of its user
+    /// is revoked; tokens revoked by logout or the session cap are just
+    /// refused. Stateless (JWT) refresh tokens can't be revoked, so a new
+    /// pair is minted.
     pub async fn refresh_access_token(&self, token: &str) -> Result<TokenPair, Box<dyn Error>> {
-        let user_id = if let Some(repo) = &self.token_repository {
-            // Verify token in database
-            let stored_token = repo.find_by_token(token).await?
-                .ok_or("Invalid refresh token")?;
-                
-            if !stored_token.is_valid() {
-                return Err("Token expired or revoked".into());
-            }
-            
-            stored_token.user_id
-        } else {
-            // Fall back to JWT validation
+        let Some(repo) = &self.token_repository else {
             let decoding_key = DecodingKey::from_secret(self.jwt_refresh_secret.as_bytes());
             let validation = Validation::default();
             let token_data = decode::<RefreshClaims>(token, &decoding_key, &validation)?;
-            Uuid::parse_str(&token_data.claims.sub)?
+            let user = self.find_refresh_user(Uuid::parse_str(&token_data.claims.sub)?).await?;
+            return self.generate_token(&user).await;
+        };
+
+        let stored_token = repo.find_by_token(token).await?
+            .ok_or("Invalid refresh token")?;
+
+        if stored_token.rotated_at.is_some() {
+            repo.revoke_all_for_user(stored_token.user_id).await?;
+            return Err("Invalid refresh token".into());
+        }
+
+        if !stored_token.is_valid() {
+            return Err("Token expired or revoked".into());
+        }
+
+        let mut expires_at = stored_token.expires_at;
+        if let Some(sliding) = self.sliding_refresh
+            && let Some(renewed) = stored_token.renewed_expiry(
+                Utc::now(),
+                Duration::days(self.refresh_token_lifetime_days),
+                sliding.renew_within,
+                sliding.max_lifetime,
+            )
+        {
+            expires_at = renewed;
+        }
+
+        let user = self.find_refresh_user(stored_token.user_id).await?;
+        let rotated = RefreshToken {
+            id: Uuid::new_v4(),
+            token: Uuid::new_v4().to_string(),
+            expires_at,
+            is_revoked: false,
+            ..stored_token
         };
-        
-        // Get actual user from repository if available
-        let user = if let Some(repo) = &self.user_repository {
-            repo.find_by_id(user_id).await?
-                .ok_or("User not found")?
+        // Another refresh of the same token won the race
+        if !repo.rotate(stored_token.id, &rotated).await? {
+            return Err("Invalid refresh token".into());
+        }
+
+        let (access_token, expires_in) = self.issue_access_token(&user)?;
+        Ok(TokenPair {
+            access_token,
+            refresh_token: rotated.token,
+            expires_in,
+        })
+    }
+
+    async fn find_refresh_user(&self, user_id: Uuid) -> Result<User, Box<dyn Error>> {
+        if let Some(repo) = &self.user_repository {
+            Ok(repo.find_by_id(user_id).await?.ok_or("User not found")?)
         } else {
             // Fallback to placeholder if no user repository
-            User {
+            Ok(User {
                 id: user_id,
                 name: String::new(),
                 email: String::new(),
@@ -185,22 +394,155 @@ impl AuthService {
                 created_at: Utc::now(),
                 updated_at: Utc::now(),
                 last_login: None,
-            }
+                token_epoch: 0,
+            })
+        }
+    }
+
+    /// Revokes one refresh token. Unknown or already revoked tokens are
+    /// accepted so a repeated logout still succeeds; only a value that can't
+    /// be a refresh token at all is an error.
+    pub async fn logout(&self, refresh_token: &str) -> Result<(), Box<dyn Error>> {
+        let Some(repo) = &self.token_repository else {
+            // Stateless refresh tokens can't be revoked; just check the shape
+            let decoding_key = DecodingKey::from_secret(self.jwt_refresh_secret.as_bytes());
+            let mut validation = Validation::default();
+            validation.validate_exp = false;
+            decode::<RefreshClaims>(refresh_token, &decoding_key, &validation)
+                .map_err(|_| MalformedRefreshToken)?;
+            return Ok(());
         };
-        
-        self.generate_token(&user).await
+
+        if Uuid::parse_str(refresh_token).is_err() {
+            return Err(Box::new(MalformedRefreshToken));
+        }
+        let stored_token = repo.find_by_token(refresh_token).await?;
+        if let Some(stored_token) = stored_token.filter(|t| !t.is_revoked) {
+            repo.revoke(stored_token.id).await?;
+        }
+        Ok(())
     }
-    
-    pub async fn logout(&self, user_id: Uuid) -> Result<(), Box<dyn Error>> {
+
+    /// Revokes every refresh token the user holds and returns how many were
+    /// still active; `0` when there was nothing to revoke.
+    pub async fn logout_all(&self, user_id: Uuid) -> Result<u64, Box<dyn Error>> {
         if let Some(repo) = &self.token_repository {
-            repo.revoke_all_for_user(user_id).await?;
-            Ok(())
+            repo.revoke_all_for_user(user_id).await
         } else {
             // No action needed for JWT-only implementation
-            Ok(())
+            Ok(0)
+        }
+    }
+
+    /// Revokes every refresh token in the system. With `bump_epoch`, access
+    /// tokens issued before now are rejected as well. The epoch is stored with
+    /// the tokens; without a token repository it only lives in memory.
+    pub async fn revoke_all_tokens(&self, bump_epoch: bool) -> Result<(), Box<dyn Error>> {
+        let now = Utc::now();
+        match &self.token_repository {
+            Some(repo) => {
+                repo.revoke_all().await?;
+                if bump_epoch {
+                    repo.set_tokens_not_before(now).await?;
+                }
+            }
+            None if bump_epoch => self.tokens_not_before.store(now.timestamp(), Ordering::SeqCst),
+            None => {}
+        }
+        Ok(())
+    }
+
+    pub fn allow_password_reset_request(&self, email: &str, client_ip: Option<IpAddr>) -> bool {
+        let email_allowed = self
+            .password_reset_limiter
+            .try_acquire(&format!("email:{}", email.trim().to_lowercase()));
+        let ip_allowed = client_ip.is_none_or(|ip| {
+            self.password_reset_limiter.try_acquire(&format!("ip:{}", ip))
+        });
+        email_allowed && ip_allowed
+    }
+
+    /// Issues a single-use reset token for the account registered under
+    /// `email`. `None` when there is no such account or nowhere to store tokens.
+    pub async fn create_reset_token(&self, email: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let (Some(reset_repo), Some(user_repo)) = (&self.reset_token_repository, &self.user_repository) else {
+            return Ok(None);
+        };
+        let Some(user) = user_repo.find_by_email(email.trim()).await? else {
+            return Ok(None);
+        };
+
+        let reset_token = PasswordResetToken::new(
+            user.id,
+            Uuid::new_v4().to_string(),
+            Duration::minutes(RESET_TOKEN_LIFETIME_MINUTES),
+        );
+        reset_repo.create(&reset_token).await?;
+        Ok(Some(reset_token.token))
+    }
+
+    /// Redeems a reset token and sets `new_password`. As with a password
+    /// change, the user's token epoch is bumped and every refresh token revoked.
+    pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<(), Box<dyn Error>> {
+        let (Some(reset_repo), Some(user_repo)) = (&self.reset_token_repository, &self.user_repository) else {
+            return Err(Box::new(InvalidResetToken));
+        };
+        let reset_token = reset_repo
+            .find_by_token(token)
+            .await?
+            .filter(|t| t.is_valid())
+            .ok_or(InvalidResetToken)?;
+        let mut user = user_repo
+            .find_by_id(reset_token.user_id)
+            .await?
+            .ok_or(InvalidResetToken)?;
+
+        // Claim the token before touching the password so two concurrent
+        // resets with the same token can't both go through
+        if !reset_repo.mark_used(reset_token.id).await? {
+            return Err(Box::new(InvalidResetToken));
+        }
+
+        user.update_password(self.hash_password(new_password)?);
+        user_repo.update(&user).await?;
+        self.logout_all(user.id).await?;
+        Ok(())
+    }
+
+    /// Availability checks are keyed by IP only, since probing many different
+    /// emails is exactly the abuse being limited
+    pub fn allow_email_check(&self, client_ip: Option<IpAddr>) -> bool {
+        let key = match client_ip {
+            Some(ip) => format!("ip:{}", ip),
+            None => "ip:unknown".to_string(),
+        };
+        self.email_check_limiter.try_acquire(&key)
+    }
+
+    /// Whether an access token's `epoch` claim still matches the user's
+    /// current epoch. Without a user repository there is nothing to compare
+    /// against, so every epoch is accepted.
+    pub async fn is_current_token_epoch(&self, user_id: Uuid, epoch: i32) -> Result<bool, Box<dyn Error>> {
+        match &self.user_repository {
+            Some(repo) => Ok(repo
+                .find_by_id(user_id)
+                .await?
+                .is_some_and(|user| user.token_epoch == epoch)),
+            None => Ok(true),
         }
     }
 
+    pub async fn is_issued_after_epoch(&self, issued_at: i64) -> Result<bool, Box<dyn Error>> {
+        let not_before = match &self.token_repository {
+            Some(repo) => repo
+                .tokens_not_before()
+                .await?
+                .map_or(0, |at| at.timestamp()),
+            None => self.tokens_not_before.load(Ordering::SeqCst),
+        };
+        Ok(issued_at >= not_before)
+    }
+
     pub fn get_jwt_secret(&self) -> &str {
         &self.jwt_secret
     }
@@ -1,12 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use super::super::auth_service::AuthService;
-    use crate::model::auth::RefreshToken;
+    use super::super::auth_service::{AuthService, InvalidResetToken, MalformedRefreshToken, SlidingRefresh};
+    use crate::model::auth::{PasswordResetToken, RefreshToken};
     use crate::model::user::{User, UserRole};
+    use crate::repository::auth::password_reset_repo::PasswordResetTokenRepository;
     use crate::repository::auth::token_repo::TokenRepository;
     use crate::repository::user::user_repo::UserRepository;
     use async_trait::async_trait;
-    use chrono::Utc;
+    use chrono::{DateTime, Utc};
     use mockall::mock;
     use mockall::predicate::*;
     use std::error::Error;
@@ -21,7 +22,22 @@ mod tests {
             async fn find_by_token(&self, token: &str) -> Result<Option<RefreshToken>, Box<dyn Error>>;
             async fn find_by_user_id(&self, user_id: Uuid) -> Result<Vec<RefreshToken>, Box<dyn Error>>;
             async fn revoke(&self, token_id: Uuid) -> Result<(), Box<dyn Error>>;
-            async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<(), Box<dyn Error>>;
+            async fn rotate(&self, token_id: Uuid, successor: &RefreshToken) -> Result<bool, Box<dyn Error>>;
+            async fn update_expiry(&self, token_id: Uuid, expires_at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+            async fn revoke_all_for_user(&self, user_id: Uuid) -> Result<u64, Box<dyn Error>>;
+            async fn revoke_all(&self) -> Result<(), Box<dyn Error>>;
+            async fn tokens_not_before(&self) -> Result<Option<DateTime<Utc>>, Box<dyn Error>>;
+            async fn set_tokens_not_before(&self, at: DateTime<Utc>) -> Result<(), Box<dyn Error>>;
+        }
+    }
+
+    mock! {
+        pub ResetTokenRepo {}
+        #[async_trait]
+        impl PasswordResetTokenRepository for ResetTokenRepo {
+            async fn create(&self, token: &PasswordResetToken) -> Result<(), Box<dyn Error>>;
+            async fn find_by_token(&self, token: &str) -> Result<Option<PasswordResetToken>, Box<dyn Error>>;
+            async fn mark_used(&self, token_id: Uuid) -> Result<bool, Box<dyn Error>>;
         }
     }
 
@@ -35,6 +51,7 @@ mod tests {
             async fn update(&self, user: &User) -> Result<(), Box<dyn Error>>;
             async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error>>;
             async fn find_all(&self) -> Result<Vec<User>, Box<dyn Error>>;
+            async fn count(&self) -> Result<i64, Box<dyn Error>>;
         }
     }    
     
@@ -85,6 +102,7 @@ mod tests {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             last_login: None,
+            token_epoch: 0,
         };
 
         let token_pair = auth_service
@@ -109,6 +127,7 @@ mod tests {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             last_login: None,
+            token_epoch: 0,
         };
         
         let token_pair = auth_service
@@ -118,10 +137,11 @@ mod tests {
             
         let user_id = auth_service
             .verify_token(&token_pair.access_token)
+            .await
             .expect("Failed to verify token");
             
         assert_eq!(user_id, user.id, "Token should verify to correct user ID");
-        let verify_result = auth_service.verify_token("invalid-token");
+        let verify_result = auth_service.verify_token("invalid-token").await;
         assert!(verify_result.is_err(), "Invalid token should fail verification");
     }    #[tokio::test]
     
@@ -140,6 +160,7 @@ mod tests {
             created_at: chrono::Utc::now(),
             updated_at: chrono::Utc::now(),
             last_login: None,
+            token_epoch: 0,
         };
         
         let refresh_token = RefreshToken {
@@ -149,7 +170,9 @@ mod tests {
             expires_at: Utc::now() + chrono::Duration::days(7),
             is_revoked: false,
             created_at: Utc::now(),
+            rotated_at: None,
         };
+        let old_token_id = refresh_token.id;
         
         mock_token_repo.expect_find_by_token()
             .with(eq(refresh_token_str))
@@ -159,8 +182,16 @@ mod tests {
             .with(eq(user_id))
             .returning(move |_| Ok(Some(user.clone())));
         
-        mock_token_repo.expect_create()
-            .returning(|_| Ok(()));
+        mock_token_repo.expect_rotate()
+            .withf(move |token_id, successor| {
+                *token_id == old_token_id
+                    && successor.user_id == user_id
+                    && successor.id != old_token_id
+                    && successor.token != refresh_token_str
+                    && !successor.is_revoked
+            })
+            .times(1)
+            .returning(|_, _| Ok(true));
             
         let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
             .with_token_repository(Arc::new(mock_token_repo))
@@ -173,6 +204,50 @@ mod tests {
             
         assert!(!token_pair.access_token.is_empty(), "New access token should not be empty");
         assert!(!token_pair.refresh_token.is_empty(), "New refresh token should not be empty");
+        assert_ne!(token_pair.refresh_token, refresh_token_str, "Refresh token should be rotated");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_with_rotated_token_revokes_all_sessions() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let user_id = Uuid::new_v4();
+        let mut stolen = session_token(user_id, "already-rotated", 10);
+        stolen.is_revoked = true;
+        stolen.rotated_at = Some(Utc::now());
+
+        mock_token_repo.expect_find_by_token()
+            .with(eq("already-rotated"))
+            .returning(move |_| Ok(Some(stolen.clone())));
+        mock_token_repo.expect_revoke_all_for_user()
+            .with(eq(user_id))
+            .times(1)
+            .returning(|_| Ok(2));
+        mock_token_repo.expect_rotate().never();
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_token_repository(Arc::new(mock_token_repo));
+
+        let err = auth_service.refresh_access_token("already-rotated").await.unwrap_err();
+        assert_eq!(err.to_string(), "Invalid refresh token");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_with_logged_out_token_keeps_other_sessions() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let mut logged_out = session_token(Uuid::new_v4(), "logged-out", 10);
+        logged_out.is_revoked = true;
+
+        mock_token_repo.expect_find_by_token()
+            .with(eq("logged-out"))
+            .returning(move |_| Ok(Some(logged_out.clone())));
+        mock_token_repo.expect_revoke_all_for_user().never();
+        mock_token_repo.expect_rotate().never();
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_token_repository(Arc::new(mock_token_repo));
+
+        let err = auth_service.refresh_access_token("logged-out").await.unwrap_err();
+        assert_eq!(err.to_string(), "Token expired or revoked");
     }
     
     #[tokio::test]
@@ -191,18 +266,330 @@ mod tests {
     }
     
     #[tokio::test]
-    async fn test_logout() {
+    async fn test_logout_all() {
         let mut mock_token_repo = MockTokenRepo::new();
         let user_id = Uuid::new_v4();
         
         mock_token_repo.expect_revoke_all_for_user()
             .with(eq(user_id))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(2));
             
         let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
             .with_token_repository(Arc::new(mock_token_repo));
             
-        let result = auth_service.logout(user_id).await;
-        assert!(result.is_ok(), "Logout should succeed");
+        let result = auth_service.logout_all(user_id).await;
+        assert_eq!(result.unwrap(), 2, "Logout should report the revoked tokens");
+    }
+
+    #[tokio::test]
+    async fn test_logout_revokes_presented_token() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let stored = RefreshToken::new(Uuid::new_v4(), Uuid::new_v4().to_string(), 7);
+        let stored_id = stored.id;
+        let presented = stored.token.clone();
+
+        mock_token_repo.expect_find_by_token()
+            .with(eq(presented.clone()))
+            .returning(move |_| Ok(Some(stored.clone())));
+        mock_token_repo.expect_revoke()
+            .with(eq(stored_id))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_token_repository(Arc::new(mock_token_repo));
+
+        assert!(auth_service.logout(&presented).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_logout_already_revoked_token_succeeds() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let mut stored = RefreshToken::new(Uuid::new_v4(), Uuid::new_v4().to_string(), 7);
+        stored.is_revoked = true;
+        let presented = stored.token.clone();
+
+        // No revoke expectation: revoking again would fail the test
+        mock_token_repo.expect_find_by_token()
+            .returning(move |_| Ok(Some(stored.clone())));
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_token_repository(Arc::new(mock_token_repo));
+
+        assert!(auth_service.logout(&presented).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_logout_rejects_malformed_token() {
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_token_repository(Arc::new(MockTokenRepo::new()));
+
+        let err = auth_service.logout("not-a-token").await.unwrap_err();
+        assert!(err.downcast_ref::<MalformedRefreshToken>().is_some());
+    }
+
+    fn encode_access_token(secret: &str, exp: i64) -> String {
+        let claims = crate::middleware::auth::Claims {
+            sub: Uuid::new_v4().to_string(),
+            role: "Attendee".to_string(),
+            iat: Utc::now().timestamp() - 120,
+            exp: exp as usize,
+            epoch: 0,
+        };
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &claims,
+            &jsonwebtoken::EncodingKey::from_secret(secret.as_bytes()),
+        )
+        .expect("Failed to encode token")
+    }
+
+    #[tokio::test]
+    async fn test_verify_token_expired_within_leeway() {
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_jwt_leeway(30);
+        let token = encode_access_token("test_secret", Utc::now().timestamp() - 10);
+
+        let result = auth_service.verify_token(&token).await;
+        assert!(result.is_ok(), "Token expired within leeway should be accepted");
+    }
+
+    #[tokio::test]
+    async fn test_verify_token_expired_beyond_leeway() {
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_jwt_leeway(30);
+        let token = encode_access_token("test_secret", Utc::now().timestamp() - 60);
+
+        let result = auth_service.verify_token(&token).await;
+        assert!(result.is_err(), "Token expired beyond leeway should be rejected");
+    }
+
+    fn session_token(user_id: Uuid, token: &str, age_minutes: i64) -> RefreshToken {
+        RefreshToken {
+            id: Uuid::new_v4(),
+            user_id,
+            token: token.to_string(),
+            expires_at: Utc::now() + chrono::Duration::days(7),
+            is_revoked: false,
+            created_at: Utc::now() - chrono::Duration::minutes(age_minutes),
+            rotated_at: None,
+        }
+    }
+
+    fn session_user(user_id: Uuid) -> User {
+        User {
+            id: user_id,
+            role: UserRole::Attendee,
+            name: "Session User".to_string(),
+            email: "session@example.com".to_string(),
+            password: "test_password_hash".to_string(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+            last_login: None,
+            token_epoch: 0,
+        }
+    }
+
+    fn sliding_service(stored: RefreshToken, expected_expiry: DateTime<Utc>) -> AuthService {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let mut mock_user_repo = MockUserRepo::new();
+        let user = session_user(stored.user_id);
+        let token_id = stored.id;
+
+        mock_token_repo.expect_find_by_token()
+            .returning(move |_| Ok(Some(stored.clone())));
+        mock_user_repo.expect_find_by_id()
+            .returning(move |_| Ok(Some(user.clone())));
+        mock_token_repo.expect_rotate()
+            .withf(move |old_id, successor| {
+                *old_id == token_id && (successor.expires_at - expected_expiry).num_seconds().abs() <= 5
+            })
+            .times(1)
+            .returning(|_, _| Ok(true));
+
+        AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_refresh_token_lifetime_days(7)
+            .with_sliding_refresh(SlidingRefresh {
+                renew_within: chrono::Duration::days(1),
+                max_lifetime: chrono::Duration::days(30),
+            })
+            .with_token_repository(Arc::new(mock_token_repo))
+            .with_user_repository(Arc::new(mock_user_repo))
+    }
+
+    #[tokio::test]
+    async fn test_refresh_near_expiry_extends_token() {
+        let mut stored = session_token(Uuid::new_v4(), "near-expiry", 0);
+        stored.created_at = Utc::now() - chrono::Duration::days(6);
+        stored.expires_at = Utc::now() + chrono::Duration::hours(12);
+        let auth_service = sliding_service(stored, Utc::now() + chrono::Duration::days(7));
+
+        let token_pair = auth_service.refresh_access_token("near-expiry").await.unwrap();
+        assert_ne!(token_pair.refresh_token, "near-expiry");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_far_from_expiry_keeps_token() {
+        let stored = session_token(Uuid::new_v4(), "fresh", 0);
+        let expires_at = stored.expires_at;
+        let auth_service = sliding_service(stored, expires_at);
+
+        assert!(auth_service.refresh_access_token("fresh").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_refresh_extension_capped_at_max_lifetime() {
+        let mut stored = session_token(Uuid::new_v4(), "old-session", 0);
+        stored.created_at = Utc::now() - chrono::Duration::days(27);
+        stored.expires_at = Utc::now() + chrono::Duration::hours(12);
+        let cap = stored.created_at + chrono::Duration::days(30);
+        let auth_service = sliding_service(stored, cap);
+
+        assert!(auth_service.refresh_access_token("old-session").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_generate_token_beyond_session_cap_revokes_oldest() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let user_id = Uuid::new_v4();
+        let oldest = session_token(user_id, "oldest-session", 60);
+        let newer = session_token(user_id, "newer-session", 5);
+        let oldest_id = oldest.id;
+        let existing = vec![newer, oldest];
+
+        mock_token_repo.expect_find_by_user_id()
+            .with(eq(user_id))
+            .returning(move |_| Ok(existing.clone()));
+        mock_token_repo.expect_revoke()
+            .with(eq(oldest_id))
+            .times(1)
+            .returning(|_| Ok(()));
+        mock_token_repo.expect_create()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_max_active_sessions(2)
+            .with_token_repository(Arc::new(mock_token_repo));
+
+        let result = auth_service.generate_token(&session_user(user_id)).await;
+        assert!(result.is_ok(), "Login beyond the cap should still succeed");
+    }
+
+    #[tokio::test]
+    async fn test_generate_token_under_session_cap_keeps_sessions() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let user_id = Uuid::new_v4();
+        let existing = vec![session_token(user_id, "only-session", 10)];
+
+        mock_token_repo.expect_find_by_user_id()
+            .with(eq(user_id))
+            .returning(move |_| Ok(existing.clone()));
+        mock_token_repo.expect_revoke().never();
+        mock_token_repo.expect_create()
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_max_active_sessions(2)
+            .with_token_repository(Arc::new(mock_token_repo));
+
+        let result = auth_service.generate_token(&session_user(user_id)).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_revoke_all_tokens_with_epoch_rejects_earlier_access_tokens() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        let not_before = Arc::new(std::sync::Mutex::new(None));
+        mock_token_repo.expect_revoke_all()
+            .times(1)
+            .returning(|| Ok(()));
+        let stored = not_before.clone();
+        mock_token_repo.expect_set_tokens_not_before()
+            .times(1)
+            .returning(move |at| {
+                *stored.lock().unwrap() = Some(at);
+                Ok(())
+            });
+        let stored = not_before.clone();
+        mock_token_repo.expect_tokens_not_before()
+            .returning(move || Ok(*stored.lock().unwrap()));
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_token_repository(Arc::new(mock_token_repo));
+        let token = encode_access_token("test_secret", Utc::now().timestamp() + 3600);
+        assert!(auth_service.verify_token(&token).await.is_ok());
+
+        auth_service.revoke_all_tokens(true).await.expect("Revoke all should succeed");
+
+        let result = auth_service.verify_token(&token).await;
+        assert!(result.is_err(), "Token issued before the epoch should be rejected");
+    }
+
+    #[tokio::test]
+    async fn test_revoke_all_tokens_without_epoch_keeps_access_tokens() {
+        let mut mock_token_repo = MockTokenRepo::new();
+        mock_token_repo.expect_revoke_all()
+            .times(1)
+            .returning(|| Ok(()));
+        mock_token_repo.expect_set_tokens_not_before().never();
+        mock_token_repo.expect_tokens_not_before()
+            .returning(|| Ok(None));
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_token_repository(Arc::new(mock_token_repo));
+        let token = encode_access_token("test_secret", Utc::now().timestamp() + 3600);
+
+        auth_service.revoke_all_tokens(false).await.expect("Revoke all should succeed");
+
+        assert!(auth_service.verify_token(&token).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_rejects_expired_token() {
+        let mut mock_reset_repo = MockResetTokenRepo::new();
+        let mut mock_user_repo = MockUserRepo::new();
+        let mut expired = PasswordResetToken::new(Uuid::new_v4(), "stale".to_string(), chrono::Duration::minutes(30));
+        expired.expires_at = Utc::now() - chrono::Duration::minutes(1);
+
+        mock_reset_repo.expect_find_by_token()
+            .with(eq("stale"))
+            .returning(move |_| Ok(Some(expired.clone())));
+        mock_reset_repo.expect_mark_used().never();
+        mock_user_repo.expect_find_by_id().never();
+        mock_user_repo.expect_update().never();
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_reset_token_repository(Arc::new(mock_reset_repo))
+            .with_user_repository(Arc::new(mock_user_repo));
+
+        let err = auth_service.reset_password("stale", "a-new-password").await.unwrap_err();
+        assert_eq!(err.downcast_ref::<InvalidResetToken>(), Some(&InvalidResetToken));
+    }
+
+    #[tokio::test]
+    async fn test_reset_password_loses_race_for_claimed_token() {
+        let mut mock_reset_repo = MockResetTokenRepo::new();
+        let mut mock_user_repo = MockUserRepo::new();
+        let user_id = Uuid::new_v4();
+        let user = session_user(user_id);
+        let token = PasswordResetToken::new(user_id, "contested".to_string(), chrono::Duration::minutes(30));
+
+        mock_reset_repo.expect_find_by_token()
+            .returning(move |_| Ok(Some(token.clone())));
+        mock_reset_repo.expect_mark_used()
+            .times(1)
+            .returning(|_| Ok(false));
+        mock_user_repo.expect_find_by_id()
+            .returning(move |_| Ok(Some(user.clone())));
+        mock_user_repo.expect_update().never();
+
+        let auth_service = AuthService::new("test_secret".to_string(), "test_refresh_secret".to_string(), "test_pepper".to_string())
+            .with_reset_token_repository(Arc::new(mock_reset_repo))
+            .with_user_repository(Arc::new(mock_user_repo));
+
+        let err = auth_service.reset_password("contested", "a-new-password").await.unwrap_err();
+        assert_eq!(err.downcast_ref::<InvalidResetToken>(), Some(&InvalidResetToken));
     }
 }
@@ -0,0 +1,103 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::model::transaction::BalanceAdjustment;
+use crate::repository::transaction::balance_adjustment_repo::BalanceAdjustmentRepository;
+use crate::service::transaction::balance_service::BalanceService;
+
+/// Reasons an adjustment is refused before anything is written
+#[derive(Debug, PartialEq)]
+pub enum AdjustmentError {
+    ZeroDelta,
+    MissingReason,
+    WouldGoNegative,
+}
+
+impl fmt::Display for AdjustmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdjustmentError::ZeroDelta => write!(f, "Adjustment must not be zero"),
+            AdjustmentError::MissingReason => write!(f, "Adjustment reason is required"),
+            AdjustmentError::WouldGoNegative => write!(
+                f,
+                "Adjustment would make the balance negative; set allow_negative to override"
+            ),
+        }
+    }
+}
+
+impl Error for AdjustmentError {}
+
+#[async_trait]
+pub trait BalanceAdjustmentService {
+    async fn adjust_balance(
+        &self,
+        user_id: Uuid,
+        admin_id: Uuid,
+        delta: i64,
+        reason: String,
+        allow_negative: bool,
+    ) -> Result<BalanceAdjustment, Box<dyn Error + Send + Sync>>;
+    async fn get_user_adjustments(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<BalanceAdjustment>, Box<dyn Error + Send + Sync>>;
+}
+
+pub struct DefaultBalanceAdjustmentService {
+    balance_service: Arc<dyn BalanceService + Send + Sync>,
+    adjustment_repository: Arc<dyn BalanceAdjustmentRepository + Send + Sync>,
+}
+
+impl DefaultBalanceAdjustmentService {
+    pub fn new(
+        balance_service: Arc<dyn BalanceService + Send + Sync>,
+        adjustment_repository: Arc<dyn BalanceAdjustmentRepository + Send + Sync>,
+    ) -> Self {
+        Self {
+            balance_service,
+            adjustment_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl BalanceAdjustmentService for DefaultBalanceAdjustmentService {
+    async fn adjust_balance(
+        &self,
+        user_id: Uuid,
+        admin_id: Uuid,
+        delta: i64,
+        reason: String,
+        allow_negative: bool,
+    ) -> Result<BalanceAdjustment, Box<dyn Error + Send + Sync>> {
+        if delta == 0 {
+            return Err(Box::new(AdjustmentError::ZeroDelta));
+        }
+        if reason.trim().is_empty() {
+            return Err(Box::new(AdjustmentError::MissingReason));
+        }
+
+        // Only makes sure the row exists; the repository applies the delta
+        // to whatever amount is stored when it writes
+        self.balance_service.get_or_create_balance(user_id).await?;
+        match self
+            .adjustment_repository
+            .apply(user_id, admin_id, delta, &reason, allow_negative)
+            .await?
+        {
+            Some(adjustment) => Ok(adjustment),
+            None => Err(Box::new(AdjustmentError::WouldGoNegative)),
+        }
+    }
+
+    async fn get_user_adjustments(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Vec<BalanceAdjustment>, Box<dyn Error + Send + Sync>> {
+        self.adjustment_repository.find_by_user(user_id).await
+    }
+}
@@ -55,9 +55,9 @@ impl BalanceService for DefaultBalanceService {
         match self.balance_repository.find_by_user_id(user_id).await? {
             Some(balance) => Ok(balance),
             None => {
-                let balance = Balance::new(user_id);
-                self.balance_repository.save(&balance).await?;
-                Ok(balance)
+                self.balance_repository
+                    .create_if_absent(&Balance::new(user_id))
+                    .await
             }
         }
     }
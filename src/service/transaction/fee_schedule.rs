@@ -0,0 +1,83 @@
+use std::collections::HashMap;
+
+use crate::model::transaction::BALANCE_PAYMENT_METHOD;
+
+/// Flat fee plus a percentage of the amount, in basis points (290 = 2.9%)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeRule {
+    pub flat: i64,
+    pub percent_bps: i64,
+}
+
+impl FeeRule {
+    pub fn fee_for(&self, amount: i64) -> i64 {
+        // Round the percentage part half up to the nearest whole unit
+        self.flat + (amount * self.percent_bps + 5_000) / 10_000
+    }
+}
+
+/// Processing fees keyed by payment method. Methods without a rule are free.
+#[derive(Debug, Clone, Default)]
+pub struct FeeSchedule {
+    rules: HashMap<String, FeeRule>,
+    balance_exempt: bool,
+}
+
+impl FeeSchedule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_rule(mut self, payment_method: &str, rule: FeeRule) -> Self {
+        self.rules.insert(payment_method.to_lowercase(), rule);
+        self
+    }
+
+    pub fn with_balance_exempt(mut self, exempt: bool) -> Self {
+        self.balance_exempt = exempt;
+        self
+    }
+
+    /// Parses `method=flat[+percent%]` entries separated by `;`,
+    /// e.g. `credit_card=2000+2.9%;bank_transfer=4000`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut schedule = Self::new();
+        for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (method, fee) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("fee entry '{}' must look like method=flat[+percent%]", entry))?;
+
+            let (flat, percent) = match fee.split_once('+') {
+                Some((flat, percent)) => (flat.trim(), Some(percent.trim())),
+                None => (fee.trim(), None),
+            };
+            let flat = flat
+                .parse::<i64>()
+                .map_err(|_| format!("flat fee for '{}' must be a whole number", method.trim()))?;
+            let percent_bps = match percent {
+                None => 0,
+                Some(p) => {
+                    let value = p
+                        .trim_end_matches('%')
+                        .parse::<f64>()
+                        .map_err(|_| format!("percentage fee for '{}' must be a number", method.trim()))?;
+                    (value * 100.0).round() as i64
+                }
+            };
+            if flat < 0 || percent_bps < 0 {
+                return Err(format!("fees for '{}' must not be negative", method.trim()));
+            }
+
+            schedule = schedule.with_rule(method.trim(), FeeRule { flat, percent_bps });
+        }
+        Ok(schedule)
+    }
+
+    pub fn fee_for(&self, payment_method: &str, amount: i64) -> i64 {
+        let method = payment_method.to_lowercase();
+        if self.balance_exempt && method == BALANCE_PAYMENT_METHOD {
+            return 0;
+        }
+        self.rules.get(&method).map_or(0, |rule| rule.fee_for(amount))
+    }
+}
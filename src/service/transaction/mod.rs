@@ -1,6 +1,10 @@
 pub mod transaction_service;
 pub mod balance_service;
 pub mod payment_service;
+pub mod payment_circuit_breaker;
+pub mod fee_schedule;
+pub mod payment_webhook_service;
+pub mod balance_adjustment_service;
 
 pub use transaction_service::{
     TransactionService,
@@ -21,4 +25,8 @@ pub mod tests {
     pub mod transaction_service_tests;
     pub mod balance_service_tests;
     pub mod payment_service_tests;
+    pub mod payment_circuit_breaker_tests;
+    pub mod fee_schedule_tests;
+    pub mod payment_webhook_service_tests;
+    pub mod balance_adjustment_service_tests;
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::model::transaction::Transaction;
+use crate::service::transaction::payment_service::PaymentService;
+
+/// Returned instead of calling the gateway while the circuit is open
+#[derive(Debug, PartialEq)]
+pub struct PaymentUnavailable;
+
+impl fmt::Display for PaymentUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Payment service temporarily unavailable")
+    }
+}
+
+impl Error for PaymentUnavailable {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerSettings {
+    /// Consecutive gateway errors that open the circuit
+    pub failure_threshold: u32,
+    /// Failures further apart than this start a new streak
+    pub failure_window: Duration,
+    /// How long the circuit stays open before a single trial call is let through
+    pub cooldown: Duration,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CircuitState {
+    Closed { failures: u32, streak_started: Option<Instant> },
+    Open { until: Instant },
+    /// A trial call went out at `since`; its outcome decides the next state
+    HalfOpen { since: Instant },
+}
+
+/// Wraps a `PaymentService` and fails fast once the gateway keeps erroring.
+/// Only `Err` results count as failures; a declined payment is a normal answer.
+pub struct CircuitBreakerPaymentService {
+    inner: Arc<dyn PaymentService + Send + Sync>,
+    settings: CircuitBreakerSettings,
+    state: Mutex<CircuitState>,
+}
+
+impl CircuitBreakerPaymentService {
+    pub fn new(inner: Arc<dyn PaymentService + Send + Sync>, settings: CircuitBreakerSettings) -> Self {
+        Self {
+            inner,
+            settings,
+            state: Mutex::new(CircuitState::Closed { failures: 0, streak_started: None }),
+        }
+    }
+
+    /// Decides whether a call may reach the gateway, moving an expired open
+    /// circuit to half-open for exactly one trial. A trial that has not
+    /// reported back within `cooldown` (e.g. its request was dropped) is
+    /// written off and another one is let through.
+    fn acquire(&self) -> Result<(), PaymentUnavailable> {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        match *state {
+            CircuitState::Closed { .. } => Ok(()),
+            CircuitState::Open { until } if now >= until => {
+                *state = CircuitState::HalfOpen { since: now };
+                Ok(())
+            }
+            CircuitState::HalfOpen { since } if now.duration_since(since) >= self.settings.cooldown => {
+                *state = CircuitState::HalfOpen { since: now };
+                Ok(())
+            }
+            CircuitState::Open { .. } | CircuitState::HalfOpen { .. } => Err(PaymentUnavailable),
+        }
+    }
+
+    fn record<T>(&self, result: &Result<T, Box<dyn Error + Send + Sync>>) {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+
+        if result.is_ok() {
+            *state = CircuitState::Closed { failures: 0, streak_started: None };
+            return;
+        }
+
+        *state = match *state {
+            CircuitState::Closed { failures, streak_started } => {
+                let (failures, streak_started) = match streak_started {
+                    Some(started) if now.duration_since(started) <= self.settings.failure_window => {
+                        (failures + 1, started)
+                    }
+                    _ => (1, now),
+                };
+                if failures >= self.settings.failure_threshold {
+                    CircuitState::Open { until: now + self.settings.cooldown }
+                } else {
+                    CircuitState::Closed { failures, streak_started: Some(streak_started) }
+                }
+            }
+            // The half-open trial failed, so wait out another cooldown
+            CircuitState::HalfOpen { .. } | CircuitState::Open { .. } => {
+                CircuitState::Open { until: now + self.settings.cooldown }
+            }
+        };
+    }
+}
+
+#[async_trait]
+impl PaymentService for CircuitBreakerPaymentService {
+    async fn process_payment(&self, transaction: &Transaction) -> Result<(bool, Option<String>), Box<dyn Error + Send + Sync>> {
+        self.acquire()?;
+        let result = self.inner.process_payment(transaction).await;
+        self.record(&result);
+        result
+    }
+
+    async fn refund_payment(&self, transaction: &Transaction) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.acquire()?;
+        let result = self.inner.refund_payment(transaction).await;
+        self.record(&result);
+        result
+    }
+}
@@ -7,6 +7,7 @@ use crate::model::transaction::Transaction;
 #[async_trait]
 pub trait PaymentService {
     async fn process_payment(&self, transaction: &Transaction) -> Result<(bool, Option<String>), Box<dyn Error + Send + Sync>>;
+    async fn refund_payment(&self, transaction: &Transaction) -> Result<(), Box<dyn Error + Send + Sync>>;
 }
 
 pub struct MockPaymentService;
@@ -20,7 +21,7 @@ impl MockPaymentService {
 #[async_trait]
 impl PaymentService for MockPaymentService {
     async fn process_payment(&self, transaction: &Transaction) -> Result<(bool, Option<String>), Box<dyn Error + Send + Sync>> {
-        let success = transaction.amount >= 0;
+        let success = transaction.total_charged() >= 0;
         let reference = if success {
             Some(format!("PG-REF-{}", Uuid::new_v4()))
         } else {
@@ -29,4 +30,11 @@ impl PaymentService for MockPaymentService {
         
         Ok((success, reference))
     }
+
+    async fn refund_payment(&self, transaction: &Transaction) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if transaction.external_reference.is_none() {
+            return Err("Transaction has no gateway reference to refund".into());
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,106 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+use std::error::Error;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::model::transaction::TransactionStatus;
+use crate::repository::transaction::payment_event_repo::PaymentEventRepository;
+use crate::repository::transaction::transaction_repo::TransactionRepository;
+
+pub const CAPTURED_EVENT: &str = "payment.captured";
+pub const FAILED_EVENT: &str = "payment.failed";
+
+/// Callback body sent by the payment provider
+#[derive(Debug, Deserialize)]
+pub struct PaymentWebhookEvent {
+    pub event_id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub transaction_id: Uuid,
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WebhookOutcome {
+    Applied,
+    Duplicate,
+    Ignored,
+}
+
+#[async_trait]
+pub trait PaymentWebhookService {
+    fn verify_signature(&self, payload: &[u8], signature_hex: &str) -> bool;
+    async fn handle_event(
+        &self,
+        event: PaymentWebhookEvent,
+    ) -> Result<WebhookOutcome, Box<dyn Error + Send + Sync>>;
+}
+
+pub struct DefaultPaymentWebhookService {
+    secret: String,
+    event_repository: Arc<dyn PaymentEventRepository + Send + Sync>,
+    transaction_repository: Arc<dyn TransactionRepository + Send + Sync>,
+}
+
+impl DefaultPaymentWebhookService {
+    pub fn new(
+        secret: String,
+        event_repository: Arc<dyn PaymentEventRepository + Send + Sync>,
+        transaction_repository: Arc<dyn TransactionRepository + Send + Sync>,
+    ) -> Self {
+        Self {
+            secret,
+            event_repository,
+            transaction_repository,
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentWebhookService for DefaultPaymentWebhookService {
+    /// Checks the hex-encoded HMAC-SHA256 of the raw body in constant time
+    fn verify_signature(&self, payload: &[u8], signature_hex: &str) -> bool {
+        let Ok(signature) = hex::decode(signature_hex.trim()) else {
+            return false;
+        };
+        let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(self.secret.as_bytes()) else {
+            return false;
+        };
+        mac.update(payload);
+        mac.verify_slice(&signature).is_ok()
+    }
+
+    async fn handle_event(
+        &self,
+        event: PaymentWebhookEvent,
+    ) -> Result<WebhookOutcome, Box<dyn Error + Send + Sync>> {
+        let status = match event.event_type.as_str() {
+            CAPTURED_EVENT => TransactionStatus::Success,
+            FAILED_EVENT => TransactionStatus::Failed,
+            _ => return Ok(WebhookOutcome::Ignored),
+        };
+
+        if self
+            .transaction_repository
+            .find_by_id(event.transaction_id)
+            .await?
+            .is_none()
+        {
+            return Ok(WebhookOutcome::Ignored);
+        }
+
+        // A finalized transaction is never moved again, whatever order callbacks arrive in
+        let claimed = self
+            .event_repository
+            .apply(&event.event_id, event.transaction_id, status, event.reference.as_deref())
+            .await?;
+        if !claimed {
+            return Ok(WebhookOutcome::Duplicate);
+        }
+
+        Ok(WebhookOutcome::Applied)
+    }
+}
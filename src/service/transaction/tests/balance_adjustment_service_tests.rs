@@ -0,0 +1,91 @@
+use crate::repository::transaction::balance_adjustment_repo::{
+    BalanceAdjustmentRepository, InMemoryBalanceAdjustmentRepository,
+};
+use crate::service::transaction::balance_adjustment_service::{
+    AdjustmentError, BalanceAdjustmentService, DefaultBalanceAdjustmentService,
+};
+use crate::service::transaction::tests::common::*;
+use crate::service::transaction::BalanceService;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (
+        DefaultBalanceAdjustmentService,
+        Arc<dyn BalanceService + Send + Sync>,
+        Arc<InMemoryBalanceAdjustmentRepository>,
+    ) {
+        let balances = Arc::new(MockBalanceRepository::new());
+        let balance_service: Arc<dyn BalanceService + Send + Sync> =
+            Arc::new(crate::service::transaction::DefaultBalanceService::new(balances.clone()));
+        let audit = Arc::new(InMemoryBalanceAdjustmentRepository::new(balances));
+        let service = DefaultBalanceAdjustmentService::new(balance_service.clone(), audit.clone());
+        (service, balance_service, audit)
+    }
+
+    #[test]
+    fn test_positive_adjustment_credits_balance() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service, audit) = setup();
+        let user_id = Uuid::new_v4();
+        let admin_id = Uuid::new_v4();
+        rt.block_on(balance_service.add_funds(user_id, 1000)).unwrap();
+
+        let adjustment = rt
+            .block_on(service.adjust_balance(user_id, admin_id, 500, "Goodwill credit".to_string(), false))
+            .unwrap();
+
+        assert_eq!(adjustment.resulting_balance, 1500);
+        assert_eq!(adjustment.admin_id, admin_id);
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, 1500);
+
+        let audit = rt.block_on(audit.find_by_user(user_id)).unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].delta, 500);
+        assert_eq!(audit[0].reason, "Goodwill credit");
+    }
+
+    #[test]
+    fn test_negative_adjustment_below_zero_rejected_without_flag() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service, audit) = setup();
+        let user_id = Uuid::new_v4();
+        rt.block_on(balance_service.add_funds(user_id, 300)).unwrap();
+
+        let result = rt.block_on(service.adjust_balance(
+            user_id,
+            Uuid::new_v4(),
+            -500,
+            "Error reversal".to_string(),
+            false,
+        ));
+
+        let err = result.unwrap_err();
+        assert_eq!(err.downcast_ref::<AdjustmentError>(), Some(&AdjustmentError::WouldGoNegative));
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, 300);
+        assert!(rt.block_on(audit.find_by_user(user_id)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_negative_adjustment_below_zero_allowed_with_flag() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service, audit) = setup();
+        let user_id = Uuid::new_v4();
+        rt.block_on(balance_service.add_funds(user_id, 300)).unwrap();
+
+        let adjustment = rt
+            .block_on(service.adjust_balance(user_id, Uuid::new_v4(), -500, "Chargeback".to_string(), true))
+            .unwrap();
+
+        assert_eq!(adjustment.resulting_balance, -200);
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, -200);
+        assert_eq!(rt.block_on(audit.find_by_user(user_id)).unwrap().len(), 1);
+    }
+}
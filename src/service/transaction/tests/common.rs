@@ -1,26 +1,52 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::error::Error;
 use uuid::Uuid;
 use chrono::Utc;
-use crate::model::transaction::{Transaction, TransactionStatus, Balance};
-use crate::repository::transaction::transaction_repo::TransactionRepository;
+use crate::model::transaction::{RefundReason, RefundReasonCategory, Transaction, TransactionStatus, TransactionStatusSnapshot, Balance};
+use crate::common::sort::SortOrder;
+use crate::repository::transaction::transaction_repo::{AlreadyRefunded, TransactionFilter, TransactionRepository, TransactionStats, sort_transactions};
 use crate::repository::transaction::balance_repo::BalanceRepository;
 use crate::service::transaction::balance_service::{BalanceService, DefaultBalanceService};
 use crate::service::transaction::payment_service::{PaymentService, MockPaymentService};
 use crate::service::transaction::transaction_service::DefaultTransactionService;
 use async_trait::async_trait;
+use tokio::sync::Barrier;
 
 pub struct MockTransactionRepository {
     transactions: Mutex<HashMap<Uuid, Transaction>>,
+    balances: Arc<MockBalanceRepository>,
+    read_gate: Mutex<Option<Arc<Barrier>>>,
+    fail_settles: AtomicBool,
 }
 
 impl MockTransactionRepository {
     pub fn new() -> Self {
+        Self::with_balances(Arc::new(MockBalanceRepository::new()))
+    }
+
+    /// Shares `balances` with the balance service so balance payments debit
+    /// the same store the test reads back
+    pub fn with_balances(balances: Arc<MockBalanceRepository>) -> Self {
         Self {
             transactions: Mutex::new(HashMap::new()),
+            balances,
+            read_gate: Mutex::new(None),
+            fail_settles: AtomicBool::new(false),
         }
     }
+
+    /// Makes the next `readers` lookups by id wait for each other, so
+    /// concurrent callers all act on the same snapshot
+    pub fn hold_reads(&self, readers: usize) {
+        *self.read_gate.lock().unwrap() = Some(Arc::new(Barrier::new(readers)));
+    }
+
+    /// Makes `settle_pending` fail, as a write lost after the gateway charged
+    pub fn fail_settles(&self) {
+        self.fail_settles.store(true, Ordering::SeqCst);
+    }
 }
 
 #[async_trait]
@@ -32,34 +58,169 @@ impl TransactionRepository for MockTransactionRepository {
         Ok(transaction_clone)
     }
 
+    async fn save_within_pending_cap(&self, transaction: &Transaction, max_pending: usize) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now();
+        let mut transactions = self.transactions.lock().unwrap();
+        let pending = transactions
+            .values()
+            .filter(|t| t.user_id == transaction.user_id && t.status == TransactionStatus::Pending && !t.is_expired(now))
+            .count();
+        if pending >= max_pending {
+            return Ok(None);
+        }
+
+        transactions.insert(transaction.id, transaction.clone());
+        Ok(Some(transaction.clone()))
+    }
+
     async fn find_by_id(&self, id: Uuid) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
-        let transactions = self.transactions.lock().unwrap();
-        Ok(transactions.get(&id).cloned())
+        let found = self.transactions.lock().unwrap().get(&id).cloned();
+        let gate = self.read_gate.lock().unwrap().clone();
+        if let Some(gate) = gate {
+            if gate.wait().await.is_leader() {
+                self.read_gate.lock().unwrap().take();
+            }
+        }
+        Ok(found)
     }
 
-    async fn find_by_user(&self, user_id: Uuid) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
+    async fn find_by_user(&self, user_id: Uuid, sort: &SortOrder) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
         let transactions = self.transactions.lock().unwrap();
-        let user_transactions: Vec<Transaction> = transactions
+        let mut user_transactions: Vec<Transaction> = transactions
             .values()
             .filter(|t| t.user_id == user_id)
             .cloned()
             .collect();
+        sort_transactions(&mut user_transactions, sort);
         Ok(user_transactions)
     }
 
+    async fn find_by_user_paged(&self, user_id: Uuid, filter: &TransactionFilter, sort: &SortOrder, limit: usize, offset: usize) -> Result<(Vec<Transaction>, usize), Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.lock().unwrap();
+        let mut matching: Vec<Transaction> = transactions
+            .values()
+            .filter(|t| t.user_id == user_id && filter.matches(t))
+            .cloned()
+            .collect();
+        sort_transactions(&mut matching, sort);
+        let total = matching.len();
+        Ok((matching.into_iter().skip(offset).take(limit).collect(), total))
+    }
+
+    async fn count_active_pending(&self, user_id: Uuid) -> Result<usize, Box<dyn Error + Send + Sync>> {
+        let now = Utc::now();
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions
+            .values()
+            .filter(|t| t.user_id == user_id && t.status == TransactionStatus::Pending && !t.is_expired(now))
+            .count())
+    }
+
+    async fn find_status(&self, id: Uuid) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions.get(&id).map(TransactionStatusSnapshot::from))
+    }
+
+    async fn find_by_external_reference(&self, reference: &str) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions
+            .values()
+            .find(|t| t.external_reference.as_deref() == Some(reference))
+            .cloned())
+    }
+
+    async fn find_by_ids(&self, ids: &[Uuid]) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(ids.iter().filter_map(|id| transactions.get(id).cloned()).collect())
+    }
+
     async fn update_status(&self, id: Uuid, status: TransactionStatus) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
         let mut transactions = self.transactions.lock().unwrap();
         
         match transactions.get_mut(&id) {
             Some(transaction) => {
                 transaction.status = status;
+                if status != TransactionStatus::Refunded {
+                    transaction.refund_reason = None;
+                }
+                transaction.updated_at = Utc::now();
+                Ok(transaction.clone())
+            },
+            None => Err("Transaction not found".into()),
+        }
+    }
+
+    async fn reopen_failed(&self, id: Uuid) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        let mut transactions = self.transactions.lock().unwrap();
+
+        match transactions.get_mut(&id) {
+            Some(transaction) if transaction.status == TransactionStatus::Failed => {
+                transaction.status = TransactionStatus::Pending;
+                transaction.updated_at = Utc::now();
+                Ok(Some(transaction.clone()))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    async fn settle_pending(&self, id: Uuid, status: TransactionStatus, reference: Option<&str>) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync>> {
+        if self.fail_settles.load(Ordering::SeqCst) {
+            return Err("Database unavailable".into());
+        }
+        let mut transactions = self.transactions.lock().unwrap();
+
+        match transactions.get_mut(&id) {
+            Some(transaction) if transaction.status == TransactionStatus::Pending => {
+                transaction.status = status;
+                if let Some(reference) = reference {
+                    transaction.external_reference = Some(reference.to_string());
+                }
+                transaction.updated_at = Utc::now();
+                Ok(Some(transaction.clone()))
+            },
+            _ => Ok(None),
+        }
+    }
+
+    async fn pay_from_balance(&self, id: Uuid) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        let mut transactions = self.transactions.lock().unwrap();
+        let mut balances = self.balances.balances.lock().unwrap();
+
+        match transactions.get_mut(&id) {
+            Some(transaction) if transaction.status == TransactionStatus::Pending => {
+                balances
+                    .get_mut(&transaction.user_id)
+                    .ok_or("Insufficient funds")?
+                    .withdraw(transaction.total_charged())?;
+                transaction.status = TransactionStatus::Success;
                 transaction.updated_at = Utc::now();
                 Ok(transaction.clone())
             },
+            Some(_) => Err("Transaction is already finalized".into()),
             None => Err("Transaction not found".into()),
         }
     }
 
+    async fn mark_refunded(&self, id: Uuid, reason: &RefundReason) -> Result<Transaction, Box<dyn Error + Send + Sync>> {
+        let mut transactions = self.transactions.lock().unwrap();
+
+        match transactions.get_mut(&id) {
+            Some(transaction) if transaction.status == TransactionStatus::Success => {
+                transaction.status = TransactionStatus::Refunded;
+                transaction.refund_reason = Some(reason.clone());
+                transaction.updated_at = Utc::now();
+                Ok(transaction.clone())
+            },
+            Some(_) => Err(Box::new(AlreadyRefunded)),
+            None => Err("Transaction not found".into()),
+        }
+    }
+
+    async fn stats(&self) -> Result<TransactionStats, Box<dyn Error + Send + Sync>> {
+        let transactions = self.transactions.lock().unwrap();
+        Ok(transactions.values().collect())
+    }
+
     async fn delete(&self, id: Uuid) -> Result<(), Box<dyn Error + Send + Sync>> {
         let mut transactions = self.transactions.lock().unwrap();
         if transactions.remove(&id).is_some() {
@@ -90,6 +251,11 @@ impl BalanceRepository for MockBalanceRepository {
         Ok(())
     }
 
+    async fn create_if_absent(&self, balance: &Balance) -> Result<Balance, Box<dyn Error + Send + Sync>> {
+        let mut balances = self.balances.lock().unwrap();
+        Ok(balances.entry(balance.user_id).or_insert_with(|| balance.clone()).clone())
+    }
+
     async fn find_by_user_id(&self, user_id: Uuid) -> Result<Option<Balance>, Box<dyn Error + Send + Sync>> {
         let balances = self.balances.lock().unwrap();
         Ok(balances.get(&user_id).cloned())
@@ -97,8 +263,8 @@ impl BalanceRepository for MockBalanceRepository {
 }
 
 pub fn create_transaction_service() -> DefaultTransactionService {
-    let transaction_repository = Arc::new(MockTransactionRepository::new());
     let balance_repository = Arc::new(MockBalanceRepository::new());
+    let transaction_repository = Arc::new(MockTransactionRepository::with_balances(balance_repository.clone()));
     let balance_service = Arc::new(DefaultBalanceService::new(balance_repository));
     let payment_service = Arc::new(MockPaymentService::new());
     
@@ -109,6 +275,116 @@ pub fn create_transaction_service() -> DefaultTransactionService {
     )
 }
 
+pub fn create_transaction_service_with_balance() -> (
+    DefaultTransactionService,
+    Arc<dyn BalanceService + Send + Sync>,
+) {
+    create_transaction_service_with_balance_repository(Arc::new(MockBalanceRepository::new()))
+}
+
+/// Like `create_transaction_service_with_balance`, over a balance store the
+/// caller can share with other services
+pub fn create_transaction_service_with_balance_repository(
+    balance_repository: Arc<MockBalanceRepository>,
+) -> (
+    DefaultTransactionService,
+    Arc<dyn BalanceService + Send + Sync>,
+) {
+    let transaction_repository = Arc::new(MockTransactionRepository::with_balances(balance_repository.clone()));
+    let balance_service: Arc<dyn BalanceService + Send + Sync> =
+        Arc::new(DefaultBalanceService::new(balance_repository));
+    let payment_service = Arc::new(MockPaymentService::new());
+
+    let service = DefaultTransactionService::new(
+        transaction_repository,
+        balance_service.clone(),
+        payment_service
+    );
+    (service, balance_service)
+}
+
+/// Gateway stand-in that remembers which transactions it was asked to refund
+pub struct RecordingPaymentService {
+    pub refunded: Mutex<Vec<Uuid>>,
+}
+
+impl RecordingPaymentService {
+    pub fn new() -> Self {
+        Self {
+            refunded: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentService for RecordingPaymentService {
+    async fn process_payment(&self, _transaction: &Transaction) -> Result<(bool, Option<String>), Box<dyn Error + Send + Sync>> {
+        Ok((true, Some(format!("PG-REF-{}", Uuid::new_v4()))))
+    }
+
+    async fn refund_payment(&self, transaction: &Transaction) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.refunded.lock().unwrap().push(transaction.id);
+        Ok(())
+    }
+}
+
+pub fn create_transaction_service_with_gateway() -> (
+    DefaultTransactionService,
+    Arc<dyn BalanceService + Send + Sync>,
+    Arc<RecordingPaymentService>,
+) {
+    let balance_repository = Arc::new(MockBalanceRepository::new());
+    let transaction_repository = Arc::new(MockTransactionRepository::with_balances(balance_repository.clone()));
+    let balance_service: Arc<dyn BalanceService + Send + Sync> =
+        Arc::new(DefaultBalanceService::new(balance_repository));
+    let payment_service = Arc::new(RecordingPaymentService::new());
+
+    let service = DefaultTransactionService::new(
+        transaction_repository,
+        balance_service.clone(),
+        payment_service.clone()
+    );
+    (service, balance_service, payment_service)
+}
+
+/// Gateway stand-in that approves or declines according to a script, then
+/// approves once the script runs out
+pub struct ScriptedPaymentService {
+    outcomes: Mutex<VecDeque<bool>>,
+}
+
+impl ScriptedPaymentService {
+    pub fn new(outcomes: Vec<bool>) -> Self {
+        Self {
+            outcomes: Mutex::new(outcomes.into()),
+        }
+    }
+}
+
+#[async_trait]
+impl PaymentService for ScriptedPaymentService {
+    async fn process_payment(&self, _transaction: &Transaction) -> Result<(bool, Option<String>), Box<dyn Error + Send + Sync>> {
+        let approved = self.outcomes.lock().unwrap().pop_front().unwrap_or(true);
+        Ok((approved, approved.then(|| format!("PG-REF-{}", Uuid::new_v4()))))
+    }
+
+    async fn refund_payment(&self, _transaction: &Transaction) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+pub fn create_transaction_service_with_outcomes(outcomes: Vec<bool>) -> DefaultTransactionService {
+    let balance_repository = Arc::new(MockBalanceRepository::new());
+    let transaction_repository = Arc::new(MockTransactionRepository::with_balances(balance_repository.clone()));
+    let balance_service = Arc::new(DefaultBalanceService::new(balance_repository));
+
+    DefaultTransactionService::new(
+        transaction_repository,
+        balance_service,
+        Arc::new(ScriptedPaymentService::new(outcomes))
+    )
+}
+
 pub fn create_balance_service() -> Arc<dyn BalanceService> {
     let balance_repository = Arc::new(MockBalanceRepository::new());
     Arc::new(DefaultBalanceService::new(balance_repository))
@@ -117,3 +393,10 @@ pub fn create_balance_service() -> Arc<dyn BalanceService> {
 pub fn create_payment_service() -> Arc<dyn PaymentService> {
     Arc::new(MockPaymentService::new())
 }
+
+pub fn customer_refund_reason() -> RefundReason {
+    RefundReason {
+        category: RefundReasonCategory::CustomerRequest,
+        note: None,
+    }
+}
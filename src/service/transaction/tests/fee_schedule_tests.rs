@@ -0,0 +1,85 @@
+use crate::service::transaction::fee_schedule::{FeeRule, FeeSchedule};
+use crate::service::transaction::tests::common::*;
+use crate::service::transaction::transaction_service::TransactionService;
+use uuid::Uuid;
+use tokio::runtime::Runtime;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card_and_balance_schedule() -> FeeSchedule {
+        FeeSchedule::new()
+            .with_rule("credit_card", FeeRule { flat: 2000, percent_bps: 290 })
+            .with_rule("balance", FeeRule { flat: 500, percent_bps: 0 })
+            .with_balance_exempt(true)
+    }
+
+    #[test]
+    fn test_credit_card_purchase_includes_fee() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service().with_fee_schedule(card_and_balance_schedule());
+
+        let transaction = rt.block_on(service.create_transaction(
+            Uuid::new_v4(),
+            None,
+            100_000,
+            "Concert ticket".to_string(),
+            "credit_card".to_string(),
+        )).unwrap();
+
+        assert_eq!(transaction.fee_amount, 2000 + 2900);
+        assert_eq!(transaction.total_charged(), 104_900);
+    }
+
+    #[test]
+    fn test_balance_purchase_is_fee_exempt() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service().with_fee_schedule(card_and_balance_schedule());
+
+        let transaction = rt.block_on(service.create_transaction(
+            Uuid::new_v4(),
+            None,
+            100_000,
+            "Concert ticket".to_string(),
+            "balance".to_string(),
+        )).unwrap();
+
+        assert_eq!(transaction.fee_amount, 0);
+        assert_eq!(transaction.total_charged(), 100_000);
+    }
+
+    #[test]
+    fn test_parse_fee_schedule() {
+        let schedule = FeeSchedule::parse("credit_card=2000+2.9%; bank_transfer=4000").unwrap();
+
+        assert_eq!(schedule.fee_for("credit_card", 10_000), 2290);
+        assert_eq!(schedule.fee_for("bank_transfer", 10_000), 4000);
+        assert_eq!(schedule.fee_for("e_wallet", 10_000), 0);
+    }
+
+    #[test]
+    fn test_parse_fee_schedule_rejects_malformed_entry() {
+        assert!(FeeSchedule::parse("credit_card").is_err());
+        assert!(FeeSchedule::parse("credit_card=abc").is_err());
+        assert!(FeeSchedule::parse("credit_card=100+x%").is_err());
+    }
+
+    #[test]
+    fn test_quoted_fee_matches_charged_fee() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service().with_fee_schedule(card_and_balance_schedule());
+
+        for method in ["credit_card", "balance", "e_wallet"] {
+            let quoted = service.quote_fee(75_000, method);
+            let transaction = rt.block_on(service.create_transaction(
+                Uuid::new_v4(),
+                None,
+                75_000,
+                "Quoted purchase".to_string(),
+                method.to_string(),
+            )).unwrap();
+            assert_eq!(transaction.fee_amount, quoted, "fee mismatch for {}", method);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use crate::model::transaction::Transaction;
+use crate::service::transaction::payment_circuit_breaker::{
+    CircuitBreakerPaymentService, CircuitBreakerSettings, PaymentUnavailable,
+};
+use crate::service::transaction::payment_service::PaymentService;
+use async_trait::async_trait;
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+/// Gateway that errors until `failing` is cleared, and never answers while
+/// `hanging` is set
+pub struct FlakyPaymentService {
+    pub failing: AtomicBool,
+    pub hanging: AtomicBool,
+    pub calls: AtomicUsize,
+}
+
+#[async_trait]
+impl PaymentService for FlakyPaymentService {
+    async fn process_payment(&self, _transaction: &Transaction) -> Result<(bool, Option<String>), Box<dyn Error + Send + Sync>> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        if self.hanging.load(Ordering::SeqCst) {
+            std::future::pending::<()>().await;
+        }
+        if self.failing.load(Ordering::SeqCst) {
+            return Err("Gateway timed out".into());
+        }
+        Ok((true, Some(format!("PG-REF-{}", Uuid::new_v4()))))
+    }
+
+    async fn refund_payment(&self, _transaction: &Transaction) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker(gateway: Arc<FlakyPaymentService>, cooldown: Duration) -> CircuitBreakerPaymentService {
+        CircuitBreakerPaymentService::new(
+            gateway,
+            CircuitBreakerSettings {
+                failure_threshold: 3,
+                failure_window: Duration::from_secs(60),
+                cooldown,
+            },
+        )
+    }
+
+    fn failing_gateway() -> Arc<FlakyPaymentService> {
+        Arc::new(FlakyPaymentService {
+            failing: AtomicBool::new(true),
+            hanging: AtomicBool::new(false),
+            calls: AtomicUsize::new(0),
+        })
+    }
+
+    fn transaction() -> Transaction {
+        Transaction::new(Uuid::new_v4(), None, 1000, "Ticket".to_string(), "credit_card".to_string())
+    }
+
+    #[test]
+    fn test_breaker_opens_after_failure_threshold() {
+        let rt = Runtime::new().unwrap();
+        let gateway = failing_gateway();
+        let breaker = breaker(gateway.clone(), Duration::from_secs(60));
+
+        for _ in 0..3 {
+            let err = rt.block_on(breaker.process_payment(&transaction())).unwrap_err();
+            assert!(err.downcast_ref::<PaymentUnavailable>().is_none());
+        }
+
+        let err = rt.block_on(breaker.process_payment(&transaction())).unwrap_err();
+        assert!(err.downcast_ref::<PaymentUnavailable>().is_some());
+        assert_eq!(err.to_string(), "Payment service temporarily unavailable");
+        assert_eq!(gateway.calls.load(Ordering::SeqCst), 3, "Open circuit must not reach the gateway");
+    }
+
+    #[test]
+    fn test_breaker_recovers_after_successful_half_open_call() {
+        let rt = Runtime::new().unwrap();
+        let gateway = failing_gateway();
+        let breaker = breaker(gateway.clone(), Duration::from_millis(20));
+
+        for _ in 0..3 {
+            let _ = rt.block_on(breaker.process_payment(&transaction()));
+        }
+        let err = rt.block_on(breaker.process_payment(&transaction())).unwrap_err();
+        assert!(err.downcast_ref::<PaymentUnavailable>().is_some());
+
+        std::thread::sleep(Duration::from_millis(30));
+        gateway.failing.store(false, Ordering::SeqCst);
+
+        let (success, _) = rt.block_on(breaker.process_payment(&transaction())).unwrap();
+        assert!(success);
+
+        // Closed again: every call reaches the gateway
+        assert!(rt.block_on(breaker.process_payment(&transaction())).is_ok());
+        assert_eq!(gateway.calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[test]
+    fn test_failed_half_open_call_reopens_circuit() {
+        let rt = Runtime::new().unwrap();
+        let gateway = failing_gateway();
+        let breaker = breaker(gateway.clone(), Duration::from_millis(20));
+
+        for _ in 0..3 {
+            let _ = rt.block_on(breaker.process_payment(&transaction()));
+        }
+        std::thread::sleep(Duration::from_millis(30));
+
+        let err = rt.block_on(breaker.process_payment(&transaction())).unwrap_err();
+        assert!(err.downcast_ref::<PaymentUnavailable>().is_none(), "Trial call should reach the gateway");
+        let err = rt.block_on(breaker.process_payment(&transaction())).unwrap_err();
+        assert!(err.downcast_ref::<PaymentUnavailable>().is_some());
+        assert_eq!(gateway.calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_abandoned_half_open_trial_expires_after_cooldown() {
+        let rt = Runtime::new().unwrap();
+        let gateway = failing_gateway();
+        let breaker = breaker(gateway.clone(), Duration::from_millis(20));
+
+        for _ in 0..3 {
+            let _ = rt.block_on(breaker.process_payment(&transaction()));
+        }
+        std::thread::sleep(Duration::from_millis(30));
+
+        // The trial call never comes back, so it never records an outcome
+        gateway.hanging.store(true, Ordering::SeqCst);
+        let abandoned = rt.block_on(async {
+            tokio::time::timeout(Duration::from_millis(5), breaker.process_payment(&transaction())).await
+        });
+        assert!(abandoned.is_err());
+        let err = rt.block_on(breaker.process_payment(&transaction())).unwrap_err();
+        assert!(err.downcast_ref::<PaymentUnavailable>().is_some(), "Only one trial at a time");
+
+        std::thread::sleep(Duration::from_millis(30));
+        gateway.hanging.store(false, Ordering::SeqCst);
+        gateway.failing.store(false, Ordering::SeqCst);
+
+        assert!(rt.block_on(breaker.process_payment(&transaction())).is_ok());
+        assert!(rt.block_on(breaker.process_payment(&transaction())).is_ok());
+        assert_eq!(gateway.calls.load(Ordering::SeqCst), 6);
+    }
+}
@@ -0,0 +1,127 @@
+use crate::model::transaction::{Transaction, TransactionStatus};
+use crate::repository::transaction::payment_event_repo::InMemoryPaymentEventRepository;
+use crate::repository::transaction::transaction_repo::TransactionRepository;
+use crate::service::transaction::payment_webhook_service::{
+    DefaultPaymentWebhookService, PaymentWebhookEvent, PaymentWebhookService, WebhookOutcome,
+    CAPTURED_EVENT,
+};
+use crate::service::transaction::tests::common::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &str = "webhook_test_secret";
+
+    fn setup() -> (DefaultPaymentWebhookService, Arc<MockTransactionRepository>) {
+        let transaction_repository = Arc::new(MockTransactionRepository::new());
+        let service = DefaultPaymentWebhookService::new(
+            SECRET.to_string(),
+            Arc::new(InMemoryPaymentEventRepository::new(transaction_repository.clone())),
+            transaction_repository.clone(),
+        );
+        (service, transaction_repository)
+    }
+
+    fn pending_transaction(rt: &Runtime, repo: &MockTransactionRepository) -> Transaction {
+        let transaction = Transaction::new(
+            Uuid::new_v4(),
+            None,
+            5000,
+            "Gateway purchase".to_string(),
+            "credit_card".to_string(),
+        );
+        rt.block_on(repo.save(&transaction)).unwrap()
+    }
+
+    fn capture_event(event_id: &str, transaction_id: Uuid) -> PaymentWebhookEvent {
+        PaymentWebhookEvent {
+            event_id: event_id.to_string(),
+            event_type: CAPTURED_EVENT.to_string(),
+            transaction_id,
+            reference: Some("PG-CAPTURE-1".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_capture_marks_transaction_success() {
+        let rt = Runtime::new().unwrap();
+        let (service, repo) = setup();
+        let transaction = pending_transaction(&rt, &repo);
+
+        let outcome = rt.block_on(service.handle_event(capture_event("evt_1", transaction.id))).unwrap();
+        assert_eq!(outcome, WebhookOutcome::Applied);
+
+        let stored = rt.block_on(repo.find_by_id(transaction.id)).unwrap().unwrap();
+        assert_eq!(stored.status, TransactionStatus::Success);
+        assert_eq!(stored.external_reference, Some("PG-CAPTURE-1".to_string()));
+    }
+
+    #[test]
+    fn test_duplicate_delivery_is_noop() {
+        let rt = Runtime::new().unwrap();
+        let (service, repo) = setup();
+        let transaction = pending_transaction(&rt, &repo);
+
+        rt.block_on(service.handle_event(capture_event("evt_dup", transaction.id))).unwrap();
+        let first = rt.block_on(repo.find_by_id(transaction.id)).unwrap().unwrap();
+
+        let outcome = rt.block_on(service.handle_event(capture_event("evt_dup", transaction.id))).unwrap();
+        assert_eq!(outcome, WebhookOutcome::Duplicate);
+
+        let second = rt.block_on(repo.find_by_id(transaction.id)).unwrap().unwrap();
+        assert_eq!(first.updated_at, second.updated_at);
+    }
+
+    #[test]
+    fn test_concurrent_deliveries_apply_once() {
+        let rt = Runtime::new().unwrap();
+        let (service, repo) = setup();
+        let transaction = pending_transaction(&rt, &repo);
+
+        let (first, second) = rt.block_on(async {
+            tokio::join!(
+                service.handle_event(capture_event("evt_race", transaction.id)),
+                service.handle_event(capture_event("evt_race", transaction.id)),
+            )
+        });
+        let mut outcomes = vec![first.unwrap(), second.unwrap()];
+        outcomes.sort_by_key(|outcome| outcome == &WebhookOutcome::Duplicate);
+        assert_eq!(outcomes, vec![WebhookOutcome::Applied, WebhookOutcome::Duplicate]);
+    }
+
+    #[test]
+    fn test_unknown_event_type_is_ignored() {
+        let rt = Runtime::new().unwrap();
+        let (service, repo) = setup();
+        let transaction = pending_transaction(&rt, &repo);
+
+        let mut event = capture_event("evt_unknown", transaction.id);
+        event.event_type = "payment.disputed".to_string();
+
+        let outcome = rt.block_on(service.handle_event(event)).unwrap();
+        assert_eq!(outcome, WebhookOutcome::Ignored);
+
+        let stored = rt.block_on(repo.find_by_id(transaction.id)).unwrap().unwrap();
+        assert_eq!(stored.status, TransactionStatus::Pending);
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let (service, _) = setup();
+        let payload = br#"{"event_id":"evt_sig"}"#;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET.as_bytes()).unwrap();
+        mac.update(payload);
+        let signature = hex::encode(mac.finalize().into_bytes());
+
+        assert!(service.verify_signature(payload, &signature));
+        assert!(!service.verify_signature(b"tampered", &signature));
+        assert!(!service.verify_signature(payload, "not-hex"));
+    }
+}
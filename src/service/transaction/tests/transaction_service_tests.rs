@@ -1,7 +1,18 @@
 use crate::service::transaction::tests::common::*;
+use crate::common::pagination::Pagination;
+use crate::common::sort::SortOrder;
+use crate::repository::transaction::transaction_repo::{AlreadyRefunded, TransactionFilter};
+use crate::service::transaction::balance_service::{BalanceService, DefaultBalanceService};
+use std::sync::Arc;
 use uuid::Uuid;
-use crate::model::transaction::TransactionStatus;
-use crate::service::transaction::transaction_service::TransactionService;
+use chrono::{Duration, Utc};
+use crate::model::transaction::{
+    BALANCE_PAYMENT_METHOD, RefundPolicy, RefundReason, RefundReasonCategory, TOPUP_DESCRIPTION, Transaction,
+    TransactionCategory, TransactionStatus,
+};
+use crate::service::transaction::transaction_service::{
+    DefaultTransactionService, PaymentNotRetryable, TooManyPendingCheckouts, TransactionService,
+};
 use tokio::runtime::Runtime;
 
 #[cfg(test)]
@@ -32,8 +43,16 @@ mod tests {
         assert_eq!(transaction.ticket_id, ticket_id);
         assert_eq!(transaction.amount, amount);
         assert_eq!(transaction.description, description);
-        assert_eq!(transaction.payment_method, payment_method);
+        assert_eq!(transaction.payment_method, "credit card");
         assert_eq!(transaction.status, TransactionStatus::Pending);
+    }
+
+    #[test]
+    fn test_payment_methods_are_configurable_and_normalized() {
+        let service = create_transaction_service()
+            .with_payment_methods(vec!["PayPal ".to_string(), "credit_card".to_string()]);
+
+        assert_eq!(service.payment_methods(), ["paypal".to_string(), "credit_card".to_string()]);
     }    
     
     #[test]
@@ -181,13 +200,195 @@ mod tests {
         )).unwrap();
         rt.block_on(service.process_payment(transaction.id, None)).unwrap();
 
-        let result = rt.block_on(service.refund_transaction(transaction.id));
+        let result = rt.block_on(service.refund_transaction(transaction.id, customer_refund_reason(), None));
         
         assert!(result.is_ok());
         let refunded = result.unwrap();
         assert_eq!(refunded.status, TransactionStatus::Refunded);
     }    
+
+    #[test]
+    fn test_refund_records_reason() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+
+        let transaction = rt.block_on(service.create_transaction(
+            Uuid::new_v4(),
+            Some(Uuid::new_v4()),
+            1000,
+            "Ticket purchase".to_string(),
+            "credit_card".to_string(),
+        )).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        let reason = RefundReason {
+            category: RefundReasonCategory::EventCancelled,
+            note: Some("Venue closed".to_string()),
+        };
+        let refunded = rt.block_on(service.refund_transaction(transaction.id, reason.clone(), None)).unwrap();
+        assert_eq!(refunded.refund_reason.as_ref(), Some(&reason));
+
+        let stored = rt.block_on(service.get_transaction(transaction.id)).unwrap().unwrap();
+        assert_eq!(stored.refund_reason, Some(reason));
+    }
+    
+    #[test]
+    fn test_refund_credits_original_payer() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service) = create_transaction_service_with_balance();
+        let buyer_id = Uuid::new_v4();
+        let current_holder_id = Uuid::new_v4();
+        rt.block_on(balance_service.add_funds(buyer_id, 1500)).unwrap();
+        
+        let transaction = rt.block_on(service.create_transaction(
+            buyer_id,
+            Some(Uuid::new_v4()),
+            1500,
+            "Ticket purchase".to_string(),
+            "balance".to_string(),
+        )).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        // The ticket now belongs to someone else, but the refund must still go to the buyer
+        rt.block_on(balance_service.get_or_create_balance(current_holder_id)).unwrap();
+
+        let refunded = rt.block_on(service.refund_transaction(transaction.id, customer_refund_reason(), None)).unwrap();
+        assert_eq!(refunded.user_id, buyer_id);
+
+        let buyer_balance = rt.block_on(balance_service.get_or_create_balance(buyer_id)).unwrap();
+        let holder_balance = rt.block_on(balance_service.get_or_create_balance(current_holder_id)).unwrap();
+        assert_eq!(buyer_balance.amount, 1500);
+        assert_eq!(holder_balance.amount, 0);
+    }    
     
+    #[test]
+    fn test_balance_funded_refund_restores_balance() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service, gateway) = create_transaction_service_with_gateway();
+        let user_id = Uuid::new_v4();
+        rt.block_on(balance_service.add_funds(user_id, 2000)).unwrap();
+
+        let transaction = rt.block_on(service.create_transaction(
+            user_id,
+            Some(Uuid::new_v4()),
+            1500,
+            "Ticket purchase".to_string(),
+            "balance".to_string(),
+        )).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, 500);
+
+        let refunded = rt.block_on(service.refund_transaction(transaction.id, customer_refund_reason(), None)).unwrap();
+        assert_eq!(refunded.status, TransactionStatus::Refunded);
+
+        // Refunding again must not credit the balance a second time
+        assert!(rt.block_on(service.refund_transaction(transaction.id, customer_refund_reason(), None)).is_err());
+
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, 2000);
+        assert!(gateway.refunded.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_refunds_credit_once() {
+        let rt = Runtime::new().unwrap();
+        let balances = Arc::new(MockBalanceRepository::new());
+        let repository = Arc::new(MockTransactionRepository::with_balances(balances.clone()));
+        let balance_service: Arc<dyn BalanceService + Send + Sync> =
+            Arc::new(DefaultBalanceService::new(balances));
+        let service = DefaultTransactionService::new(
+            repository.clone(),
+            balance_service.clone(),
+            Arc::new(RecordingPaymentService::new()),
+        );
+        let user_id = Uuid::new_v4();
+        rt.block_on(balance_service.add_funds(user_id, 2000)).unwrap();
+
+        let transaction = rt.block_on(service.create_transaction(
+            user_id,
+            Some(Uuid::new_v4()),
+            1500,
+            "Ticket purchase".to_string(),
+            "balance".to_string(),
+        )).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        // Both requests read the transaction as Success before either writes
+        repository.hold_reads(2);
+        let (first, second) = rt.block_on(async {
+            tokio::join!(
+                service.refund_transaction(transaction.id, customer_refund_reason(), None),
+                service.refund_transaction(transaction.id, customer_refund_reason(), None),
+            )
+        });
+
+        let (won, lost) = if first.is_ok() { (first, second) } else { (second, first) };
+        assert!(won.is_ok());
+        assert!(lost.unwrap_err().downcast_ref::<AlreadyRefunded>().is_some());
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, 2000);
+    }
+
+    #[test]
+    fn test_gateway_refund_goes_through_gateway() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service, gateway) = create_transaction_service_with_gateway();
+        let user_id = Uuid::new_v4();
+
+        let transaction = rt.block_on(service.create_transaction(
+            user_id,
+            Some(Uuid::new_v4()),
+            1500,
+            "Ticket purchase".to_string(),
+            "credit_card".to_string(),
+        )).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        rt.block_on(service.refund_transaction(transaction.id, customer_refund_reason(), None)).unwrap();
+        assert!(rt.block_on(service.refund_transaction(transaction.id, customer_refund_reason(), None)).is_err());
+
+        assert_eq!(*gateway.refunded.lock().unwrap(), vec![transaction.id]);
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, 0);
+    }
+
+    #[test]
+    fn test_balance_refund_policy_credits_gateway_payment_to_balance() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service, gateway) = create_transaction_service_with_gateway();
+        let service = service.with_refund_policy(RefundPolicy::Balance);
+        let user_id = Uuid::new_v4();
+
+        let transaction = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+        rt.block_on(service.refund_transaction(transaction.id, customer_refund_reason(), None)).unwrap();
+
+        assert!(gateway.refunded.lock().unwrap().is_empty());
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, transaction.amount);
+    }
+
+    #[test]
+    fn test_refund_override_beats_configured_policy() {
+        let rt = Runtime::new().unwrap();
+        let (service, balance_service, gateway) = create_transaction_service_with_gateway();
+        let service = service.with_refund_policy(RefundPolicy::Balance);
+        let user_id = Uuid::new_v4();
+
+        let transaction = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+        rt.block_on(service.refund_transaction(
+            transaction.id,
+            customer_refund_reason(),
+            Some(RefundPolicy::Original),
+        )).unwrap();
+
+        assert_eq!(*gateway.refunded.lock().unwrap(), vec![transaction.id]);
+        let balance = rt.block_on(balance_service.get_or_create_balance(user_id)).unwrap();
+        assert_eq!(balance.amount, 0);
+    }
+
     #[test]
     fn test_get_transaction_found() {
         let rt = Runtime::new().unwrap();
@@ -244,7 +445,7 @@ mod tests {
             "Credit Card".to_string(),
         )).unwrap();
 
-        let result = rt.block_on(service.get_user_transactions(user_id));
+        let result = rt.block_on(service.get_user_transactions(user_id, &SortOrder::descending("created_at")));
         
         assert!(result.is_ok());
         let transactions = result.unwrap();
@@ -253,6 +454,167 @@ mod tests {
         assert!(transactions.iter().any(|t| t.id == transaction2.id));
     }    
     
+    #[test]
+    fn test_get_user_transactions_sorted_by_amount() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+        let user_id = Uuid::new_v4();
+
+        for amount in [2000, 500, 1000] {
+            rt.block_on(service.create_transaction(
+                user_id,
+                None,
+                amount,
+                format!("Transaction {}", amount),
+                "credit_card".to_string(),
+            )).unwrap();
+        }
+
+        let ascending = rt
+            .block_on(service.get_user_transactions(user_id, &SortOrder::ascending("amount")))
+            .unwrap();
+        let amounts: Vec<i64> = ascending.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![500, 1000, 2000]);
+
+        let descending = rt
+            .block_on(service.get_user_transactions(user_id, &SortOrder::descending("amount")))
+            .unwrap();
+        let amounts: Vec<i64> = descending.iter().map(|t| t.amount).collect();
+        assert_eq!(amounts, vec![2000, 1000, 500]);
+    }
+
+    #[test]
+    fn test_get_user_transactions_paged_by_status_and_date() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service_with_outcomes(vec![false, true]);
+        let user_id = Uuid::new_v4();
+
+        let declined = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(declined.id, None)).unwrap();
+        let paid = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(paid.id, None)).unwrap();
+        checkout(&rt, &service, Uuid::new_v4()).unwrap();
+
+        let pagination = Pagination::parse(None, None).unwrap();
+        let failed = rt
+            .block_on(service.get_user_transactions_paged(
+                user_id,
+                &TransactionFilter { status: Some(TransactionStatus::Failed), ..Default::default() },
+                &SortOrder::descending("created_at"),
+                &pagination,
+            ))
+            .unwrap()
+            .items;
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].id, declined.id);
+
+        let window = TransactionFilter {
+            status: None,
+            category: None,
+            from_date: Some(declined.created_at),
+            to_date: Some(declined.created_at),
+        };
+        let in_window = rt
+            .block_on(service.get_user_transactions_paged(user_id, &window, &SortOrder::descending("created_at"), &pagination))
+            .unwrap()
+            .items;
+        assert!(in_window.iter().any(|t| t.id == declined.id));
+        assert!(in_window.iter().all(|t| t.created_at == declined.created_at));
+
+        let future = TransactionFilter { from_date: Some(Utc::now() + Duration::days(1)), ..Default::default() };
+        let none = rt
+            .block_on(service.get_user_transactions_paged(user_id, &future, &SortOrder::descending("created_at"), &pagination))
+            .unwrap()
+            .items;
+        assert!(none.is_empty());
+    }
+
+    #[test]
+    fn test_get_user_transactions_paged_reports_total() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+        let user_id = Uuid::new_v4();
+        for _ in 0..3 {
+            checkout(&rt, &service, user_id).unwrap();
+        }
+        rt.block_on(service.add_funds_to_balance(user_id, 500, "bank_transfer".to_string())).unwrap();
+
+        let pagination = Pagination::parse(Some(2), Some(2)).unwrap();
+        let page = rt
+            .block_on(service.get_user_transactions_paged(
+                user_id,
+                &TransactionFilter { category: Some(TransactionCategory::Ticket), ..Default::default() },
+                &SortOrder::descending("created_at"),
+                &pagination,
+            ))
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.total, 3);
+        assert!(page.items.iter().all(|t| t.category == TransactionCategory::Ticket));
+    }
+
+    #[test]
+    fn test_payment_link_processed_before_expiry() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+
+        let transaction = rt.block_on(service.create_payment_link(
+            Uuid::new_v4(),
+            None,
+            1000,
+            "Payment link".to_string(),
+            "credit_card".to_string(),
+            Utc::now() + Duration::minutes(15),
+        )).unwrap();
+        assert!(transaction.expires_at.is_some());
+
+        let processed = rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+        assert_eq!(processed.status, TransactionStatus::Success);
+    }
+
+    #[test]
+    fn test_payment_link_rejected_after_expiry() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+
+        let transaction = rt.block_on(service.create_payment_link(
+            Uuid::new_v4(),
+            None,
+            1000,
+            "Payment link".to_string(),
+            "credit_card".to_string(),
+            Utc::now() + Duration::milliseconds(20),
+        )).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(30));
+
+        let err = rt.block_on(service.process_payment(transaction.id, None)).unwrap_err();
+        assert_eq!(err.to_string(), "Payment window expired");
+
+        let stored = rt.block_on(service.get_transaction(transaction.id)).unwrap().unwrap();
+        assert_eq!(stored.status, TransactionStatus::Failed);
+    }
+
+    #[test]
+    fn test_get_transaction_by_reference() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+
+        let transaction = rt.block_on(service.create_transaction(
+            Uuid::new_v4(),
+            None,
+            1000,
+            "Reconciled purchase".to_string(),
+            "credit_card".to_string(),
+        )).unwrap();
+        rt.block_on(service.process_payment(transaction.id, Some("PG-RECON-42".to_string()))).unwrap();
+
+        let found = rt.block_on(service.get_transaction_by_reference("PG-RECON-42")).unwrap();
+        assert_eq!(found.map(|t| t.id), Some(transaction.id));
+
+        let missing = rt.block_on(service.get_transaction_by_reference("PG-UNKNOWN")).unwrap();
+        assert!(missing.is_none());
+    }
+
     #[test]
     fn test_delete_transaction_success() {
         let rt = Runtime::new().unwrap();
@@ -287,4 +649,241 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().to_string(), "Transaction not found");
     }
+
+    fn checkout(
+        rt: &Runtime,
+        service: &DefaultTransactionService,
+        user_id: Uuid,
+    ) -> Result<Transaction, Box<dyn std::error::Error + Send + Sync>> {
+        rt.block_on(service.create_transaction(
+            user_id,
+            Some(Uuid::new_v4()),
+            1000,
+            "Ticket checkout".to_string(),
+            "credit_card".to_string(),
+        ))
+    }
+
+    #[test]
+    fn test_checkout_blocked_at_cap_until_one_is_paid() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service().with_max_concurrent_checkouts(2);
+        let user_id = Uuid::new_v4();
+
+        let first = checkout(&rt, &service, user_id).unwrap();
+        checkout(&rt, &service, user_id).unwrap();
+
+        let err = checkout(&rt, &service, user_id).unwrap_err();
+        assert_eq!(err.to_string(), "Too many pending checkouts");
+        assert!(err.downcast_ref::<TooManyPendingCheckouts>().is_some());
+
+        // Another user's checkouts are counted separately
+        assert!(checkout(&rt, &service, Uuid::new_v4()).is_ok());
+
+        rt.block_on(service.process_payment(first.id, None)).unwrap();
+        assert!(checkout(&rt, &service, user_id).is_ok());
+    }
+
+    #[test]
+    fn test_checkout_allowed_again_after_one_is_released() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service().with_max_concurrent_checkouts(1);
+        let user_id = Uuid::new_v4();
+
+        let held = checkout(&rt, &service, user_id).unwrap();
+        assert!(checkout(&rt, &service, user_id).is_err());
+
+        rt.block_on(service.delete_transaction(held.id)).unwrap();
+        assert!(checkout(&rt, &service, user_id).is_ok());
+    }
+
+    #[test]
+    fn test_concurrent_checkouts_respect_cap() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service().with_max_concurrent_checkouts(1);
+        let user_id = Uuid::new_v4();
+        let attempt = || service.create_transaction(user_id, None, 1000, "Ticket checkout".to_string(), "credit_card".to_string());
+
+        let (first, second) = rt.block_on(async { tokio::join!(attempt(), attempt()) });
+
+        assert!(first.is_ok() != second.is_ok(), "Exactly one checkout should fit under the cap");
+    }
+
+    #[test]
+    fn test_expired_payment_link_does_not_count_toward_cap() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service().with_max_concurrent_checkouts(1);
+        let user_id = Uuid::new_v4();
+
+        rt.block_on(service.create_payment_link(
+            user_id,
+            None,
+            1000,
+            "Payment link".to_string(),
+            "credit_card".to_string(),
+            Utc::now() + Duration::milliseconds(20),
+        )).unwrap();
+        assert!(checkout(&rt, &service, user_id).is_err());
+
+        std::thread::sleep(std::time::Duration::from_millis(30));
+        assert!(checkout(&rt, &service, user_id).is_ok());
+    }
+
+    #[test]
+    fn test_flows_assign_transaction_category() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+        let user_id = Uuid::new_v4();
+
+        let purchase = checkout(&rt, &service, user_id).unwrap();
+        assert_eq!(purchase.category, TransactionCategory::Ticket);
+
+        rt.block_on(service.add_funds_to_balance(user_id, 3000, "bank_transfer".to_string())).unwrap();
+        rt.block_on(service.withdraw_funds(user_id, 1000, "Cash out".to_string())).unwrap();
+
+        let history = rt
+            .block_on(service.get_user_transactions(user_id, &SortOrder::ascending("created_at")))
+            .unwrap();
+        let categories: Vec<_> = history.iter().map(|t| t.category).collect();
+        assert_eq!(
+            categories,
+            vec![TransactionCategory::Ticket, TransactionCategory::Topup, TransactionCategory::Withdrawal]
+        );
+
+        let topup = &history[1];
+        assert_eq!(topup.amount, 3000);
+        assert_eq!(topup.status, TransactionStatus::Success);
+    }
+
+    #[test]
+    fn test_topup_record_cannot_be_refunded() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+        let user_id = Uuid::new_v4();
+
+        rt.block_on(service.add_funds_to_balance(user_id, 3000, "bank_transfer".to_string())).unwrap();
+        let topup = rt
+            .block_on(service.get_user_transactions(user_id, &SortOrder::descending("created_at")))
+            .unwrap()
+            .remove(0);
+
+        let err = rt.block_on(service.refund_transaction(topup.id, customer_refund_reason(), None)).unwrap_err();
+        assert_eq!(err.to_string(), "Topup transactions cannot be refunded");
+    }
+
+    #[test]
+    fn test_retry_payment_succeeds_once_gateway_accepts() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service_with_outcomes(vec![false, true]);
+        let user_id = Uuid::new_v4();
+
+        let transaction = checkout(&rt, &service, user_id).unwrap();
+        let declined = rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+        assert_eq!(declined.status, TransactionStatus::Failed);
+
+        let retried = rt.block_on(service.retry_payment(transaction.id)).unwrap();
+        assert_eq!(retried.id, transaction.id);
+        assert_eq!(retried.status, TransactionStatus::Success);
+        assert!(retried.external_reference.is_some());
+    }
+
+    #[test]
+    fn test_retry_payment_stays_failed_when_declined_again() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service_with_outcomes(vec![false, false]);
+        let user_id = Uuid::new_v4();
+
+        let transaction = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        let retried = rt.block_on(service.retry_payment(transaction.id)).unwrap();
+        assert_eq!(retried.status, TransactionStatus::Failed);
+    }
+
+    #[test]
+    fn test_concurrent_retries_charge_once() {
+        let rt = Runtime::new().unwrap();
+        let repository = Arc::new(MockTransactionRepository::new());
+        let service = DefaultTransactionService::new(
+            repository.clone(),
+            Arc::new(DefaultBalanceService::new(Arc::new(MockBalanceRepository::new()))),
+            Arc::new(ScriptedPaymentService::new(vec![false])),
+        );
+        let user_id = Uuid::new_v4();
+
+        let transaction = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        // Both retries see the transaction as Failed before either reopens it
+        repository.hold_reads(2);
+        let (first, second) = rt.block_on(async {
+            tokio::join!(service.retry_payment(transaction.id), service.retry_payment(transaction.id))
+        });
+
+        let (won, lost) = if first.is_ok() { (first, second) } else { (second, first) };
+        assert_eq!(won.unwrap().status, TransactionStatus::Success);
+        assert!(lost.unwrap_err().downcast_ref::<PaymentNotRetryable>().is_some());
+    }
+
+    #[test]
+    fn test_retry_payment_keeps_charged_transaction_out_of_failed() {
+        let rt = Runtime::new().unwrap();
+        let repository = Arc::new(MockTransactionRepository::new());
+        let service = DefaultTransactionService::new(
+            repository.clone(),
+            Arc::new(DefaultBalanceService::new(Arc::new(MockBalanceRepository::new()))),
+            Arc::new(ScriptedPaymentService::new(vec![false, true])),
+        );
+        let user_id = Uuid::new_v4();
+
+        let transaction = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        // The gateway approves the retry but the outcome is never stored
+        repository.fail_settles();
+        assert!(rt.block_on(service.retry_payment(transaction.id)).is_err());
+
+        let stored = rt.block_on(service.get_transaction(transaction.id)).unwrap().unwrap();
+        assert_eq!(stored.status, TransactionStatus::Pending);
+        let err = rt.block_on(service.retry_payment(transaction.id)).unwrap_err();
+        assert!(err.downcast_ref::<PaymentNotRetryable>().is_some());
+    }
+
+    #[test]
+    fn test_retry_payment_rejects_successful_transaction() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+        let user_id = Uuid::new_v4();
+
+        let transaction = checkout(&rt, &service, user_id).unwrap();
+        rt.block_on(service.process_payment(transaction.id, None)).unwrap();
+
+        let err = rt.block_on(service.retry_payment(transaction.id)).unwrap_err();
+        assert!(err.downcast_ref::<PaymentNotRetryable>().is_some());
+        let stored = rt.block_on(service.get_transaction(transaction.id)).unwrap().unwrap();
+        assert_eq!(stored.status, TransactionStatus::Success);
+    }
+
+    #[test]
+    fn test_balance_movements_use_standard_labels() {
+        let rt = Runtime::new().unwrap();
+        let service = create_transaction_service();
+        let user_id = Uuid::new_v4();
+
+        rt.block_on(service.add_funds_to_balance(user_id, 3000, " Bank_Transfer".to_string())).unwrap();
+        rt.block_on(service.withdraw_funds(user_id, 1000, "Cash out".to_string())).unwrap();
+
+        let history = rt
+            .block_on(service.get_user_transactions(user_id, &SortOrder::ascending("created_at")))
+            .unwrap();
+        let (topup, withdrawal) = (&history[0], &history[1]);
+
+        assert_eq!(topup.category, TransactionCategory::Topup);
+        assert_eq!(topup.payment_method, "bank_transfer");
+        assert_eq!(topup.description, TOPUP_DESCRIPTION);
+
+        assert_eq!(withdrawal.category, TransactionCategory::Withdrawal);
+        assert_eq!(withdrawal.payment_method, BALANCE_PAYMENT_METHOD);
+        assert!(withdrawal.is_balance_funded());
+    }
 }
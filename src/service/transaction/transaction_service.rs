@@ -1,12 +1,21 @@
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::error::Error;
+use std::fmt;
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::model::transaction::{Transaction, TransactionStatus};
-use crate::repository::transaction::transaction_repo::TransactionRepository;
+use crate::common::pagination::{Page, Pagination};
+use crate::common::sort::SortOrder;
+use crate::model::transaction::{
+    BALANCE_PAYMENT_METHOD, DEFAULT_PAYMENT_METHODS, normalize_payment_method, RefundPolicy, RefundReason, TOPUP_DESCRIPTION, Transaction, TransactionCategory, TransactionStatus,
+    TransactionStatusSnapshot,
+};
+use crate::repository::transaction::transaction_repo::{
+    TransactionFilter, TransactionRepository, TransactionStats,
+};
 use crate::service::transaction::balance_service::BalanceService;
+use crate::service::transaction::fee_schedule::FeeSchedule;
 use crate::service::transaction::payment_service::PaymentService;
 
 #[async_trait]
@@ -20,20 +29,47 @@ pub trait TransactionService {
         payment_method: String,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>>;
 
+    /// Pending transaction that can only be paid until `expires_at`
+    async fn create_payment_link(
+        &self,
+        user_id: Uuid,
+        ticket_id: Option<Uuid>,
+        amount: i64,
+        description: String,
+        payment_method: String,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>>;
+
+    /// Processing fee a checkout of `amount` with `payment_method` would be charged
+    fn quote_fee(&self, amount: i64, payment_method: &str) -> i64;
+
+    /// Normalized payment methods a checkout or top-up may name
+    fn payment_methods(&self) -> &[String];
+
     async fn process_payment(
         &self,
         transaction_id: Uuid,
         external_reference: Option<String>,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>>;
 
+    /// Charges a `Failed` transaction again; it ends `Success`, or `Failed`
+    /// if the gateway declines once more
+    async fn retry_payment(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>>;
+
     async fn validate_payment(
         &self,
         transaction_id: Uuid,
     ) -> Result<bool, Box<dyn Error + Send + Sync + 'static>>;
 
+    /// `refund_to` overrides the configured refund policy; `None` uses it
     async fn refund_transaction(
         &self,
         transaction_id: Uuid,
+        reason: RefundReason,
+        refund_to: Option<RefundPolicy>,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>>;
 
     async fn get_transaction(
@@ -41,11 +77,42 @@ pub trait TransactionService {
         transaction_id: Uuid,
     ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync + 'static>>;
 
+    async fn get_transaction_status(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync + 'static>>;
+
+    async fn get_transaction_by_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync + 'static>>;
+
+    async fn get_transactions_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync + 'static>>;
+
     async fn get_user_transactions(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync + 'static>>;
 
+    /// One page of the user's transactions matching `filter`
+    async fn get_user_transactions_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        pagination: &Pagination,
+    ) -> Result<Page<Transaction>, Box<dyn Error + Send + Sync + 'static>>;
+
+    /// Pending transactions of the user that can still be paid
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync + 'static>>;
+
     async fn add_funds_to_balance(
         &self,
         user_id: Uuid,
@@ -65,16 +132,65 @@ pub trait TransactionService {
         user_id: Uuid,
     ) -> Result<crate::model::transaction::Balance, Box<dyn Error + Send + Sync + 'static>>;
 
+    /// Pending count and ticket revenue across all users
+    async fn get_transaction_stats(
+        &self,
+    ) -> Result<TransactionStats, Box<dyn Error + Send + Sync + 'static>>;
+
     async fn delete_transaction(
         &self,
         transaction_id: Uuid,
     ) -> Result<(), Box<dyn Error + Send + Sync + 'static>>;
 }
 
+/// Returned when a user already holds the configured number of unpaid checkouts
+#[derive(Debug, PartialEq)]
+pub struct TooManyPendingCheckouts;
+
+impl fmt::Display for TooManyPendingCheckouts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Too many pending checkouts")
+    }
+}
+
+impl Error for TooManyPendingCheckouts {}
+
+/// Returned when asked to retry a transaction that didn't fail
+#[derive(Debug, PartialEq)]
+pub struct PaymentNotRetryable;
+
+impl fmt::Display for PaymentNotRetryable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Only failed transactions can be retried")
+    }
+}
+
+impl Error for PaymentNotRetryable {}
+
+/// Where a payment attempt stopped, so a retry only reopens attempts that
+/// never charged anything
+enum PaymentAttemptError {
+    NotCharged(Box<dyn Error + Send + Sync + 'static>),
+    /// Charged, but the outcome could not be stored
+    Unsettled(Box<dyn Error + Send + Sync + 'static>),
+}
+
+impl PaymentAttemptError {
+    fn into_inner(self) -> Box<dyn Error + Send + Sync + 'static> {
+        match self {
+            PaymentAttemptError::NotCharged(e) | PaymentAttemptError::Unsettled(e) => e,
+        }
+    }
+}
+
 pub struct DefaultTransactionService {
     transaction_repository: Arc<dyn TransactionRepository + Send + Sync>,
     balance_service: Arc<dyn BalanceService + Send + Sync>,
     payment_service: Arc<dyn PaymentService + Send + Sync>,
+    fee_schedule: FeeSchedule,
+    max_concurrent_checkouts: Option<usize>,
+    refund_policy: RefundPolicy,
+    payment_methods: Vec<String>,
 }
 
 impl DefaultTransactionService {
@@ -87,57 +203,104 @@ impl DefaultTransactionService {
             transaction_repository,
             balance_service,
             payment_service,
+            fee_schedule: FeeSchedule::new(),
+            max_concurrent_checkouts: None,
+            refund_policy: RefundPolicy::default(),
+            payment_methods: DEFAULT_PAYMENT_METHODS.iter().map(|m| m.to_string()).collect(),
         }
     }
-}
 
-#[async_trait]
-impl TransactionService for DefaultTransactionService {
-    async fn create_transaction(
+    pub fn with_fee_schedule(mut self, fee_schedule: FeeSchedule) -> Self {
+        self.fee_schedule = fee_schedule;
+        self
+    }
+
+    pub fn with_max_concurrent_checkouts(mut self, max_checkouts: usize) -> Self {
+        self.max_concurrent_checkouts = Some(max_checkouts);
+        self
+    }
+
+    pub fn with_refund_policy(mut self, refund_policy: RefundPolicy) -> Self {
+        self.refund_policy = refund_policy;
+        self
+    }
+
+    pub fn with_payment_methods(mut self, payment_methods: Vec<String>) -> Self {
+        self.payment_methods = payment_methods
+            .iter()
+            .map(|method| normalize_payment_method(method))
+            .collect();
+        self
+    }
+
+    /// Keeps a settled record of a balance change so it shows up in the
+    /// user's history and in category reports
+    async fn record_balance_movement(
         &self,
         user_id: Uuid,
-        ticket_id: Option<Uuid>,
         amount: i64,
+        category: TransactionCategory,
         description: String,
         payment_method: String,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
-        if amount <= 0 {
-            return Err("Transaction amount must be positive".into());
-        }
-
-        let transaction = Transaction::new(user_id, ticket_id, amount, description, payment_method);
-
+        let mut transaction = Transaction::new(user_id, None, amount, description, payment_method);
+        transaction.category = category;
+        transaction.status = TransactionStatus::Success;
         self.transaction_repository.save(&transaction).await
     }
 
-    async fn process_payment(
+    async fn attempt_payment(
         &self,
         transaction_id: Uuid,
         external_reference: Option<String>,
-    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+    ) -> Result<Transaction, PaymentAttemptError> {
+        use PaymentAttemptError::{NotCharged, Unsettled};
+
         let transaction = match self
             .transaction_repository
             .find_by_id(transaction_id)
-            .await?
+            .await
+            .map_err(NotCharged)?
         {
             Some(t) => t,
-            None => return Err("Transaction not found".into()),
+            None => return Err(NotCharged("Transaction not found".into())),
         };
 
         if transaction.is_finalized() {
-            return Err("Transaction is already finalized".into());
+            return Err(NotCharged("Transaction is already finalized".into()));
+        }
+
+        if transaction.is_expired(Utc::now()) {
+            self.transaction_repository
+                .update_status(transaction_id, TransactionStatus::Failed)
+                .await
+                .map_err(NotCharged)?;
+            return Err(NotCharged("Payment window expired".into()));
         }
 
         if let Some(ref_id) = external_reference {
-            let mut updated = self
+            return self
                 .transaction_repository
-                .update_status(transaction_id, TransactionStatus::Success)
-                .await?;
-            updated.external_reference = Some(ref_id);
-            return self.transaction_repository.save(&updated).await;
+                .settle_pending(transaction_id, TransactionStatus::Success, Some(&ref_id))
+                .await
+                .map_err(Unsettled)?
+                .ok_or_else(|| Unsettled("Transaction is already finalized".into()));
+        }
+
+        // Debit and settlement commit together, so a failure charged nothing
+        if transaction.is_balance_funded() {
+            return self
+                .transaction_repository
+                .pay_from_balance(transaction_id)
+                .await
+                .map_err(NotCharged);
         }
 
-        let (success, reference) = self.payment_service.process_payment(&transaction).await?;
+        let (success, reference) = self
+            .payment_service
+            .process_payment(&transaction)
+            .await
+            .map_err(NotCharged)?;
 
         let status = if success {
             TransactionStatus::Success
@@ -145,14 +308,133 @@ impl TransactionService for DefaultTransactionService {
             TransactionStatus::Failed
         };
 
-        let mut updated_transaction = self
+        self.transaction_repository
+            .settle_pending(transaction_id, status, reference.as_deref())
+            .await
+            .map_err(Unsettled)?
+            .ok_or_else(|| Unsettled("Transaction is already finalized".into()))
+    }
+
+    /// Saves a new checkout, refusing it with `TooManyPendingCheckouts` when
+    /// the user already holds the configured number of live pending ones
+    async fn save_checkout(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+        let Some(max_checkouts) = self.max_concurrent_checkouts else {
+            return self.transaction_repository.save(transaction).await;
+        };
+
+        match self
             .transaction_repository
-            .update_status(transaction_id, status)
-            .await?;
-        updated_transaction.external_reference = reference;
-        updated_transaction.updated_at = Utc::now();
+            .save_within_pending_cap(transaction, max_checkouts)
+            .await?
+        {
+            Some(saved) => Ok(saved),
+            None => Err(Box::new(TooManyPendingCheckouts)),
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionService for DefaultTransactionService {
+    async fn create_transaction(
+        &self,
+        user_id: Uuid,
+        ticket_id: Option<Uuid>,
+        amount: i64,
+        description: String,
+        payment_method: String,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+        if amount <= 0 {
+            return Err("Transaction amount must be positive".into());
+        }
+
+        let payment_method = normalize_payment_method(&payment_method);
+        let mut transaction = Transaction::new(user_id, ticket_id, amount, description, payment_method);
+        transaction.fee_amount = self.quote_fee(amount, &transaction.payment_method);
+
+        self.save_checkout(&transaction).await
+    }
+
+    async fn create_payment_link(
+        &self,
+        user_id: Uuid,
+        ticket_id: Option<Uuid>,
+        amount: i64,
+        description: String,
+        payment_method: String,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+        if amount <= 0 {
+            return Err("Transaction amount must be positive".into());
+        }
+        if expires_at <= Utc::now() {
+            return Err("Payment link expiry must be in the future".into());
+        }
+
+        let payment_method = normalize_payment_method(&payment_method);
+        let mut transaction = Transaction::new(user_id, ticket_id, amount, description, payment_method);
+        transaction.fee_amount = self.quote_fee(amount, &transaction.payment_method);
+        transaction.expires_at = Some(expires_at);
+
+        self.save_checkout(&transaction).await
+    }
 
-        self.transaction_repository.save(&updated_transaction).await
+    fn quote_fee(&self, amount: i64, payment_method: &str) -> i64 {
+        self.fee_schedule.fee_for(payment_method, amount)
+    }
+
+    fn payment_methods(&self) -> &[String] {
+        &self.payment_methods
+    }
+
+    async fn process_payment(
+        &self,
+        transaction_id: Uuid,
+        external_reference: Option<String>,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+        self.attempt_payment(transaction_id, external_reference)
+            .await
+            .map_err(PaymentAttemptError::into_inner)
+    }
+
+    async fn retry_payment(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
+        if self
+            .transaction_repository
+            .find_by_id(transaction_id)
+            .await?
+            .is_none()
+        {
+            return Err("Transaction not found".into());
+        }
+
+        // Only one of two concurrent retries can move it out of Failed, so
+        // the other never reaches the charge.
+        if self
+            .transaction_repository
+            .reopen_failed(transaction_id)
+            .await?
+            .is_none()
+        {
+            return Err(Box::new(PaymentNotRetryable));
+        }
+
+        match self.attempt_payment(transaction_id, None).await {
+            Ok(processed) => Ok(processed),
+            Err(PaymentAttemptError::NotCharged(e)) => {
+                self.transaction_repository
+                    .settle_pending(transaction_id, TransactionStatus::Failed, None)
+                    .await?;
+                Err(e)
+            }
+            // The money may have moved, so the row stays as it is for
+            // reconciliation rather than becoming retryable again
+            Err(PaymentAttemptError::Unsettled(e)) => Err(e),
+        }
     }
 
     async fn validate_payment(
@@ -174,6 +456,8 @@ impl TransactionService for DefaultTransactionService {
     async fn refund_transaction(
         &self,
         transaction_id: Uuid,
+        reason: RefundReason,
+        refund_to: Option<RefundPolicy>,
     ) -> Result<Transaction, Box<dyn Error + Send + Sync + 'static>> {
         let mut transaction = match self
             .transaction_repository
@@ -185,12 +469,37 @@ impl TransactionService for DefaultTransactionService {
         };
 
         transaction
-            .refund()
+            .refund(reason.clone())
             .map_err(|e| -> Box<dyn Error + Send + Sync + 'static> { e.into() })?;
 
-        self.transaction_repository
-            .update_status(transaction_id, TransactionStatus::Refunded)
-            .await
+        // Only succeeds while the row is still `Success`, so of two concurrent
+        // refunds exactly one gets past here and moves the money.
+        let refunded = self
+            .transaction_repository
+            .mark_refunded(transaction_id, &reason)
+            .await?;
+
+        // The refund always goes back to the payer recorded on the transaction,
+        // even if the ticket it bought has since been transferred to someone else.
+        let to_balance = refunded.is_balance_funded()
+            || refund_to.unwrap_or(self.refund_policy) == RefundPolicy::Balance;
+        let result = if to_balance {
+            self.balance_service
+                .add_funds(refunded.user_id, refunded.total_charged())
+                .await
+                .map(|_| ())
+        } else {
+            self.payment_service.refund_payment(&refunded).await
+        };
+
+        if let Err(e) = result {
+            self.transaction_repository
+                .update_status(transaction_id, TransactionStatus::Success)
+                .await?;
+            return Err(e);
+        }
+
+        Ok(refunded)
     }
 
     async fn get_transaction(
@@ -200,12 +509,56 @@ impl TransactionService for DefaultTransactionService {
         self.transaction_repository.find_by_id(transaction_id).await
     }
 
+    async fn get_transaction_status(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Option<TransactionStatusSnapshot>, Box<dyn Error + Send + Sync + 'static>> {
+        self.transaction_repository.find_status(transaction_id).await
+    }
+
+    async fn get_transaction_by_reference(
+        &self,
+        reference: &str,
+    ) -> Result<Option<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
+        self.transaction_repository.find_by_external_reference(reference).await
+    }
+
+    async fn get_transactions_by_ids(
+        &self,
+        ids: &[Uuid],
+    ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
+        self.transaction_repository.find_by_ids(ids).await
+    }
+
     async fn get_user_transactions(
         &self,
         user_id: Uuid,
+        sort: &SortOrder,
     ) -> Result<Vec<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
-        self.transaction_repository.find_by_user(user_id).await
+        self.transaction_repository.find_by_user(user_id, sort).await
     }
+
+    async fn get_user_transactions_paged(
+        &self,
+        user_id: Uuid,
+        filter: &TransactionFilter,
+        sort: &SortOrder,
+        pagination: &Pagination,
+    ) -> Result<Page<Transaction>, Box<dyn Error + Send + Sync + 'static>> {
+        let (transactions, total) = self
+            .transaction_repository
+            .find_by_user_paged(user_id, filter, sort, pagination.per_page(), pagination.offset())
+            .await?;
+        Ok(pagination.page_of(transactions, total))
+    }
+
+    async fn count_active_pending(
+        &self,
+        user_id: Uuid,
+    ) -> Result<usize, Box<dyn Error + Send + Sync + 'static>> {
+        self.transaction_repository.count_active_pending(user_id).await
+    }
+
     async fn add_funds_to_balance(
         &self,
         user_id: Uuid,
@@ -217,6 +570,14 @@ impl TransactionService for DefaultTransactionService {
         }
 
         let new_balance = self.balance_service.add_funds(user_id, amount).await?;
+        self.record_balance_movement(
+            user_id,
+            amount,
+            TransactionCategory::Topup,
+            TOPUP_DESCRIPTION.to_string(),
+            normalize_payment_method(&payment_method),
+        )
+        .await?;
 
         Ok(new_balance)
     }
@@ -237,6 +598,14 @@ impl TransactionService for DefaultTransactionService {
         }
 
         let new_balance = self.balance_service.withdraw_funds(user_id, amount).await?;
+        self.record_balance_movement(
+            user_id,
+            amount,
+            TransactionCategory::Withdrawal,
+            description,
+            BALANCE_PAYMENT_METHOD.to_string(),
+        )
+        .await?;
 
         Ok(new_balance)
     }
@@ -247,6 +616,12 @@ impl TransactionService for DefaultTransactionService {
         self.balance_service.get_or_create_balance(user_id).await
     }
 
+    async fn get_transaction_stats(
+        &self,
+    ) -> Result<TransactionStats, Box<dyn Error + Send + Sync + 'static>> {
+        self.transaction_repository.stats().await
+    }
+
     async fn delete_transaction(
         &self,
         transaction_id: Uuid,